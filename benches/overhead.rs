@@ -0,0 +1,120 @@
+//! Measures the framework overhead per node on graphs of a million tiny
+//! nodes, failing if it exceeds the budget.
+//!
+//! Run with `cargo bench --bench overhead`.
+
+use dag_compute::{ComputationGraph, NodeHandle, SchedulingStrategy};
+
+use std::time::{Duration, Instant};
+
+const NODE_COUNT: usize = 1_000_000;
+// Framework overhead budget per node, including planning
+const BUDGET_PER_NODE: Duration = Duration::from_nanos(300);
+const SAMPLES: usize = 5;
+
+// Each node adds the values of the two nodes before it, either through a
+// boxed closure or a plain function pointer
+fn build_graph(boxed: bool) -> ComputationGraph<u64> {
+    let mut graph = ComputationGraph::new();
+    let mut prev: NodeHandle = graph.insert_node("node0".to_owned(), Box::new(|_| 1));
+    let mut prev_prev = graph.insert_node("node1".to_owned(), Box::new(|_| 1));
+    for i in 2..NODE_COUNT {
+        let name = format!("node{}", i);
+        let mut next = if boxed {
+            graph.insert_node(name, Box::new(|x: &[&u64]| x[0].wrapping_add(*x[1])))
+        } else {
+            graph.insert_binary_node(name, |a, b| a.wrapping_add(*b))
+        };
+        graph.set_inputs(&mut next, &[&prev, &prev_prev]);
+        prev_prev = prev;
+        prev = next;
+    }
+    graph.designate_output(&prev);
+    graph
+}
+
+// Each node increments the value of the node before it
+fn build_chain() -> ComputationGraph<u64> {
+    let mut graph = ComputationGraph::new();
+    let mut prev: NodeHandle = graph.insert_node("node0".to_owned(), Box::new(|_| 0));
+    for i in 1..NODE_COUNT {
+        let mut next = graph.insert_unary_node(format!("node{}", i), |x| x + 1);
+        graph.set_inputs(&mut next, &[&prev]);
+        prev = next;
+    }
+    graph.designate_output(&prev);
+    graph
+}
+
+// A single value consumed by every other node but the last, which sums them
+fn build_fan_out() -> ComputationGraph<u64> {
+    let mut graph = ComputationGraph::new();
+    let root: NodeHandle = graph.insert_node("root".to_owned(), Box::new(|_| 1));
+    let consumers: Vec<NodeHandle> = (2..NODE_COUNT).map(|i| {
+        let mut consumer = graph.insert_unary_node(format!("node{}", i), |x| x + 1);
+        graph.set_inputs(&mut consumer, &[&root]);
+        consumer
+    }).collect();
+    let mut sum = graph.insert_node("sum".to_owned(),
+        Box::new(|x: &[&u64]| x.iter().copied().sum()));
+    graph.set_inputs(&mut sum, &consumers.iter().collect::<Vec<_>>());
+    graph.designate_output(&sum);
+    graph
+}
+
+// Takes the fastest of several samples to filter out scheduling noise
+fn measure(name: &str, mut run: impl FnMut() -> Duration) -> bool {
+    let best = (0..SAMPLES).map(|_| run()).min().unwrap();
+    let per_node = best / NODE_COUNT as u32;
+    let within_budget = per_node <= BUDGET_PER_NODE;
+    println!("{:<24} {:>8.1} ms total, {:>5} ns/node{}", name,
+        best.as_secs_f64() * 1000.0, per_node.as_nanos(),
+        if within_budget { "" } else { "  OVER BUDGET" });
+    within_budget
+}
+
+fn main() {
+    // Ignore the arguments that cargo bench passes to every bench target
+    let mut all_within_budget = true;
+    all_within_budget &= measure("compute", || {
+        let graph = build_graph(true);
+        let start = Instant::now();
+        std::hint::black_box(graph.compute());
+        start.elapsed()
+    });
+    all_within_budget &= measure("compute (fn pointers)", || {
+        let graph = build_graph(false);
+        let start = Instant::now();
+        std::hint::black_box(graph.compute());
+        start.elapsed()
+    });
+    all_within_budget &= measure("compute (chain)", || {
+        let graph = build_chain();
+        let start = Instant::now();
+        std::hint::black_box(graph.compute());
+        start.elapsed()
+    });
+    all_within_budget &= measure("compute (locality)", || {
+        let mut graph = build_fan_out();
+        graph.set_scheduling_strategy(SchedulingStrategy::Locality);
+        let start = Instant::now();
+        std::hint::black_box(graph.compute());
+        start.elapsed()
+    });
+    let graph = build_graph(true);
+    all_within_budget &= measure("compute_iterations(1)", || {
+        let start = Instant::now();
+        std::hint::black_box(graph.compute_iterations(1));
+        start.elapsed()
+    });
+    all_within_budget &= measure("compute_monte_carlo(1)", || {
+        let start = Instant::now();
+        std::hint::black_box(graph.compute_monte_carlo(1, 0, 1));
+        start.elapsed()
+    });
+    if !all_within_budget {
+        eprintln!("Per-node overhead exceeds the budget of {} ns",
+            BUDGET_PER_NODE.as_nanos());
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,138 @@
+#![forbid(unsafe_code)]
+#![doc(html_root_url = "https://docs.rs/dag_compute_derive/0.1.0")]
+
+//! Derive macros for `dag_compute`. Use these through the `derive` feature
+//! of `dag_compute` rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields,
+    GenericParam, Lifetime, LitStr, Type};
+
+/// Derives `dag_compute::ComputeNode` for a struct of `&T` input fields.
+///
+/// See the documentation of the `ComputeNode` trait for details.
+#[proc_macro_derive(ComputeNode, attributes(compute_node))]
+pub fn derive_compute_node(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand_compute_node(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into()
+    }
+}
+
+fn expand_compute_node(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let struct_name = &input.ident;
+
+    let mut node_name: Option<LitStr> = None;
+    let mut value_type: Option<Type> = None;
+    for attr in input.attrs.iter() {
+        if !attr.path().is_ident("compute_node") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                node_name = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("value") {
+                value_type = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `name` or `value`"))
+            }
+        })?;
+    }
+
+    let data = match input.data {
+        Data::Struct(ref data) => data,
+        _ => return Err(syn::Error::new_spanned(struct_name,
+            "ComputeNode can only be derived for structs"))
+    };
+
+    // Inputs are filled in positionally, so collect each field's accessor
+    let mut field_types = Vec::new();
+    let construct = match data.fields {
+        Fields::Named(ref fields) => {
+            let inits = fields.named.iter().enumerate().map(|(i, field)| {
+                let ident = field.ident.as_ref().unwrap();
+                quote!(#ident: inputs[#i])
+            });
+            field_types.extend(fields.named.iter().map(|f| &f.ty));
+            quote!(#struct_name { #(#inits),* })
+        },
+        Fields::Unnamed(ref fields) => {
+            let inits = (0..fields.unnamed.len()).map(|i| quote!(inputs[#i]));
+            field_types.extend(fields.unnamed.iter().map(|f| &f.ty));
+            quote!(#struct_name ( #(#inits),* ))
+        },
+        Fields::Unit => quote!(#struct_name)
+    };
+    let arity = field_types.len();
+
+    let value_type = match value_type {
+        Some(ty) => ty,
+        None => match field_types.first() {
+            Some(Type::Reference(ref_type)) => (*ref_type.elem).clone(),
+            Some(other) => return Err(syn::Error::new_spanned(other,
+                "ComputeNode input fields must be references to the graph value type")),
+            None => return Err(syn::Error::new_spanned(struct_name,
+                "structs without fields need #[compute_node(value = Type)]"))
+        }
+    };
+
+    let node_name = node_name.unwrap_or_else(|| LitStr::new(
+        &to_snake_case(&struct_name.to_string()), Span::call_site()));
+
+    // Input references only live for the duration of the call, so implement
+    // the trait on the 'static instantiation and let each call infer its own
+    let mut type_args = Vec::new();
+    for param in input.generics.params.iter() {
+        match param {
+            GenericParam::Lifetime(_) => {
+                type_args.push(Lifetime::new("'static", Span::call_site()));
+            },
+            _ => return Err(syn::Error::new_spanned(param,
+                "ComputeNode cannot be derived for structs with type or const parameters"))
+        }
+    }
+    let self_type: Type = if type_args.is_empty() {
+        parse_quote!(#struct_name)
+    } else {
+        parse_quote!(#struct_name<#(#type_args),*>)
+    };
+
+    Ok(quote! {
+        impl ::dag_compute::ComputeNode<#value_type> for #self_type {
+            const ARITY: usize = #arity;
+            fn node_name() -> ::std::string::String {
+                ::std::string::String::from(#node_name)
+            }
+            fn eval_inputs(inputs: &[&#value_type]) -> #value_type {
+                let node = #construct;
+                node.eval()
+            }
+        }
+    })
+}
+
+// Runs of capitals form a single word, ending before the capital that
+// starts the next word, so that `HTTPServer` becomes `http_server`
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len());
+    for (i, c) in chars.iter().copied().enumerate() {
+        if c.is_uppercase() {
+            let after_word = i > 0 && !chars[i - 1].is_uppercase();
+            let starts_word = i > 0 && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_word || starts_word {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
@@ -0,0 +1,182 @@
+use crate::{ComputationGraph, NodeHandle};
+
+use slotmap::SecondaryMap;
+
+use std::any::{Any, type_name};
+use std::error::Error;
+use std::fmt;
+
+/// A type-erased value for graphs whose nodes produce heterogeneous outputs.
+///
+/// Unlike a bare `Box<dyn Any>`, this remembers the name of the stored type
+/// so that failed downcasts can report what was actually present.
+pub struct AnyValue {
+    value: Box<dyn Any + Send + Sync>,
+    type_name: &'static str
+}
+impl AnyValue {
+    /// Wraps the given value.
+    pub fn new<U: Any + Send + Sync>(value: U) -> AnyValue {
+        AnyValue {
+            value: Box::new(value),
+            type_name: type_name::<U>()
+        }
+    }
+    /// Returns the name of the stored type.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+    /// Returns `true` if the stored value is of type `U`.
+    pub fn is<U: Any>(&self) -> bool {
+        self.value.is::<U>()
+    }
+    /// Returns a reference to the stored value if it is of type `U`.
+    pub fn downcast_ref<U: Any>(&self) -> Option<&U> {
+        self.value.downcast_ref::<U>()
+    }
+    /// Returns a reference to the stored value, which must be of type `U`.
+    ///
+    /// This is meant for reading the inputs of nodes, whose wiring already
+    /// determines their types. Panics naming both types if the stored value
+    /// is of another type; use
+    /// [`ComputationGraph::value_of`] to read the value of a given node with
+    /// errors naming the node.
+    pub fn value<U: Any>(&self) -> &U {
+        match self.value.downcast_ref::<U>() {
+            Some(val) => val,
+            None => panic!("Expected a value of type {} but found {}",
+                type_name::<U>(), self.type_name)
+        }
+    }
+    /// Returns the stored value if it is of type `U`.
+    ///
+    /// On failure the original `AnyValue` is handed back unchanged.
+    pub fn downcast<U: Any>(self) -> Result<U, AnyValue> {
+        let type_name = self.type_name;
+        match self.value.downcast::<U>() {
+            Ok(val) => Ok(*val),
+            Err(value) => Err(AnyValue { value, type_name })
+        }
+    }
+}
+impl fmt::Debug for AnyValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AnyValue {{ type_name: {:?}, value: ... }}", self.type_name)
+    }
+}
+
+/// The error returned when a node's value is not of the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DowncastError {
+    node_name: String,
+    expected: &'static str,
+    actual: &'static str
+}
+impl DowncastError {
+    /// Returns the name of the node whose value failed to downcast.
+    pub fn node_name(&self) -> &str {
+        &self.node_name
+    }
+    /// Returns the name of the requested type.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+    /// Returns the name of the type that was actually stored.
+    pub fn actual(&self) -> &'static str {
+        self.actual
+    }
+}
+impl fmt::Display for DowncastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "node {:?} produced a value of type {} but {} was expected",
+            self.node_name, self.actual, self.expected)
+    }
+}
+impl Error for DowncastError {}
+
+impl ComputationGraph<AnyValue> {
+    /// Computes the value of the output node and downcasts it to `U`.
+    pub fn compute_as<U: Any>(self) -> Result<U, DowncastError> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        let node_name = self.node_storage.get(out_key).unwrap().name.to_string();
+        self.compute().downcast::<U>().map_err(|val| DowncastError {
+            node_name,
+            expected: type_name::<U>(),
+            actual: val.type_name()
+        })
+    }
+    /// Computes the value of the given node, without consuming the graph,
+    /// and downcasts it to `U`.
+    ///
+    /// Only the node and its ancestors are evaluated, along with the sinks,
+    /// so this can check the type produced by any node of the graph.
+    pub fn value_of<U: Any>(&self, node: &NodeHandle) -> Result<U, DowncastError> {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let order = self.evaluation_order(node.node_key);
+        let refcounts = self.order_refcounts(&order, node.node_key);
+        let mut values = self.execute_order(&order, Some(refcounts), SecondaryMap::new(),
+            &self.run_context(0));
+        values.remove(node.node_key).unwrap().downcast::<U>().map_err(|val| DowncastError {
+            node_name: self.node_name(node).to_owned(),
+            expected: type_name::<U>(),
+            actual: val.type_name()
+        })
+    }
+    /// Inserts a node converting the given node's value from `U` to `V`.
+    ///
+    /// The returned handle can be used as an input wherever a `V` is
+    /// expected, avoiding hand-written glue nodes. The conversion node panics
+    /// at computation time if the producer's value is not a `U`.
+    pub fn wire_with_into<U, V>(&mut self, producer: &NodeHandle) -> NodeHandle
+    where
+        U: Any + Clone + Into<V>,
+        V: Any + Send + Sync
+    {
+        let producer_name = self.node_name(producer).to_owned();
+        let name = format!("{} (into {})", producer_name, type_name::<V>());
+        let mut handle = self.insert_node(name, Box::new(move |inputs| {
+            match inputs[0].downcast_ref::<U>() {
+                Some(val) => AnyValue::new::<V>(val.clone().into()),
+                None => panic!("{}",
+                    DowncastError {
+                        node_name: producer_name.clone(),
+                        expected: type_name::<U>(),
+                        actual: inputs[0].type_name()
+                    })
+            }
+        }));
+        self.set_inputs(&mut handle, &[producer]);
+        handle
+    }
+    /// Inserts a node attempting to convert the given node's value from `U`
+    /// to `V`.
+    ///
+    /// Like [`wire_with_into`](Self::wire_with_into), but for fallible or
+    /// lossy conversions. The value of the returned node is the
+    /// `Result<V, E>` of the conversion, so that consumers can handle a
+    /// failed conversion instead of the conversion node panicking. The
+    /// conversion node still panics if the producer's value is not a `U`.
+    pub fn wire_with_try_into<U, V>(&mut self, producer: &NodeHandle) -> NodeHandle
+    where
+        U: Any + Clone + TryInto<V>,
+        U::Error: Any + Send + Sync,
+        V: Any + Send + Sync
+    {
+        let producer_name = self.node_name(producer).to_owned();
+        let name = format!("{} (try into {})", producer_name, type_name::<V>());
+        let mut handle = self.insert_node(name, Box::new(move |inputs| {
+            match inputs[0].downcast_ref::<U>() {
+                Some(val) => AnyValue::new::<Result<V, U::Error>>(val.clone().try_into()),
+                None => panic!("{}",
+                    DowncastError {
+                        node_name: producer_name.clone(),
+                        expected: type_name::<U>(),
+                        actual: inputs[0].type_name()
+                    })
+            }
+        }));
+        self.set_inputs(&mut handle, &[producer]);
+        handle
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle};
+
+use std::error::Error;
+use std::fmt;
+
+use ndarray::{ArrayD, Axis, Ix2, IxDyn, LinalgScalar};
+use slotmap::SecondaryMap;
+
+/// The error returned when wiring array nodes with incompatible shapes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeError {
+    node_name: String,
+    message: String
+}
+impl ShapeError {
+    /// Returns the name of the node that could not be inserted.
+    pub fn node_name(&self) -> &str {
+        &self.node_name
+    }
+}
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot insert node {:?}: {}", self.node_name, self.message)
+    }
+}
+impl Error for ShapeError {}
+
+/// A builder for graphs of `ndarray` arrays that checks shapes as nodes are
+/// wired together.
+///
+/// Each node's output shape is tracked, so incompatible operands are
+/// reported when inserting a node rather than when computing the graph. The
+/// underlying graph can be accessed at any time for other operations.
+#[derive(Debug)]
+pub struct ArrayGraphBuilder<A> {
+    graph: ComputationGraph<ArrayD<A>>,
+    shapes: SecondaryMap<ComputeGraphKey, Vec<usize>>
+}
+impl<A> Default for ArrayGraphBuilder<A> {
+    fn default() -> Self {
+        ArrayGraphBuilder {
+            graph: ComputationGraph::new(),
+            shapes: SecondaryMap::new()
+        }
+    }
+}
+impl<A: LinalgScalar + Send + Sync> ArrayGraphBuilder<A> {
+    pub fn new() -> ArrayGraphBuilder<A> {
+        ArrayGraphBuilder::default()
+    }
+    /// Returns a reference to the underlying graph.
+    pub fn graph(&self) -> &ComputationGraph<ArrayD<A>> {
+        &self.graph
+    }
+    /// Returns a mutable reference to the underlying graph.
+    ///
+    /// Nodes inserted directly into the graph have no known shape and
+    /// cannot be used as operands of the builder's nodes.
+    pub fn graph_mut(&mut self) -> &mut ComputationGraph<ArrayD<A>> {
+        &mut self.graph
+    }
+    /// Returns the underlying graph.
+    pub fn into_graph(self) -> ComputationGraph<ArrayD<A>> {
+        self.graph
+    }
+    /// Returns the output shape of a node inserted through the builder.
+    pub fn shape(&self, node: &NodeHandle) -> &[usize] {
+        self.operand_shape(node).expect("Node has no known shape")
+    }
+
+    /// Inserts a placeholder that must be fed arrays of the given shape.
+    pub fn input(&mut self, name: String, shape: &[usize]) -> NodeHandle {
+        let handle = self.graph.insert_placeholder(name);
+        self.shapes.insert(handle.node_key, shape.to_vec());
+        handle
+    }
+    /// Inserts a node producing a constant array.
+    pub fn constant(&mut self, name: String, value: ArrayD<A>) -> NodeHandle {
+        let shape = value.shape().to_vec();
+        let handle = self.graph.insert_node(name, Box::new(move |_| value.clone()));
+        self.shapes.insert(handle.node_key, shape);
+        handle
+    }
+    /// Inserts a node applying `func` to each element.
+    pub fn map(&mut self, name: String, operand: &NodeHandle,
+            func: impl Fn(A) -> A + Send + Sync + 'static) -> Result<NodeHandle, ShapeError> {
+        let shape = self.known_shape(&name, operand)?.to_vec();
+        Ok(self.insert_with_shape(name, shape, &[operand],
+            move |x| x[0].mapv(&func)))
+    }
+    /// Inserts a node adding two arrays of the same shape elementwise.
+    pub fn add(&mut self, name: String, lhs: &NodeHandle, rhs: &NodeHandle)
+            -> Result<NodeHandle, ShapeError> {
+        self.elementwise(name, lhs, rhs, |a, b| a + b)
+    }
+    /// Inserts a node subtracting two arrays of the same shape elementwise.
+    pub fn sub(&mut self, name: String, lhs: &NodeHandle, rhs: &NodeHandle)
+            -> Result<NodeHandle, ShapeError> {
+        self.elementwise(name, lhs, rhs, |a, b| a - b)
+    }
+    /// Inserts a node multiplying two arrays of the same shape elementwise.
+    pub fn mul(&mut self, name: String, lhs: &NodeHandle, rhs: &NodeHandle)
+            -> Result<NodeHandle, ShapeError> {
+        self.elementwise(name, lhs, rhs, |a, b| a * b)
+    }
+    /// Inserts a node dividing two arrays of the same shape elementwise.
+    pub fn div(&mut self, name: String, lhs: &NodeHandle, rhs: &NodeHandle)
+            -> Result<NodeHandle, ShapeError> {
+        self.elementwise(name, lhs, rhs, |a, b| a / b)
+    }
+    /// Inserts a node computing the matrix product of two 2-D arrays.
+    pub fn matmul(&mut self, name: String, lhs: &NodeHandle, rhs: &NodeHandle)
+            -> Result<NodeHandle, ShapeError> {
+        let lhs_shape = self.known_shape(&name, lhs)?;
+        let rhs_shape = self.known_shape(&name, rhs)?;
+        if lhs_shape.len() != 2 || rhs_shape.len() != 2 || lhs_shape[1] != rhs_shape[0] {
+            return Err(ShapeError {
+                message: format!("cannot multiply matrices of shapes {:?} and {:?}",
+                    lhs_shape, rhs_shape),
+                node_name: name
+            });
+        }
+        let shape = vec![lhs_shape[0], rhs_shape[1]];
+        Ok(self.insert_with_shape(name, shape, &[lhs, rhs], |x| {
+            let lhs = x[0].view().into_dimensionality::<Ix2>().unwrap();
+            let rhs = x[1].view().into_dimensionality::<Ix2>().unwrap();
+            lhs.dot(&rhs).into_dyn()
+        }))
+    }
+    /// Inserts a node summing all elements into a 0-dimensional array.
+    pub fn sum(&mut self, name: String, operand: &NodeHandle)
+            -> Result<NodeHandle, ShapeError> {
+        self.known_shape(&name, operand)?;
+        Ok(self.insert_with_shape(name, Vec::new(), &[operand],
+            |x| ArrayD::from_elem(IxDyn(&[]), x[0].sum())))
+    }
+    /// Inserts a node summing along the given axis, removing that axis.
+    pub fn sum_axis(&mut self, name: String, operand: &NodeHandle, axis: usize)
+            -> Result<NodeHandle, ShapeError> {
+        let mut shape = self.known_shape(&name, operand)?.to_vec();
+        if axis >= shape.len() {
+            return Err(ShapeError {
+                message: format!("axis {} is out of bounds for shape {:?}", axis, shape),
+                node_name: name
+            });
+        }
+        shape.remove(axis);
+        Ok(self.insert_with_shape(name, shape, &[operand],
+            move |x| x[0].sum_axis(Axis(axis))))
+    }
+
+    fn operand_shape(&self, node: &NodeHandle) -> Option<&[usize]> {
+        assert_eq!(node.graph_id, self.graph.graph_id,
+            "Received NodeHandle for different graph");
+        self.shapes.get(node.node_key).map(Vec::as_slice)
+    }
+    fn known_shape(&self, name: &str, node: &NodeHandle) -> Result<&[usize], ShapeError> {
+        self.operand_shape(node).ok_or_else(|| ShapeError {
+            node_name: name.to_owned(),
+            message: format!("operand {:?} has no known shape", self.graph.node_name(node))
+        })
+    }
+    fn elementwise(&mut self, name: String, lhs: &NodeHandle, rhs: &NodeHandle,
+            func: fn(A, A) -> A) -> Result<NodeHandle, ShapeError> {
+        let lhs_shape = self.known_shape(&name, lhs)?;
+        let rhs_shape = self.known_shape(&name, rhs)?;
+        if lhs_shape != rhs_shape {
+            return Err(ShapeError {
+                message: format!("operand shapes {:?} and {:?} differ", lhs_shape, rhs_shape),
+                node_name: name
+            });
+        }
+        let shape = lhs_shape.to_vec();
+        Ok(self.insert_with_shape(name, shape, &[lhs, rhs], move |x| {
+            let mut out = x[0].clone();
+            out.zip_mut_with(x[1], |a, b| *a = func(*a, *b));
+            out
+        }))
+    }
+    fn insert_with_shape(&mut self, name: String, shape: Vec<usize>, inputs: &[&NodeHandle],
+            func: impl Fn(&[&ArrayD<A>]) -> ArrayD<A> + Send + Sync + 'static) -> NodeHandle {
+        let mut handle = self.graph.insert_node(name, Box::new(func));
+        self.graph.set_inputs(&mut handle, inputs);
+        self.shapes.insert(handle.node_key, shape);
+        handle
+    }
+}
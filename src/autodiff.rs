@@ -0,0 +1,339 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind};
+
+use std::sync::Arc;
+
+use slotmap::SecondaryMap;
+use log::info;
+
+/// A differentiable operation that can be inserted into a graph with
+/// [`ComputationGraph::insert_diff_node`].
+pub trait DiffOp<T>: Send + Sync {
+    /// Computes the output of the operation.
+    fn eval(&self, inputs: &[&T]) -> T;
+    /// Computes the vector-Jacobian product of the operation.
+    ///
+    /// Given the inputs, the computed output, and the cotangent (gradient)
+    /// of the final result with respect to the output, returns one cotangent
+    /// per input.
+    fn vjp(&self, inputs: &[&T], output: &T, cotangent: &T) -> Vec<T>;
+    /// Computes the Jacobian-vector product of the operation.
+    ///
+    /// Given the inputs, the computed output, and the tangent (directional
+    /// derivative) of each input, returns the tangent of the output.
+    fn jvp(&self, inputs: &[&T], output: &T, tangents: &[&T]) -> T;
+}
+
+/// Values that gradients can be computed and accumulated for.
+pub trait Differentiable: Clone {
+    /// Returns the derivative of a value with respect to itself.
+    fn ones_like(&self) -> Self;
+    /// Returns the derivative of a constant with the same shape as a value.
+    fn zeros_like(&self) -> Self;
+    /// Adds another gradient contribution into this one.
+    fn accumulate(&mut self, other: &Self);
+}
+macro_rules! impl_differentiable_float {
+    ($($float:ty),*) => {$(
+        impl Differentiable for $float {
+            fn ones_like(&self) -> Self {
+                1.0
+            }
+            fn zeros_like(&self) -> Self {
+                0.0
+            }
+            fn accumulate(&mut self, other: &Self) {
+                *self += other;
+            }
+        }
+        impl Differentiable for Vec<$float> {
+            fn ones_like(&self) -> Self {
+                vec![1.0; self.len()]
+            }
+            fn zeros_like(&self) -> Self {
+                vec![0.0; self.len()]
+            }
+            fn accumulate(&mut self, other: &Self) {
+                assert_eq!(self.len(), other.len(), "Gradient shapes do not match");
+                for (acc, val) in self.iter_mut().zip(other.iter()) {
+                    *acc += val;
+                }
+            }
+        }
+    )*}
+}
+impl_differentiable_float!(f32, f64);
+
+impl<T> ComputationGraph<T> {
+    /// Inserts a differentiable node, returning an opaque node handle.
+    pub fn insert_diff_node(&mut self, name: impl Into<Arc<str>>,
+            op: impl DiffOp<T> + 'static) -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::DiffFunc(Arc::new(op)))
+    }
+}
+impl<T: Differentiable> ComputationGraph<T> {
+    /// Computes the gradients of `output` with respect to each of `params`
+    /// using reverse-mode automatic differentiation.
+    ///
+    /// The graph is evaluated without being consumed. Gradients only flow
+    /// through nodes inserted with [`insert_diff_node`](Self::insert_diff_node),
+    /// and all other nodes are treated as constants. The gradient for a
+    /// parameter that `output` does not depend on is `None`. The graph must
+    /// not contain placeholders.
+    pub fn backward(&self, output: &NodeHandle, params: &[&NodeHandle]) -> Vec<Option<T>> {
+        self.backward_with(output, params, Vec::new())
+    }
+    /// Computes the gradients of `output` with respect to each of `params`
+    /// like [`backward`](Self::backward), feeding the given values to
+    /// placeholders.
+    pub fn backward_with<'a>(&self, output: &NodeHandle, params: &[&NodeHandle],
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>) -> Vec<Option<T>> {
+        assert_eq!(output.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        info!("Differentiating DAG");
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        let (order, values) = self.evaluate_retained(output.node_key, placeholder_values);
+
+        let mut cotangents: SecondaryMap<_, T> = SecondaryMap::new();
+        let seed = values.get(output.node_key).unwrap().ones_like();
+        cotangents.insert(output.node_key, seed);
+        for node_key in order.iter().rev().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let op = match node.kind {
+                NodeKind::DiffFunc(ref op) => op,
+                _ => continue
+            };
+            // Nodes the output does not depend on through DiffOps get no gradient
+            let cotangent = match cotangents.get(node_key) {
+                Some(cotangent) => cotangent.clone(),
+                None => continue
+            };
+            node_log!(Trace, self, node_key, node.name, "Propagating gradient through node");
+            let node_inputs: Vec<&T> = node.input_nodes.iter()
+                .map(|key| values.get(*key).unwrap())
+                .collect();
+            let input_grads = op.vjp(&node_inputs, values.get(node_key).unwrap(),
+                &cotangent);
+            assert_eq!(input_grads.len(), node_inputs.len(),
+                "Node {} returned the wrong number of gradients", node.name);
+            for (input_key, grad) in node.input_nodes.iter().zip(input_grads) {
+                match cotangents.get_mut(*input_key) {
+                    Some(acc) => acc.accumulate(&grad),
+                    None => {
+                        cotangents.insert(*input_key, grad);
+                    }
+                }
+            }
+        }
+        params.iter().map(|param| {
+            assert_eq!(param.graph_id, self.graph_id,
+                "Received NodeHandle for different graph");
+            cotangents.remove(param.node_key)
+        }).collect()
+    }
+    /// Computes the value of `output` along with its directional derivative
+    /// using forward-mode automatic differentiation.
+    ///
+    /// `tangents` gives the direction as the derivative of each listed node,
+    /// and all other nodes without inputs are treated as constants. As with
+    /// [`backward`](Self::backward), derivatives only flow through nodes
+    /// inserted with [`insert_diff_node`](Self::insert_diff_node). The graph
+    /// must not contain placeholders.
+    pub fn forward_derivative(&self, output: &NodeHandle,
+            tangents: &[(&NodeHandle, T)]) -> (T, T) {
+        self.forward_derivative_with(output, tangents, Vec::new())
+    }
+    /// Computes the value of `output` along with its directional derivative
+    /// like [`forward_derivative`](Self::forward_derivative), feeding the
+    /// given values to placeholders.
+    ///
+    /// Placeholders are treated as constants unless listed in `tangents`.
+    pub fn forward_derivative_with<'a>(&self, output: &NodeHandle,
+            tangents: &[(&NodeHandle, T)],
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>) -> (T, T) {
+        assert_eq!(output.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        info!("Differentiating DAG in forward mode");
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        let (order, mut values) = self.evaluate_retained(output.node_key, placeholder_values);
+
+        let mut node_tangents: SecondaryMap<_, T> = SecondaryMap::new();
+        for (handle, tangent) in tangents {
+            assert_eq!(handle.graph_id, self.graph_id,
+                "Received NodeHandle for different graph");
+            node_tangents.insert(handle.node_key, tangent.clone());
+        }
+        for node_key in order.iter().copied() {
+            if node_tangents.contains_key(node_key) {
+                continue;
+            }
+            let node = self.node_storage.get(node_key).unwrap();
+            let op = match node.kind {
+                NodeKind::DiffFunc(ref op) => op,
+                _ => continue
+            };
+            // Nodes with constant inputs have a zero tangent, so skip them
+            if node.input_nodes.iter().all(|key| !node_tangents.contains_key(*key)) {
+                continue;
+            }
+            node_log!(Trace, self, node_key, node.name, "Propagating tangent through node");
+            let node_inputs: Vec<&T> = node.input_nodes.iter()
+                .map(|key| values.get(*key).unwrap())
+                .collect();
+            let input_zeros: Vec<Option<T>> = node.input_nodes.iter()
+                .zip(node_inputs.iter())
+                .map(|(key, val)| if node_tangents.contains_key(*key) {
+                    None
+                } else {
+                    Some(val.zeros_like())
+                })
+                .collect();
+            let input_tangents: Vec<&T> = node.input_nodes.iter()
+                .zip(input_zeros.iter())
+                .map(|(key, zero)| match zero {
+                    Some(zero) => zero,
+                    None => node_tangents.get(*key).unwrap()
+                })
+                .collect();
+            let tangent = op.jvp(&node_inputs, values.get(node_key).unwrap(),
+                &input_tangents);
+            node_tangents.insert(node_key, tangent);
+        }
+        let value = values.remove(output.node_key).unwrap();
+        let tangent = node_tangents.remove(output.node_key)
+            .unwrap_or_else(|| value.zeros_like());
+        (value, tangent)
+    }
+}
+
+/// A graph computing gradients, produced by
+/// [`ComputationGraph::gradient_graph`].
+#[derive(Debug)]
+pub struct GradientGraph<T> {
+    /// The transformed graph, which includes a copy of the forward nodes.
+    pub graph: ComputationGraph<T>,
+    /// Handles to the gradient of each requested parameter, or `None` for
+    /// parameters that the output does not depend on.
+    pub gradients: Vec<Option<NodeHandle>>,
+    forward_nodes: SecondaryMap<ComputeGraphKey, NodeHandle>,
+    source_graph_id: usize
+}
+impl<T> GradientGraph<T> {
+    /// Takes the handle of the copy of a node from the original graph.
+    ///
+    /// This is mainly useful for feeding values to copied placeholders.
+    /// Each handle can only be taken once.
+    pub fn take_forward_node(&mut self, original: &NodeHandle) -> NodeHandle {
+        assert_eq!(original.graph_id, self.source_graph_id,
+            "Received NodeHandle for different graph");
+        self.forward_nodes.remove(original.node_key)
+            .expect("Node was not copied into the gradient graph or was already taken")
+    }
+}
+
+impl<T: Differentiable + Send + Sync + 'static> ComputationGraph<T> {
+    /// Builds a new graph computing the gradients of `output` with respect
+    /// to each of `params`, without evaluating anything.
+    ///
+    /// Every ancestor of `output` must be a node inserted with
+    /// [`insert_diff_node`](Self::insert_diff_node) or a placeholder, since
+    /// other nodes cannot be copied into the new graph. Gradient nodes are
+    /// named after the nodes they differentiate, so the result can be
+    /// inspected with [`dot_graph`](Self::dot_graph) like any other graph.
+    pub fn gradient_graph(&self, output: &NodeHandle,
+            params: &[&NodeHandle]) -> GradientGraph<T> {
+        assert_eq!(output.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        info!("Building gradient graph");
+        let order = self.toposort_from(&[output.node_key]);
+        let mut grad_graph = ComputationGraph::new();
+
+        // Copy the forward computation
+        let mut forward_nodes: SecondaryMap<_, NodeHandle> = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let mut handle = match node.kind {
+                NodeKind::DiffFunc(ref op) => grad_graph.insert_node_kind(
+                    node.name.clone(), NodeKind::DiffFunc(op.clone())),
+                NodeKind::Placeholder => grad_graph.insert_placeholder(node.name.clone()),
+                _ => panic!("Node {} is not differentiable", node.name)
+            };
+            let inputs: Vec<&NodeHandle> = node.input_nodes.iter()
+                .map(|key| forward_nodes.get(*key).unwrap())
+                .collect();
+            if !inputs.is_empty() {
+                grad_graph.set_inputs(&mut handle, &inputs);
+            }
+            forward_nodes.insert(node_key, handle);
+        }
+
+        // Propagate cotangents backwards, summing contributions per node
+        let mut contributions: SecondaryMap<_, Vec<NodeHandle>> = SecondaryMap::new();
+        let output_name = &self.node_storage.get(output.node_key).unwrap().name;
+        let mut seed = grad_graph.insert_node(format!("grad {}", output_name),
+            Box::new(|x: &[&T]| x[0].ones_like()));
+        grad_graph.set_inputs(&mut seed, &[forward_nodes.get(output.node_key).unwrap()]);
+        contributions.insert(output.node_key, vec![seed]);
+        let mut cotangents: SecondaryMap<_, NodeHandle> = SecondaryMap::new();
+        for node_key in order.iter().rev().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let mut node_contributions = match contributions.remove(node_key) {
+                Some(node_contributions) => node_contributions,
+                None => continue
+            };
+            let cotangent = if node_contributions.len() == 1 {
+                node_contributions.pop().unwrap()
+            } else {
+                let mut sum = grad_graph.insert_node(format!("grad {}", node.name),
+                    Box::new(|x: &[&T]| {
+                        let mut acc = x[0].clone();
+                        for contribution in x[1..].iter() {
+                            acc.accumulate(contribution);
+                        }
+                        acc
+                    }));
+                let sum_inputs: Vec<&NodeHandle> = node_contributions.iter().collect();
+                grad_graph.set_inputs(&mut sum, &sum_inputs);
+                sum
+            };
+            if let NodeKind::DiffFunc(ref op) = node.kind {
+                let input_count = node.input_nodes.len();
+                if input_count > 0 {
+                    let op = op.clone();
+                    let (mut vjp, vjp_outputs) = grad_graph.insert_multi_output_node(
+                        format!("vjp {}", node.name), input_count,
+                        Box::new(move |x: &[&T]| {
+                            op.vjp(&x[..input_count], x[input_count], x[input_count+1])
+                        }));
+                    let mut vjp_inputs: Vec<&NodeHandle> = node.input_nodes.iter()
+                        .map(|key| forward_nodes.get(*key).unwrap())
+                        .collect();
+                    vjp_inputs.push(forward_nodes.get(node_key).unwrap());
+                    vjp_inputs.push(&cotangent);
+                    grad_graph.set_inputs(&mut vjp, &vjp_inputs);
+                    for (input_key, grad) in node.input_nodes.iter().zip(vjp_outputs) {
+                        match contributions.get_mut(*input_key) {
+                            Some(input_contributions) => input_contributions.push(grad),
+                            None => {
+                                contributions.insert(*input_key, vec![grad]);
+                            }
+                        }
+                    }
+                }
+            }
+            node_log!(Trace, self, node_key, node.name, "Built gradient for node");
+            cotangents.insert(node_key, cotangent);
+        }
+
+        let gradients = params.iter().map(|param| {
+            assert_eq!(param.graph_id, self.graph_id,
+                "Received NodeHandle for different graph");
+            cotangents.remove(param.node_key)
+        }).collect();
+        GradientGraph {
+            graph: grad_graph,
+            gradients,
+            forward_nodes,
+            source_graph_id: self.graph_id
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{ComputationGraph, NodeHandle};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use slotmap::Key as KeyTrait;
+use log::{info, debug};
+
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+/// A node whose value differs from the one recorded in a baseline, found by
+/// [`ComputationGraph::compute_against_baseline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence<T> {
+    /// The ID of the node.
+    pub node: u64,
+    /// The name of the node.
+    pub name: String,
+    /// The value recorded in the baseline.
+    pub expected: T,
+    /// The value computed in this run.
+    pub actual: T
+}
+impl<T: fmt::Debug> fmt::Display for Divergence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Node {} ({}) diverged from the baseline: expected {:?}, got {:?}",
+            self.name, self.node, self.expected, self.actual)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BaselineEntry<T> {
+    name: String,
+    output: T
+}
+
+impl<T> ComputationGraph<T>
+        where T: Serialize + DeserializeOwned + Clone + PartialEq + fmt::Debug {
+    /// Computes the value of the output node, comparing the value of every
+    /// evaluated node to a baseline file.
+    /// 
+    /// If `baseline` does not exist, it is created with the values of this
+    /// run and no divergence is reported, so deleting the file records a new
+    /// baseline. Otherwise the first node in evaluation order whose value
+    /// differs from the baseline is returned. Nodes are matched by name, with
+    /// nodes sharing a name matched in evaluation order, and nodes missing
+    /// from the baseline are ignored.
+    /// 
+    /// The graph is not consumed, and must not contain placeholders. Every
+    /// value is kept until the end of the run so that it can be compared.
+    pub fn compute_against_baseline(&self, baseline: impl AsRef<Path>)
+            -> io::Result<(T, Option<Divergence<T>>)> {
+        self.compute_against_baseline_with(baseline, Vec::new())
+    }
+    /// Computes the value of the output node like
+    /// [`compute_against_baseline`](Self::compute_against_baseline),
+    /// feeding the given values to placeholders.
+    pub fn compute_against_baseline_with<'a>(&self, baseline: impl AsRef<Path>,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>)
+            -> io::Result<(T, Option<Divergence<T>>)> {
+        let baseline = baseline.as_ref();
+        let out_key = self.output_node.expect("Output not yet designated");
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        let order = self.evaluation_order(out_key);
+        let mut values = self.execute_order(&order, None, placeholder_values,
+            &self.run_context(0));
+        // Multi-output nodes are compared through their output handles
+        let evaluated: Vec<_> = order.iter().copied()
+            .filter(|key| values.contains_key(*key))
+            .collect();
+
+        if !baseline.exists() {
+            info!("Recording baseline to {}", baseline.display());
+            let entries: Vec<_> = evaluated.iter().map(|key| BaselineEntry {
+                name: self.node_storage.get(*key).unwrap().name.to_string(),
+                output: values.get(*key).unwrap()
+            }).collect();
+            let file = File::create(baseline)?;
+            serde_json::to_writer(BufWriter::new(file), &entries)?;
+            return Ok((values.remove(out_key).unwrap(), None));
+        }
+
+        info!("Comparing DAG to baseline {}", baseline.display());
+        let file = File::open(baseline)?;
+        let entries: Vec<BaselineEntry<T>> = serde_json::from_reader(BufReader::new(file))?;
+        // Values recorded under each name, next to match last
+        let mut expected: HashMap<String, Vec<T>> = HashMap::new();
+        for entry in entries.into_iter().rev() {
+            expected.entry(entry.name).or_default().push(entry.output);
+        }
+        let mut divergence = None;
+        for node_key in evaluated {
+            let name = &self.node_storage.get(node_key).unwrap().name;
+            let Some(expected_value) = expected.get_mut(&**name).and_then(Vec::pop) else {
+                debug!("Node {} is not in the baseline", name);
+                continue;
+            };
+            if *values.get(node_key).unwrap() != expected_value {
+                // Later nodes usually diverge only because this one did
+                divergence = Some(Divergence {
+                    node: node_key.data().as_ffi(),
+                    name: name.to_string(),
+                    expected: expected_value,
+                    actual: values.get(node_key).unwrap().clone()
+                });
+                break;
+            }
+        }
+        Ok((values.remove(out_key).unwrap(), divergence))
+    }
+}
@@ -0,0 +1,65 @@
+use crate::{ComputationGraph, NodeContext};
+
+use std::thread;
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+/// Derives a well-mixed per-run seed from a base seed using SplitMix64.
+pub(crate) fn derive_seed(base_seed: u64, index: u64) -> u64 {
+    let mut z = base_seed.wrapping_add(index.wrapping_add(1)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl<T: Send + Sync> ComputationGraph<T> {
+    /// Evaluates the graph `runs` times, returning the output of each run in
+    /// order.
+    /// 
+    /// Each run receives a [`NodeContext`] with its run index and a seed
+    /// derived from `base_seed`, so results are reproducible for a given
+    /// base seed regardless of `threads`. Runs are spread across `threads`
+    /// threads, with 1 evaluating them sequentially on the calling thread.
+    /// The graph is not consumed, and must not contain placeholders.
+    pub fn compute_monte_carlo(&self, runs: usize, base_seed: u64, threads: usize) -> Vec<T> {
+        assert!(threads > 0, "At least one thread is required");
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG for {} Monte Carlo runs", runs);
+        let order = self.evaluation_order(out_key);
+        let refcounts = self.order_refcounts(&order, out_key);
+
+        let run_once = |run_index: usize| {
+            debug!("Evaluating run {}", run_index);
+            let context = NodeContext::new(run_index,
+                derive_seed(base_seed, run_index as u64), self.clock.clone());
+            let mut values = self.execute_order(&order, Some(refcounts.clone()),
+                SecondaryMap::new(), &context);
+            values.remove(out_key).unwrap()
+        };
+        if threads == 1 {
+            return (0..runs).map(run_once).collect();
+        }
+        let mut outputs: Vec<Option<T>> = (0..runs).map(|_| None).collect();
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads).map(|thread_index| {
+                let run_once = &run_once;
+                scope.spawn(move || {
+                    (thread_index..runs).step_by(threads)
+                        .map(|run_index| (run_index, run_once(run_index)))
+                        .collect::<Vec<_>>()
+                })
+            }).collect();
+            for worker in workers {
+                // Propagate panics from nodes with their original payload
+                let worker_outputs = worker.join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err));
+                for (run_index, output) in worker_outputs {
+                    outputs[run_index] = Some(output);
+                }
+            }
+        });
+        outputs.into_iter().map(Option::unwrap).collect()
+    }
+}
@@ -0,0 +1,88 @@
+use crate::ComputationGraph;
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A source of the current time, used to time node evaluations.
+///
+/// The graph uses [`SystemClock`] unless another clock is set with
+/// [`ComputationGraph::set_clock`]. Tests of time-dependent features can use
+/// a [`ManualClock`] to control time without sleeping.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+}
+
+/// A [`Clock`] reading the system's monotonic clock with [`Instant::now`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A [`Clock`] that only advances when told to.
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: Mutex<Duration>
+}
+impl ManualClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> ManualClock {
+        ManualClock {
+            start: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO)
+        }
+    }
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+}
+
+// Wrapper allowing the graph and NodeContext to keep deriving their traits
+#[derive(Clone)]
+pub(crate) struct ClockHook(pub(crate) Arc<dyn Clock>);
+impl Default for ClockHook {
+    fn default() -> Self {
+        ClockHook(Arc::new(SystemClock))
+    }
+}
+impl fmt::Debug for ClockHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ClockHook(...)")
+    }
+}
+// Contexts are only equal when they read the same clock
+impl PartialEq for ClockHook {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+impl Eq for ClockHook {}
+
+impl<T> ComputationGraph<T> {
+    /// Sets the clock used to time node evaluations, such as for profiles,
+    /// summaries and listener events, and read by nodes through
+    /// [`NodeContext::now`](crate::NodeContext::now).
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = ClockHook(clock);
+    }
+    /// Returns the current time according to the graph's clock.
+    pub(crate) fn now(&self) -> Instant {
+        self.clock.0.now()
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{ComputationGraph, ComputeGraphKey, InputList, NodeKind};
+
+use std::collections::HashMap;
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+
+use log::debug;
+
+// Nodes by name and inputs, possibly outdated if inputs were set again
+pub(crate) type DedupIndex = HashMap<(Arc<str>, InputList), ComputeGraphKey>;
+
+impl<T> ComputationGraph<T> {
+    /// Enables or disables deduplication of nodes, which is disabled by
+    /// default.
+    /// 
+    /// While enabled, setting the inputs of a newly inserted node that has
+    /// the same name, the same kind and the same inputs as an existing node
+    /// removes the new node and points its handle to the existing one, so
+    /// memoizing graph builders do not duplicate work. As closures cannot be
+    /// compared, nodes of the same kind are assumed to compute the same
+    /// thing if their names match. Nodes without inputs are deduplicated
+    /// when their inputs are set to an empty list, and nodes already used
+    /// as an input or designated as the output or a sink are never removed.
+    pub fn set_deduplication(&mut self, enabled: bool) {
+        if !enabled {
+            self.dedup_index = None;
+            return;
+        }
+        if self.dedup_index.is_some() {
+            return;
+        }
+        self.dedup_index = Some(DedupIndex::new());
+        let keys: Vec<_> = self.node_storage.keys().collect();
+        for key in keys {
+            self.index_node(key);
+        }
+    }
+    /// Records a node in the deduplication index if deduplication is
+    /// enabled, unless an equivalent node is already recorded.
+    pub(crate) fn index_node(&mut self, key: ComputeGraphKey) {
+        let Some(ref mut index) = self.dedup_index else {
+            return;
+        };
+        let node = self.node_storage.get(key).unwrap();
+        let index_key = (node.name.clone(), node.input_nodes.clone());
+        match index.get(&index_key) {
+            // Keep the older node unless it no longer matches
+            Some(existing) if self.node_storage.get(*existing)
+                .is_some_and(|existing| existing.name == node.name
+                    && existing.input_nodes == node.input_nodes) => {},
+            _ => {
+                index.insert(index_key, key);
+            }
+        }
+    }
+    /// Returns an existing node equivalent to the new node at `key` with the
+    /// given inputs, if deduplication is enabled and the new node can be
+    /// removed.
+    pub(crate) fn find_duplicate(&self, key: ComputeGraphKey, inputs: &InputList)
+            -> Option<ComputeGraphKey> {
+        let index = self.dedup_index.as_ref()?;
+        let node = self.node_storage.get(key).unwrap();
+        if *self.node_refcount.get(key).unwrap() != 0 || !node.input_nodes.is_empty() {
+            return None;
+        }
+        let existing_key = *index.get(&(node.name.clone(), inputs.clone()))?;
+        let existing = self.node_storage.get(existing_key)?;
+        let equivalent = existing_key != key && existing.name == node.name
+            && existing.input_nodes == *inputs && same_kind(&existing.kind, &node.kind);
+        if equivalent {
+            debug!("Reusing existing node {} instead of a duplicate", node.name);
+        }
+        equivalent.then_some(existing_key)
+    }
+}
+
+/// Returns whether two node kinds can be treated as computing the same
+/// thing given the same name and inputs.
+fn same_kind<T>(a: &NodeKind<T>, b: &NodeKind<T>) -> bool {
+    match (a, b) {
+        (NodeKind::Unary(a), NodeKind::Unary(b)) => ptr::fn_addr_eq(*a, *b),
+        (NodeKind::Binary(a), NodeKind::Binary(b)) => ptr::fn_addr_eq(*a, *b),
+        (NodeKind::Select(a, _), NodeKind::Select(b, _)) => ptr::fn_addr_eq(*a, *b),
+        (NodeKind::ShortCircuit(a_op, a, _), NodeKind::ShortCircuit(b_op, b, _)) =>
+            a_op == b_op && ptr::fn_addr_eq(*a, *b),
+        (NodeKind::MultiFunc(_, a), NodeKind::MultiFunc(_, b)) => a == b,
+        // Each of these has its own state or wiring
+        (NodeKind::MultiOutput(_), _) | (NodeKind::Placeholder, _) | (NodeKind::Source(_), _)
+            | (NodeKind::Delay(_, _), _) => false,
+        _ => mem::discriminant(a) == mem::discriminant(b)
+    }
+}
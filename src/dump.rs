@@ -0,0 +1,150 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle};
+
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use slotmap::Key as KeyTrait;
+use log::warn;
+
+/// Where values selected for dumping are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DumpTarget {
+    /// Print each value to standard output, prefixed with the node name.
+    Stdout,
+    /// Print each value to standard error, prefixed with the node name.
+    Stderr,
+    /// Append each value as a line to a file per node in the directory,
+    /// named after the node and its ID.
+    Directory(PathBuf)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DumpSelector {
+    Node(ComputeGraphKey),
+    Pattern(String)
+}
+
+pub(crate) struct ValueDump<T> {
+    selector: DumpSelector,
+    target: DumpTarget,
+    format: fn(&T) -> String
+}
+impl<T> fmt::Debug for ValueDump<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ValueDump {{ selector: {:?}, target: {:?} }}", self.selector, self.target)
+    }
+}
+
+impl<T: fmt::Debug> ComputationGraph<T> {
+    /// Writes the value of a node to `target` every time it is computed,
+    /// formatted with `Debug`.
+    pub fn dump_node_values(&mut self, node: &NodeHandle, target: DumpTarget) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.value_dumps.push(ValueDump {
+            selector: DumpSelector::Node(node.node_key),
+            target,
+            format: |value| format!("{:?}", value)
+        });
+    }
+    /// Writes the values of every node whose name matches `pattern` to
+    /// `target` every time they are computed, formatted with `Debug`.
+    /// 
+    /// In the pattern, `*` matches any sequence of characters and `?` matches
+    /// any single character. The pattern also applies to nodes inserted
+    /// later.
+    pub fn dump_values_matching(&mut self, pattern: &str, target: DumpTarget) {
+        self.value_dumps.push(ValueDump {
+            selector: DumpSelector::Pattern(pattern.to_owned()),
+            target,
+            format: |value| format!("{:?}", value)
+        });
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Stops dumping values of any node.
+    pub fn clear_value_dumps(&mut self) {
+        self.value_dumps.clear();
+    }
+    /// Returns whether a dump selects the values of a node.
+    pub(crate) fn is_dumped(&self, key: ComputeGraphKey) -> bool {
+        let name = &self.node_storage.get(key).unwrap().name;
+        self.value_dumps.iter().any(|dump| match dump.selector {
+            DumpSelector::Node(node_key) => node_key == key,
+            DumpSelector::Pattern(ref pattern) => glob_match(pattern, name)
+        })
+    }
+    /// Writes a newly computed value to the targets of the dumps selecting
+    /// its node.
+    pub(crate) fn dump_value(&self, key: ComputeGraphKey, value: &T) {
+        if self.value_dumps.is_empty() {
+            return;
+        }
+        let name = &self.node_storage.get(key).unwrap().name;
+        for dump in self.value_dumps.iter() {
+            let selected = match dump.selector {
+                DumpSelector::Node(node_key) => node_key == key,
+                DumpSelector::Pattern(ref pattern) => glob_match(pattern, name)
+            };
+            if !selected {
+                continue;
+            }
+            let formatted = (dump.format)(value);
+            match dump.target {
+                DumpTarget::Stdout => println!("{}: {}", name, formatted),
+                DumpTarget::Stderr => eprintln!("{}: {}", name, formatted),
+                DumpTarget::Directory(ref dir) => {
+                    let file_name = format!("{}-{}.txt",
+                        name.replace(|c: char| !c.is_alphanumeric() && c != '_', "_"),
+                        key.data().as_ffi());
+                    // Dumping is a debugging aid, so failures must not abort the run
+                    let result = fs::create_dir_all(dir).and_then(|_| {
+                        let mut file = OpenOptions::new().create(true).append(true)
+                            .open(dir.join(file_name))?;
+                        writeln!(file, "{}", formatted)
+                    });
+                    if let Err(error) = result {
+                        warn!(node = &**name, node_id = key.data().as_ffi(),
+                            graph_id = self.graph_id;
+                            "Could not dump value of node {}: {}", name, error);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns whether `name` matches `pattern`, where `*` matches any sequence
+/// of characters and `?` matches any single character.
+pub(crate) fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried at
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            },
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            },
+            _ => match backtrack {
+                // Let the last `*` match one more character
+                Some((star, star_n)) => {
+                    backtrack = Some((star, star_n + 1));
+                    p = star + 1;
+                    n = star_n + 1;
+                },
+                None => return false
+            }
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
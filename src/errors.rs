@@ -0,0 +1,193 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind};
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+use log::{info, warn};
+
+/// How [`ComputationGraph::try_compute`] handles nodes that panic, set with
+/// [`ComputationGraph::set_error_mode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Stop evaluating at the first node that panics.
+    #[default]
+    FailFast,
+    /// Keep evaluating every node that does not depend on a failed node, so
+    /// that the failures of all independent branches are reported together.
+    CollectAll
+}
+
+/// A node that panicked during [`ComputationGraph::try_compute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeFailure {
+    /// The ID of the node.
+    pub node: u64,
+    /// The name of the node.
+    pub name: String,
+    /// The panic message.
+    pub message: String
+}
+
+/// The result of a [`ComputationGraph::try_compute`] run in which at least
+/// one node panicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionFailure<T> {
+    /// The nodes that panicked, in evaluation order.
+    pub failures: Vec<NodeFailure>,
+    /// The IDs of the nodes that were not evaluated because one of their
+    /// ancestors panicked, or because evaluation stopped early.
+    pub skipped: Vec<u64>,
+    /// The value of the output node, if it was computed.
+    pub output: Option<T>,
+    /// The values of the sink nodes that were computed, keyed by node ID.
+    pub sinks: HashMap<u64, T>
+}
+impl<T> fmt::Display for ExecutionFailure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes failed ({} skipped)", self.failures.len(), self.skipped.len())?;
+        for failure in self.failures.iter() {
+            write!(f, "\n  {} ({}): {}", failure.name, failure.node, failure.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Extracts the message of a panic payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned())
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets how [`try_compute`](Self::try_compute) handles nodes that
+    /// panic.
+    pub fn set_error_mode(&mut self, mode: ErrorMode) {
+        self.error_mode = mode;
+    }
+    /// Returns how [`try_compute`](Self::try_compute) handles nodes that
+    /// panic.
+    pub fn error_mode(&self) -> ErrorMode {
+        self.error_mode
+    }
+    /// Computes and returns the value of the output node, catching panics
+    /// of individual nodes instead of unwinding.
+    ///
+    /// If any node panics, the failures are returned along with the output
+    /// and sink values that could still be computed. With
+    /// [`ErrorMode::FailFast`] evaluation stops at the first failure, while
+    /// with [`ErrorMode::CollectAll`] every node not depending on a failed
+    /// node is still evaluated. Nodes are evaluated one at a time, without
+    /// the optimizations of [`compute`](Self::compute), and both branches of
+    /// select and short-circuit nodes are evaluated eagerly, so a failure in
+    /// a branch that would not have been taken is still reported. Unfed
+    /// placeholders, as well as source and delay nodes, which are only given
+    /// values by their own entry points, are reported as failures.
+    pub fn try_compute(self) -> Result<T, ExecutionFailure<T>> {
+        self.try_compute_inputs(SecondaryMap::new())
+    }
+    /// Computes the value of the output node like
+    /// [`try_compute`](Self::try_compute), feeding the given values to
+    /// placeholders.
+    pub fn try_compute_with<'a>(self, inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>)
+            -> Result<T, ExecutionFailure<T>> {
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        self.try_compute_inputs(placeholder_values)
+    }
+    fn try_compute_inputs(mut self, inputs: SecondaryMap<ComputeGraphKey, T>)
+            -> Result<T, ExecutionFailure<T>> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG catching node failures");
+        let order = self.computation_order();
+        let context = self.run_context(0);
+        let mut values = inputs;
+        let mut multi_values: SecondaryMap<ComputeGraphKey, Vec<Option<T>>> =
+            SecondaryMap::new();
+        // Nodes that panicked or were skipped
+        let mut failed: SecondaryMap<ComputeGraphKey, ()> = SecondaryMap::new();
+        let mut failures = Vec::new();
+        let mut skipped = Vec::new();
+        let mut progress = self.notify_plan(&order);
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let stopped = self.error_mode == ErrorMode::FailFast && !failures.is_empty();
+            if stopped || node.input_nodes.iter().any(|key| failed.contains_key(*key)) {
+                failed.insert(node_key, ());
+                skipped.push(node_key.data().as_ffi());
+                continue;
+            }
+            let node_started = self.notify_node_start(node_key);
+            let result = match node.kind {
+                NodeKind::Placeholder | NodeKind::Source(_) | NodeKind::Delay(_, _) => {
+                    if values.contains_key(node_key) {
+                        Ok(())
+                    } else {
+                        // Panics explaining how such nodes must be given values
+                        panic::catch_unwind(AssertUnwindSafe(|| node.call(&[], &context)))
+                            .map(|_| ())
+                    }
+                },
+                NodeKind::MultiOutput(index) => {
+                    let source_vals = multi_values.get_mut(node.input_nodes[0]).unwrap();
+                    values.insert(node_key, source_vals[index].take().unwrap());
+                    Ok(())
+                },
+                _ => {
+                    let node_inputs: Vec<&T> = node.input_nodes.iter()
+                        .map(|key| values.get(*key).unwrap())
+                        .collect();
+                    if node.is_multi_func() {
+                        panic::catch_unwind(AssertUnwindSafe(|| node.call_multi(&node_inputs)))
+                            .map(|outputs| {
+                                multi_values.insert(node_key,
+                                    outputs.into_iter().map(Some).collect());
+                            })
+                    } else {
+                        panic::catch_unwind(AssertUnwindSafe(|| {
+                            self.evaluate_node(node_key, node, &node_inputs, &context)
+                        })).map(|output| {
+                            values.insert(node_key, output);
+                        })
+                    }
+                }
+            };
+            match result {
+                Ok(()) => {
+                    if let Some(value) = values.get(node_key) {
+                        self.publish_value(node_key, value);
+                    }
+                    self.notify_node_finish(node_key, node_started, &mut progress);
+                },
+                Err(payload) => {
+                    let message = panic_message(&*payload);
+                    warn!(node = &*node.name, node_id = node_key.data().as_ffi(),
+                        graph_id = self.graph_id;
+                        "Node {} failed: {}", node.name, message);
+                    failed.insert(node_key, ());
+                    failures.push(NodeFailure {
+                        node: node_key.data().as_ffi(),
+                        name: node.name.to_string(),
+                        message
+                    });
+                }
+            }
+        }
+        self.notify_complete(progress);
+
+        if failures.is_empty() {
+            return Ok(values.remove(out_key).unwrap());
+        }
+        let sinks = self.sink_nodes.iter()
+            .filter_map(|key| values.remove(*key).map(|value| (key.data().as_ffi(), value)))
+            .collect();
+        Err(ExecutionFailure {
+            failures,
+            skipped,
+            output: values.remove(out_key),
+            sinks
+        })
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{ComputationGraph, ExecutionListener, NodeInfo, Progress};
+use crate::errors::panic_message;
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// An event emitted while a graph is evaluated by
+/// [`ComputationGraph::compute_with_events`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecEvent {
+    /// A node will be removed because the output does not depend on it,
+    /// reported if the sweep policy is
+    /// [`SweepPolicy::Warn`](crate::SweepPolicy::Warn).
+    Unreachable {
+        /// The ID of the node.
+        node: u64,
+        /// The name of the node.
+        name: String
+    },
+    /// Evaluation is about to start with the given number of nodes.
+    Planned {
+        /// The number of nodes to evaluate.
+        node_count: usize
+    },
+    /// A node is about to be evaluated.
+    NodeStarted {
+        /// The ID of the node.
+        node: u64,
+        /// The name of the node.
+        name: String
+    },
+    /// A node finished evaluating.
+    NodeFinished {
+        /// The ID of the node.
+        node: u64,
+        /// The name of the node.
+        name: String,
+        /// The time taken to evaluate the node.
+        duration: Duration
+    },
+    /// The value of a node was dropped, as no remaining node uses it.
+    ValueDropped {
+        /// The ID of the node.
+        node: u64,
+        /// The name of the node.
+        name: String
+    },
+    /// The progress of the evaluation after a node was evaluated.
+    Progress(Progress),
+    /// Every node was evaluated.
+    Completed {
+        /// The total time taken.
+        duration: Duration
+    },
+    /// Evaluation panicked with the given message.
+    Failed {
+        /// The panic message.
+        message: String
+    }
+}
+
+// Forwards listener callbacks to a channel
+struct ChannelListener(Sender<ExecEvent>);
+impl ChannelListener {
+    fn send(&self, event: ExecEvent) {
+        // The receiver may have been dropped, which only stops the events
+        let _ = self.0.send(event);
+    }
+}
+impl ExecutionListener for ChannelListener {
+    fn on_unreachable(&self, node: NodeInfo<'_>) {
+        self.send(ExecEvent::Unreachable { node: node.id, name: node.name.to_owned() });
+    }
+    fn on_plan(&self, order: &[NodeInfo<'_>]) {
+        self.send(ExecEvent::Planned { node_count: order.len() });
+    }
+    fn on_node_start(&self, node: NodeInfo<'_>) {
+        self.send(ExecEvent::NodeStarted { node: node.id, name: node.name.to_owned() });
+    }
+    fn on_node_finish(&self, node: NodeInfo<'_>, duration: Duration) {
+        self.send(ExecEvent::NodeFinished {
+            node: node.id,
+            name: node.name.to_owned(),
+            duration
+        });
+    }
+    fn on_value_dropped(&self, node: NodeInfo<'_>) {
+        self.send(ExecEvent::ValueDropped { node: node.id, name: node.name.to_owned() });
+    }
+    fn on_progress(&self, progress: Progress) {
+        self.send(ExecEvent::Progress(progress));
+    }
+    fn on_complete(&self, duration: Duration) {
+        self.send(ExecEvent::Completed { duration });
+    }
+}
+
+impl<T: Send + 'static> ComputationGraph<T> {
+    /// Computes the value of the output node on a new thread, returning a
+    /// receiver of the events emitted as evaluation progresses along with a
+    /// handle to join the thread.
+    /// 
+    /// The events can be consumed from any thread while the graph is being
+    /// evaluated, and the channel closes once evaluation ends. If a node
+    /// panics, a [`ExecEvent::Failed`] event is sent and joining the thread
+    /// returns the panic.
+    pub fn compute_with_events(mut self) -> (Receiver<ExecEvent>, JoinHandle<T>) {
+        let (sender, receiver) = mpsc::channel();
+        let failure_sender = sender.clone();
+        self.add_listener(Arc::new(ChannelListener(sender)));
+        let handle = thread::spawn(move || {
+            match panic::catch_unwind(AssertUnwindSafe(|| self.compute())) {
+                Ok(value) => value,
+                Err(payload) => {
+                    let message = panic_message(&*payload);
+                    let _ = failure_sender.send(ExecEvent::Failed { message });
+                    panic::resume_unwind(payload)
+                }
+            }
+        });
+        (receiver, handle)
+    }
+}
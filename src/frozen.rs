@@ -0,0 +1,111 @@
+use crate::{ComputationGraph, ComputeGraphKey, ExecutionState, NodeHandle, NodeKind,
+    RetainedValues, RunOptions};
+
+use std::collections::VecDeque;
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+/// An immutable, planned graph that can be evaluated from many threads at
+/// once, produced by [`ComputationGraph::freeze`].
+///
+/// The evaluation order is computed once when the graph is frozen, so each
+/// evaluation only runs the nodes. A frozen graph is `Send` and `Sync` when
+/// `T` is, and is typically put in an [`Arc`](std::sync::Arc) and shared
+/// between the workers of a service evaluating the same DAG per request.
+/// Nodes with state, such as those inserted with
+/// [`insert_stateful_node`](ComputationGraph::insert_stateful_node), share
+/// their state between concurrent evaluations.
+///
+/// The frozen graph itself is never modified by an evaluation. State that
+/// is carried between runs, such as values kept for nodes with
+/// [`RetentionPolicy::RetainForever`](crate::RetentionPolicy::RetainForever),
+/// is instead kept in an [`ExecutionState`] passed to
+/// [`compute_in`](Self::compute_in).
+#[derive(Debug)]
+pub struct FrozenGraph<T> {
+    graph: ComputationGraph<T>,
+    order: VecDeque<ComputeGraphKey>,
+    refcounts: SecondaryMap<ComputeGraphKey, u32>,
+    out_key: ComputeGraphKey
+}
+
+impl<T> ComputationGraph<T> {
+    /// Plans the evaluation of the graph and freezes it, so that it can be
+    /// evaluated concurrently from many threads.
+    /// 
+    /// Nodes the output does not depend on are handled according to the
+    /// [`SweepPolicy`](crate::SweepPolicy). The graph must not contain delay
+    /// or source nodes, which carry values from one run to the next.
+    pub fn freeze(mut self) -> FrozenGraph<T> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Freezing DAG");
+        let order = self.computation_order();
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            assert!(!matches!(node.kind, NodeKind::Delay(_, _) | NodeKind::Source(_)),
+                "Node {} cannot be evaluated in a frozen graph", node.name);
+        }
+        let refcounts = self.order_refcounts(&order, out_key);
+        FrozenGraph {
+            graph: self,
+            order,
+            refcounts,
+            out_key
+        }
+    }
+}
+
+impl<T> FrozenGraph<T> {
+    /// Computes the value of the output node of a graph without
+    /// placeholders.
+    pub fn compute(&self) -> T {
+        self.compute_with(Vec::<(&NodeHandle, T)>::new())
+    }
+    /// Computes the value of the output node, feeding the given values to
+    /// placeholders.
+    /// 
+    /// Handles of the graph from before it was frozen are used to identify
+    /// the placeholders. Each call is evaluated as the first run of a new
+    /// [`ExecutionState`], so calls with the same inputs are reproducible.
+    pub fn compute_with<'a, In: Into<T>>(&self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, In)>) -> T {
+        let inputs = self.graph.placeholder_values(inputs, Into::into);
+        debug!("Evaluating frozen DAG");
+        let mut values = self.graph.execute_order(&self.order, Some(self.refcounts.clone()),
+            inputs, &self.graph.run_context(0));
+        values.remove(self.out_key).unwrap()
+    }
+    /// Creates a state for a sequence of runs of the graph.
+    pub fn new_state(&self) -> ExecutionState<T> {
+        ExecutionState::new(self.graph.graph_id)
+    }
+    /// Returns the frozen graph so it can be modified again.
+    pub fn into_graph(self) -> ComputationGraph<T> {
+        self.graph
+    }
+}
+
+impl<T: Clone> FrozenGraph<T> {
+    /// Computes the value of the output node as the next run of `state`,
+    /// feeding the given values to placeholders, and returns it along with
+    /// the values of retained nodes.
+    /// 
+    /// Values kept in `state` for nodes with
+    /// [`RetentionPolicy::RetainForever`](crate::RetentionPolicy::RetainForever)
+    /// are reused instead of evaluating those nodes again while their
+    /// version is unchanged, and the run index
+    /// given to [`NodeContext`](crate::NodeContext) is that of the state, so
+    /// concurrent callers only share state they pass in explicitly.
+    pub fn compute_in<'a, In: Into<T>>(&self, state: &mut ExecutionState<T>,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, In)>) -> (T, RetainedValues<T>) {
+        debug!("Evaluating frozen DAG run {}", state.runs());
+        let mut options = RunOptions::new();
+        for (placeholder, value) in inputs {
+            options = options.input(placeholder, value.into());
+        }
+        let (value, retained, _) = self.graph.execute_run(options.in_state(state),
+            Some(&self.order), Some(T::clone));
+        (value, retained.unwrap())
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeKind, ShortCircuitOp};
+
+use slotmap::SecondaryMap;
+
+/// A 64-bit FNV-1a hasher, used instead of the standard library hashers
+/// because its output is specified and never changes between releases.
+pub(crate) struct StableHasher(u64);
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub(crate) fn new() -> StableHasher {
+        StableHasher(Self::OFFSET_BASIS)
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+    // Length-prefixed so that adjacent strings cannot run into each other
+    pub(crate) fn write_str(&mut self, value: &str) {
+        self.write_u64(value.len() as u64);
+        self.write(value.as_bytes());
+    }
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Returns a hash of the structure of the graph: the names and kinds of
+    /// its nodes, the order of each node's inputs, and which nodes are the
+    /// output, sinks and delay sources.
+    /// 
+    /// The hash does not depend on the order in which nodes were inserted,
+    /// on node IDs or on the process, so it is stable across runs and can
+    /// serve as a cache key or to detect changes to generated graphs. The
+    /// functions of nodes cannot be hashed, so graphs differing only in
+    /// the functions of identically named nodes hash the same.
+    /// 
+    /// Panics if the graph contains a cycle.
+    pub fn topology_hash(&self) -> u64 {
+        // Each node is hashed along with the hashes of its inputs, so equal
+        // hashes mean equal ancestries
+        let mut node_hashes: SecondaryMap<ComputeGraphKey, u64> = SecondaryMap::new();
+        for node_key in self.toposort_all() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let mut hasher = StableHasher::new();
+            hasher.write_str(&node.name);
+            match node.kind {
+                NodeKind::Func(_) => hasher.write_str("func"),
+                NodeKind::Unary(_) => hasher.write_str("unary"),
+                NodeKind::Binary(_) => hasher.write_str("binary"),
+                NodeKind::ContextFunc(_) => hasher.write_str("context"),
+                NodeKind::MultiFunc(_, count) => {
+                    hasher.write_str("multi");
+                    hasher.write_u64(count as u64);
+                },
+                NodeKind::MultiOutput(index) => {
+                    hasher.write_str("multi_output");
+                    hasher.write_u64(index as u64);
+                },
+                NodeKind::Placeholder => hasher.write_str("placeholder"),
+                NodeKind::Source(_) => hasher.write_str("source"),
+                NodeKind::Select(_, _) => hasher.write_str("select"),
+                NodeKind::ShortCircuit(op, _, _) => hasher.write_str(match op {
+                    ShortCircuitOp::All => "and",
+                    ShortCircuitOp::Any => "or"
+                }),
+                NodeKind::Delay(_, _) => hasher.write_str("delay"),
+                #[cfg(feature = "autodiff")]
+                NodeKind::DiffFunc(_) => hasher.write_str("diff")
+            }
+            hasher.write_u64(node.input_nodes.len() as u64);
+            for input_key in node.input_nodes.iter() {
+                hasher.write_u64(*node_hashes.get(*input_key).unwrap());
+            }
+            node_hashes.insert(node_key, hasher.finish());
+        }
+
+        // Sorting makes the result independent of storage order
+        let mut all_hashes: Vec<u64> = node_hashes.values().copied().collect();
+        all_hashes.sort_unstable();
+        let mut sink_hashes: Vec<u64> = self.sink_nodes.iter()
+            .map(|key| *node_hashes.get(*key).unwrap())
+            .collect();
+        sink_hashes.sort_unstable();
+        // Delay sources are not inputs, so they are hashed as separate edges
+        let mut delay_edges: Vec<(u64, u64)> = self.node_storage.iter()
+            .filter_map(|(key, node)| node.delay_source().map(|source| {
+                (*node_hashes.get(key).unwrap(), *node_hashes.get(source).unwrap())
+            }))
+            .collect();
+        delay_edges.sort_unstable();
+
+        let mut hasher = StableHasher::new();
+        hasher.write_u64(all_hashes.len() as u64);
+        for hash in all_hashes {
+            hasher.write_u64(hash);
+        }
+        match self.output_node {
+            Some(out_key) => hasher.write_u64(*node_hashes.get(out_key).unwrap()),
+            None => hasher.write_str("no output")
+        }
+        hasher.write_u64(sink_hashes.len() as u64);
+        for hash in sink_hashes {
+            hasher.write_u64(hash);
+        }
+        hasher.write_u64(delay_edges.len() as u64);
+        for (delay, source) in delay_edges {
+            hasher.write_u64(delay);
+            hasher.write_u64(source);
+        }
+        hasher.finish()
+    }
+}
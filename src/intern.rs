@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A set of node names handing out shared copies, so that graphs repeating
+/// the same names store each distinct name only once.
+///
+/// Interned names can be passed to any method inserting a node.
+#[derive(Debug, Clone, Default)]
+pub struct NameInterner {
+    names: HashSet<Arc<str>>
+}
+impl NameInterner {
+    /// Creates an empty interner.
+    pub fn new() -> NameInterner {
+        NameInterner::default()
+    }
+    /// Returns a shared copy of `name`, only allocating the first time a
+    /// name is seen.
+    pub fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(existing) = self.names.get(name) {
+            return existing.clone();
+        }
+        let name: Arc<str> = Arc::from(name);
+        self.names.insert(name.clone());
+        name
+    }
+    /// Returns the number of distinct names interned.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+    /// Returns `true` if no names were interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{ComputationGraph, NodeHandle};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use log::debug;
+
+/// The state of a side-effect node as recorded in a [`Journal`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalState<T> {
+    /// The node has never been started.
+    NotStarted,
+    /// The node was started but did not finish, so its side effect may or
+    /// may not have happened.
+    Started,
+    /// The node finished, producing the given value.
+    Completed(T)
+}
+
+/// A record of the side-effect nodes that were started and completed,
+/// used by nodes inserted with [`ComputationGraph::insert_effect_node`].
+///
+/// Implementations should persist entries durably before returning, so that
+/// the journal survives the process crashing in the middle of a side effect.
+pub trait Journal<T>: Send + Sync {
+    /// Returns the recorded state of the named node.
+    fn state(&self, node_name: &str) -> JournalState<T>;
+    /// Records that the named node is about to perform its side effect.
+    fn record_intent(&self, node_name: &str);
+    /// Records that the named node finished, producing `output`.
+    fn record_completion(&self, node_name: &str, output: &T);
+}
+
+/// A [`Journal`] kept in memory, which makes retries within one process
+/// safe but does not survive restarts.
+#[derive(Debug)]
+pub struct MemoryJournal<T> {
+    entries: Mutex<HashMap<String, JournalState<T>>>
+}
+impl<T> Default for MemoryJournal<T> {
+    fn default() -> Self {
+        MemoryJournal {
+            entries: Mutex::new(HashMap::new())
+        }
+    }
+}
+impl<T> MemoryJournal<T> {
+    /// Creates an empty journal.
+    pub fn new() -> MemoryJournal<T> {
+        MemoryJournal::default()
+    }
+}
+impl<T: Clone + Send> Journal<T> for MemoryJournal<T> {
+    fn state(&self, node_name: &str) -> JournalState<T> {
+        self.entries.lock().unwrap().get(node_name).cloned()
+            .unwrap_or(JournalState::NotStarted)
+    }
+    fn record_intent(&self, node_name: &str) {
+        self.entries.lock().unwrap().insert(node_name.to_owned(), JournalState::Started);
+    }
+    fn record_completion(&self, node_name: &str, output: &T) {
+        self.entries.lock().unwrap().insert(node_name.to_owned(),
+            JournalState::Completed(output.clone()));
+    }
+}
+
+impl<T: 'static> ComputationGraph<T> {
+    /// Inserts a node performing a side effect at most once, returning an
+    /// opaque node handle.
+    /// 
+    /// Before `func` runs, its intent is recorded in `journal` under the
+    /// node's name, and its output is recorded once it returns. If the
+    /// journal already marks the node as completed, the recorded output is
+    /// used instead of running `func` again, so computations can safely be
+    /// retried or resumed. If the journal shows the node as started but not
+    /// completed, the node panics rather than risk repeating the side
+    /// effect. Names of effect nodes sharing a journal must be unique.
+    pub fn insert_effect_node<F>(&mut self, name: impl Into<Arc<str>>,
+            journal: Arc<dyn Journal<T>>,
+            func: F) -> NodeHandle
+    where
+        F: Fn(&[&T]) -> T + Send + Sync + 'static
+    {
+        let name = name.into();
+        let node_name = name.clone();
+        self.insert_node(name, Box::new(move |inputs| {
+            match journal.state(&node_name) {
+                JournalState::Completed(output) => {
+                    debug!("Skipping completed effect node {}", node_name);
+                    output
+                },
+                JournalState::Started => {
+                    panic!("Effect node {} was interrupted and may already have run",
+                        node_name);
+                },
+                JournalState::NotStarted => {
+                    journal.record_intent(&node_name);
+                    let output = func(inputs);
+                    journal.record_completion(&node_name, &output);
+                    output
+                }
+            }
+        }))
+    }
+}
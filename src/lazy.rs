@@ -0,0 +1,219 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind};
+
+use std::borrow::Borrow;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use slotmap::SecondaryMap;
+use log::trace;
+
+/// How a short-circuit node combines its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShortCircuitOp {
+    /// True if every input is true, decided by the first false input.
+    All,
+    /// True if any input is true, decided by the first true input.
+    Any
+}
+
+impl<T: Clone> ComputationGraph<T> {
+    /// Inserts a node selecting between two branches, returning an opaque
+    /// node handle.
+    ///
+    /// The inputs of the node must be set to a predicate node followed by
+    /// the node whose value is taken when `is_true` holds for the value of
+    /// the predicate, and the node whose value is taken otherwise. Only the
+    /// chosen branch is evaluated, along with the ancestors it needs, as
+    /// long as the predicate is evaluated before the branches. This is the
+    /// case with [`SchedulingStrategy::DepthFirst`](crate::SchedulingStrategy::DepthFirst),
+    /// while other strategies and executors may evaluate some or all nodes
+    /// of the other branch as well. The chosen value is cloned, as the
+    /// branch may be used elsewhere.
+    pub fn insert_select_node(&mut self, name: impl Into<Arc<str>>, is_true: fn(&T) -> bool)
+            -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::Select(is_true, T::clone))
+    }
+}
+
+impl<T: From<bool>> ComputationGraph<T> {
+    /// Inserts a node computing whether `is_true` holds for the values of
+    /// all of its inputs, returning an opaque node handle.
+    ///
+    /// Evaluation short-circuits: once an input is false, the inputs not
+    /// evaluated yet are skipped along with the ancestors only they need.
+    /// With [`SchedulingStrategy::DepthFirst`](crate::SchedulingStrategy::DepthFirst),
+    /// inputs are evaluated in the order they were set, so cheap checks
+    /// should come first. A node without inputs is true.
+    pub fn insert_and_node(&mut self, name: impl Into<Arc<str>>, is_true: fn(&T) -> bool)
+            -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::ShortCircuit(ShortCircuitOp::All, is_true,
+            T::from))
+    }
+    /// Inserts a node computing whether `is_true` holds for the value of
+    /// any of its inputs, returning an opaque node handle.
+    ///
+    /// Like [`insert_and_node`](Self::insert_and_node), evaluation
+    /// short-circuits once an input is true. A node without inputs is
+    /// false.
+    pub fn insert_or_node(&mut self, name: impl Into<Arc<str>>, is_true: fn(&T) -> bool)
+            -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::ShortCircuit(ShortCircuitOp::Any, is_true,
+            T::from))
+    }
+}
+
+/// The inputs of select and short-circuit nodes that are no longer needed
+/// during one run of an evaluation order.
+pub(crate) struct LazyBranches {
+    // Position of each node in the order
+    position: SecondaryMap<ComputeGraphKey, usize>,
+    // Remaining consumers that may still need each node's value
+    live_uses: SecondaryMap<ComputeGraphKey, u32>,
+    // Lazy nodes of the order, keyed by the inputs that can decide them
+    deciders: SecondaryMap<ComputeGraphKey, Vec<ComputeGraphKey>>,
+    // Chosen branch of select nodes and result of short-circuit nodes
+    outcomes: SecondaryMap<ComputeGraphKey, bool>,
+    // Positions of the inputs each node no longer needs
+    released: SecondaryMap<ComputeGraphKey, Vec<usize>>,
+    dead: SecondaryMap<ComputeGraphKey, ()>
+}
+impl LazyBranches {
+    /// Returns whether a node was skipped because only unchosen branches
+    /// or short-circuited inputs need it.
+    pub(crate) fn is_dead(&self, key: ComputeGraphKey) -> bool {
+        self.dead.contains_key(key)
+    }
+    /// Returns which branch a select node chose or the result of a
+    /// short-circuit node, if an input already decided it.
+    pub(crate) fn outcome(&self, key: ComputeGraphKey) -> Option<bool> {
+        self.outcomes.get(key).copied()
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Prepares the lazy evaluation of the select and short-circuit nodes in
+    /// `order`, or returns `None` if it contains none. Inputs whose values
+    /// are already in `values` are taken into account right away.
+    pub(crate) fn lazy_branches<V: Borrow<T>>(&self, order: &VecDeque<ComputeGraphKey>,
+            values: &SecondaryMap<ComputeGraphKey, V>) -> Option<LazyBranches> {
+        let is_lazy = |key: ComputeGraphKey| matches!(self.node_storage.get(key).unwrap().kind,
+            NodeKind::Select(..) | NodeKind::ShortCircuit(..));
+        if !order.iter().any(|key| is_lazy(*key)) {
+            return None;
+        }
+        let mut branches = LazyBranches {
+            position: SecondaryMap::new(),
+            live_uses: SecondaryMap::new(),
+            deciders: SecondaryMap::new(),
+            outcomes: SecondaryMap::new(),
+            released: SecondaryMap::new(),
+            dead: SecondaryMap::new()
+        };
+        for (index, node_key) in order.iter().copied().enumerate() {
+            branches.position.insert(node_key, index);
+            branches.live_uses.insert(node_key, 0);
+        }
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            for input_key in node.input_nodes.iter() {
+                if let Some(uses) = branches.live_uses.get_mut(*input_key) {
+                    *uses += 1;
+                }
+            }
+            let deciding_inputs = match node.kind {
+                NodeKind::Select(..) => &node.input_nodes[..1],
+                NodeKind::ShortCircuit(..) => &node.input_nodes[..],
+                _ => &[]
+            };
+            for input_key in deciding_inputs.iter().copied() {
+                let consumers = branches.deciders.entry(input_key).unwrap().or_default();
+                if !consumers.contains(&node_key) {
+                    consumers.push(node_key);
+                }
+            }
+        }
+        // Roots and carried values are needed regardless of any branch
+        let pinned = order.iter().copied()
+            .filter(|key| branches.live_uses[*key] == 0)
+            .chain(self.output_node)
+            .chain(self.sink_nodes.iter().copied())
+            .chain(order.iter().filter_map(|key| {
+                self.node_storage.get(*key).unwrap().delay_source()
+            }))
+            .collect::<Vec<_>>();
+        for key in pinned {
+            if let Some(uses) = branches.live_uses.get_mut(key) {
+                *uses += 1;
+            }
+        }
+        let known: Vec<_> = branches.deciders.keys()
+            .filter(|key| values.contains_key(*key))
+            .collect();
+        for input_key in known {
+            self.resolve_branches(&mut branches, input_key, values, 0);
+        }
+        Some(branches)
+    }
+    /// Decides every select and short-circuit node that the value of the
+    /// node at `key` decides, once it is in `values`, and marks the nodes
+    /// after `position` in the order that only their released inputs need.
+    pub(crate) fn resolve_branches<V: Borrow<T>>(&self, branches: &mut LazyBranches,
+            key: ComputeGraphKey, values: &SecondaryMap<ComputeGraphKey, V>, position: usize) {
+        let Some(consumers) = branches.deciders.get(key) else {
+            return;
+        };
+        let Some(value) = values.get(key).map(Borrow::borrow) else {
+            return;
+        };
+        // Inputs no longer needed by a consumer, as (consumer, input position)
+        let mut released = Vec::new();
+        for consumer_key in consumers.iter().copied() {
+            if branches.dead.contains_key(consumer_key)
+                    || branches.outcomes.contains_key(consumer_key) {
+                continue;
+            }
+            let consumer = self.node_storage.get(consumer_key).unwrap();
+            match consumer.kind {
+                NodeKind::Select(is_true, _) => {
+                    let chosen = is_true(value);
+                    trace!(node = &*consumer.name; "Select node {} chose its {} branch",
+                        consumer.name, if chosen { "true" } else { "false" });
+                    branches.outcomes.insert(consumer_key, chosen);
+                    released.push((consumer_key, if chosen { 2 } else { 1 }));
+                },
+                NodeKind::ShortCircuit(op, is_true, _) => {
+                    let decisive = op == ShortCircuitOp::Any;
+                    if is_true(value) != decisive {
+                        continue;
+                    }
+                    trace!(node = &*consumer.name; "Node {} short-circuited", consumer.name);
+                    branches.outcomes.insert(consumer_key, decisive);
+                    let deciding_index = consumer.input_nodes.iter()
+                        .position(|input_key| *input_key == key);
+                    released.extend((0..consumer.input_nodes.len())
+                        .filter(|index| Some(*index) != deciding_index)
+                        .map(|index| (consumer_key, index)));
+                },
+                _ => unreachable!("Only select and short-circuit nodes are decided")
+            }
+        }
+        // Release the uses of the inputs, then those of every node left
+        // without uses
+        while let Some((consumer_key, index)) = released.pop() {
+            branches.released.entry(consumer_key).unwrap().or_default().push(index);
+            let input_key = self.node_storage.get(consumer_key).unwrap().input_nodes[index];
+            let Some(uses) = branches.live_uses.get_mut(input_key) else {
+                continue;
+            };
+            *uses -= 1;
+            if *uses > 0 || branches.position[input_key] < position {
+                continue;
+            }
+            branches.dead.insert(input_key, ());
+            let already_released = branches.released.get(input_key);
+            released.extend((0..self.node_storage.get(input_key).unwrap().input_nodes.len())
+                .filter(|index| !already_released.is_some_and(|indices| indices.contains(index)))
+                .map(|index| (input_key, index)));
+        }
+    }
+}
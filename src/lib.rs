@@ -3,48 +3,405 @@
 
 use slotmap::{SlotMap, SecondaryMap, new_key_type};
 use slotmap::Key as KeyTrait;
+use smallvec::SmallVec;
 
-use std::collections::{HashSet, HashMap, VecDeque};
-use std::sync::Arc;
-use std::ops::Deref;
-use std::marker::PhantomData;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
 
-use log::{info, debug, trace};
+use log::{info, debug};
+
+/// Logs a message about a node, with the node name, node ID and graph ID as
+/// structured key-value fields so that log aggregation can group by node.
+/// The node name is also appended to the message.
+/// 
+/// The message is logged at the given level and under the current module's
+/// target unless overridden for the node with
+/// [`ComputationGraph::set_log_level`] or [`ComputationGraph::set_log_target`].
+macro_rules! node_log {
+    ($level:ident, $graph:expr, $key:expr, $name:expr, $message:literal) => {{
+        let settings = $graph.node_logging.get($key);
+        let level = settings.and_then(|settings| settings.level)
+            .unwrap_or(log::Level::$level);
+        let target = settings.and_then(|settings| settings.target.as_deref())
+            .unwrap_or(module_path!());
+        log::log!(target: target, level,
+            node = &*$name,
+            node_id = slotmap::Key::data(&$key).as_ffi(),
+            graph_id = $graph.graph_id;
+            concat!($message, " {}"), $name)
+    }};
+}
+
+mod any_value;
+pub use any_value::{AnyValue, DowncastError};
+
+mod stream;
+pub use stream::FixedPoint;
+
+mod batch;
+
+mod partition;
+pub use partition::{PartitionPlan, SubPlan, PlanNode, DataDependency};
+
+mod remote;
+pub use remote::RemoteExecutor;
+
+mod journal;
+pub use journal::{Journal, JournalState, MemoryJournal};
+
+mod provenance;
+pub use provenance::{Provenance, Traced};
+
+mod lineage;
+
+mod intern;
+pub use intern::NameInterner;
+
+mod merge;
+pub use merge::MergedHandles;
+
+mod schedule;
+pub use schedule::SchedulingStrategy;
+
+mod profile;
+pub use profile::{ExecutionReport, NodeTiming};
+
+mod retention;
+pub use retention::{RetainedValues, RetentionPolicy};
+
+mod listener;
+use listener::ListenerList;
+pub use listener::{ExecutionListener, NodeInfo, Progress};
+
+mod events;
+pub use events::ExecEvent;
+
+mod watchdog;
+pub use watchdog::Watchdog;
+
+mod logging;
+use logging::NodeLogging;
+
+mod namespace;
+
+mod names;
+use names::NameGenerator;
+
+mod dedup;
+use dedup::DedupIndex;
+
+mod hash;
+
+mod size;
+pub use size::SizeHint;
+
+mod tap;
+use tap::Tap;
+
+mod pipeline;
+
+mod source;
+use source::BoxedSource;
+
+mod window;
+pub use window::Window;
+
+mod seed;
+pub use seed::NodeRng;
+
+mod clock;
+use clock::ClockHook;
+pub use clock::{Clock, ManualClock, SystemClock};
+
+mod overrides;
+use overrides::NodeOverride;
+
+mod frozen;
+pub use frozen::FrozenGraph;
+
+mod state;
+pub use state::ExecutionState;
+
+mod run;
+pub use run::{RunOptions, RunOutput};
+
+mod errors;
+pub use errors::{ErrorMode, ExecutionFailure, NodeFailure};
+
+mod tags;
+use tags::TagFilter;
+
+mod lazy;
+use lazy::ShortCircuitOp;
+
+mod speculative;
+
+mod memory;
+pub use memory::MemoryEstimate;
+
+mod lint;
+use lint::LintHints;
+pub use lint::{LintFinding, LintKind};
+
+mod sweep;
+pub use sweep::SweepPolicy;
+
+mod summary;
+pub use summary::ExecutionSummary;
+
+mod stats;
+pub use stats::{NodeStats, StatsCollector, StatsReport};
+
+mod dump;
+use dump::ValueDump;
+pub use dump::DumpTarget;
+
+#[cfg(feature = "record")]
+mod record;
+#[cfg(feature = "record")]
+pub use record::TraceRecord;
+
+#[cfg(feature = "record")]
+mod baseline;
+#[cfg(feature = "record")]
+pub use baseline::Divergence;
+
+mod offload;
+use offload::OffloadHook;
+pub use offload::OffloadExecutor;
+
+mod passes;
+pub use passes::{GraphPass, NodeMetadata, PassReport};
+pub use passes::{CommonSubexpressionElimination, ConstantFolding, DeadNodeElimination, NodeFusion};
+
+#[cfg(feature = "derive")]
+pub use dag_compute_derive::ComputeNode;
+
+#[cfg(feature = "autodiff")]
+mod autodiff;
+#[cfg(feature = "autodiff")]
+pub use autodiff::{DiffOp, Differentiable, GradientGraph};
+
+#[cfg(feature = "ndarray")]
+mod array;
+#[cfg(feature = "ndarray")]
+pub use array::{ArrayGraphBuilder, ShapeError};
 
 new_key_type!{struct ComputeGraphKey;}
 
+static NEXT_GRAPH_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Most nodes have only a few inputs, which are then stored inline
+type InputList = SmallVec<[ComputeGraphKey; 4]>;
+
+// State of a node during the depth-first toposort
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DfsMark {
+    InProgress,
+    Finished
+}
+
+/// Empties a Vec of references so its allocation can be reused for
+/// references with a different lifetime.
+fn recycle_refs<'b, T>(mut refs: Vec<&T>) -> Vec<&'b T> {
+    refs.clear();
+    // The closure never runs, and collecting in place keeps the allocation
+    refs.into_iter().map(|_| unreachable!()).collect()
+}
+
 type BoxedEvalFn<T> = Box<dyn Fn(&[&T]) -> T + Send + Sync>;
+type BoxedMultiEvalFn<T> = Box<dyn Fn(&[&T]) -> Vec<T> + Send + Sync>;
+type BoxedContextEvalFn<T> = Box<dyn Fn(&NodeContext, &[&T]) -> T + Send + Sync>;
+
+/// Information about the current run, passed to nodes inserted with
+/// [`ComputationGraph::insert_context_node`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NodeContext {
+    run_index: usize,
+    seed: u64,
+    node_seed: u64,
+    clock: ClockHook
+}
+impl NodeContext {
+    pub(crate) fn new(run_index: usize, seed: u64, clock: ClockHook) -> NodeContext {
+        NodeContext {
+            run_index,
+            seed,
+            node_seed: 0,
+            clock
+        }
+    }
+    /// Returns a copy of the context for the node with the given name.
+    fn for_node(&self, name: &str) -> NodeContext {
+        NodeContext {
+            node_seed: seed::node_seed(self.seed, name),
+            ..self.clone()
+        }
+    }
+    /// Returns the index of the current run in a batch of runs, or 0 for a
+    /// single run.
+    pub fn run_index(&self) -> usize {
+        self.run_index
+    }
+    /// Returns the RNG seed for the current run, or 0 if none was given.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    /// Returns an RNG seed for the current node, derived from the seed of
+    /// the current run and the name of the node.
+    /// 
+    /// Nodes with different names therefore draw independent values, while
+    /// rebuilding the graph or evaluating it in a different order leaves the
+    /// seed of every node unchanged.
+    pub fn node_seed(&self) -> u64 {
+        self.node_seed
+    }
+    /// Returns a new RNG seeded with [`node_seed`](Self::node_seed).
+    pub fn rng(&self) -> NodeRng {
+        NodeRng::new(self.node_seed)
+    }
+    /// Returns the current time according to the clock of the graph being
+    /// evaluated, set with [`ComputationGraph::set_clock`].
+    pub fn now(&self) -> Instant {
+        self.clock.0.now()
+    }
+}
+
+pub(crate) enum NodeKind<T> {
+    Func(BoxedEvalFn<T>),
+    // Plain function pointers avoid the Box<dyn Fn> indirection for tiny ops
+    Unary(fn(&T) -> T),
+    Binary(fn(&T, &T) -> T),
+    ContextFunc(BoxedContextEvalFn<T>),
+    // Values are read through MultiOutput nodes, never directly
+    MultiFunc(BoxedMultiEvalFn<T>, usize),
+    // Selects one value of the MultiFunc node that is its sole input
+    MultiOutput(usize),
+    Placeholder,
+    // Values pulled by the executor, one per run
+    Source(BoxedSource<T>),
+    // Predicate on the first input choosing between the other two, and the
+    // function cloning the chosen value
+    Select(fn(&T) -> bool, fn(&T) -> T),
+    // Predicate on each input and the function converting the result
+    ShortCircuit(ShortCircuitOp, fn(&T) -> bool, fn(bool) -> T),
+    // Initial value and the node whose value is carried to the next iteration
+    Delay(T, Option<ComputeGraphKey>),
+    #[cfg(feature = "autodiff")]
+    DiffFunc(Arc<dyn DiffOp<T>>)
+}
+impl<T> fmt::Debug for NodeKind<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeKind::Func(_) => write!(f, "Func(...)"),
+            NodeKind::Unary(_) => write!(f, "Unary(...)"),
+            NodeKind::Binary(_) => write!(f, "Binary(...)"),
+            NodeKind::ContextFunc(_) => write!(f, "ContextFunc(...)"),
+            NodeKind::MultiFunc(_, count) => write!(f, "MultiFunc(..., {})", count),
+            NodeKind::MultiOutput(index) => write!(f, "MultiOutput({})", index),
+            NodeKind::Placeholder => write!(f, "Placeholder"),
+            NodeKind::Source(_) => write!(f, "Source(...)"),
+            NodeKind::Select(_, _) => write!(f, "Select(...)"),
+            NodeKind::ShortCircuit(op, _, _) => write!(f, "ShortCircuit({:?}, ...)", op),
+            NodeKind::Delay(_, source) => write!(f, "Delay(..., {:?})", source),
+            #[cfg(feature = "autodiff")]
+            NodeKind::DiffFunc(_) => write!(f, "DiffFunc(...)")
+        }
+    }
+}
 
 pub(crate) struct Node<T> {
-    name: String,
-    func: BoxedEvalFn<T>,
-    input_nodes: Vec<ComputeGraphKey>,
-    output_cache: Option<Arc<T>>
+    name: Arc<str>,
+    kind: NodeKind<T>,
+    input_nodes: InputList,
+    pure: bool,
+    device: Option<String>,
+    version_tag: Option<String>,
+    tags: Vec<String>,
+    speculative: bool,
+    retention: RetentionPolicy
 }
 impl<T> Node<T> {
-    fn new(name: String, func: BoxedEvalFn<T>) -> Node<T> {
+    fn new(name: Arc<str>, kind: NodeKind<T>) -> Node<T> {
         Node {
             name,
-            func,
-            input_nodes: Vec::default(),
-            output_cache: None
+            kind,
+            input_nodes: InputList::default(),
+            pure: false,
+            device: None,
+            version_tag: None,
+            tags: Vec::new(),
+            speculative: false,
+            retention: RetentionPolicy::default()
+        }
+    }
+    fn is_placeholder(&self) -> bool {
+        matches!(self.kind, NodeKind::Placeholder)
+    }
+    fn is_multi_func(&self) -> bool {
+        matches!(self.kind, NodeKind::MultiFunc(_, _))
+    }
+    fn delay_source(&self) -> Option<ComputeGraphKey> {
+        match self.kind {
+            NodeKind::Delay(_, source) => source,
+            _ => None
         }
     }
     // Passing arg slice instead of node handles is a leaky encapsulation
     // Doesn't seem to be possible to remove leakiness safely though?
-    pub fn eval(&mut self, args: &[&T]) {
-        if self.output_cache.is_none() {
-            self.output_cache = Some(Arc::new((self.func)(args)));
-        } else {
-            panic!("Node is already evaluated");
+    fn call(&self, args: &[&T], context: &NodeContext) -> T {
+        match self.kind {
+            NodeKind::Func(ref func) => func(args),
+            NodeKind::Unary(func) => {
+                assert_eq!(args.len(), 1, "Node {} expected 1 input but received {}",
+                    self.name, args.len());
+                func(args[0])
+            },
+            NodeKind::Binary(func) => {
+                assert_eq!(args.len(), 2, "Node {} expected 2 inputs but received {}",
+                    self.name, args.len());
+                func(args[0], args[1])
+            },
+            NodeKind::ContextFunc(ref func) => func(&context.for_node(&self.name), args),
+            NodeKind::Select(is_true, clone_value) => {
+                assert_eq!(args.len(), 3, "Node {} expected 3 inputs but received {}",
+                    self.name, args.len());
+                clone_value(if is_true(args[0]) { args[1] } else { args[2] })
+            },
+            NodeKind::ShortCircuit(op, is_true, from_bool) => from_bool(match op {
+                ShortCircuitOp::All => args.iter().all(|arg| is_true(arg)),
+                ShortCircuitOp::Any => args.iter().any(|arg| is_true(arg))
+            }),
+            #[cfg(feature = "autodiff")]
+            NodeKind::DiffFunc(ref op) => op.eval(args),
+            NodeKind::MultiFunc(_, _) | NodeKind::MultiOutput(_) => {
+                unreachable!("Multi-output nodes are evaluated separately");
+            },
+            NodeKind::Placeholder => {
+                panic!("Placeholder {} was not given a value", self.name);
+            },
+            NodeKind::Source(_) => {
+                panic!("Source node {} can only be evaluated with compute_sources",
+                    self.name);
+            },
+            NodeKind::Delay(_, _) => {
+                panic!("Delay node {} can only be evaluated with compute_iterations",
+                    self.name);
+            }
         }
     }
-    pub fn computed_val(&self) -> Arc<T> {
-        if let Some(ref val) = self.output_cache {
-            val.clone()
+    fn call_multi(&self, args: &[&T]) -> Vec<T> {
+        if let NodeKind::MultiFunc(ref func, count) = self.kind {
+            let outputs = func(args);
+            assert_eq!(outputs.len(), count,
+                "Node {} returned the wrong number of outputs", self.name);
+            outputs
         } else {
-            panic!("Node has not yet been evaluated");
+            unreachable!("Node {} is not a multi-output node", self.name);
         }
     }
 }
@@ -52,9 +409,14 @@ impl<T: fmt::Debug> fmt::Debug for Node<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "NodeHandle {{ ")?;
         write!(f, "name: {:?}, ", self.name)?;
-        write!(f, "func: ..., ")?;
+        write!(f, "kind: {:?}, ", self.kind)?;
         write!(f, "input_nodes: {:?}, ", self.input_nodes)?;
-        write!(f, "output_cache: {:?}", self.output_cache)?;
+        write!(f, "pure: {:?}, ", self.pure)?;
+        write!(f, "device: {:?}, ", self.device)?;
+        write!(f, "version_tag: {:?}, ", self.version_tag)?;
+        write!(f, "tags: {:?}, ", self.tags)?;
+        write!(f, "speculative: {:?}, ", self.speculative)?;
+        write!(f, "retention: {:?}", self.retention)?;
         write!(f, " }}")
     }
 }
@@ -67,26 +429,80 @@ pub struct NodeHandle {
     graph_id: usize
 }
 
+/// A node type that can be inserted with [`ComputationGraph::insert_compute_node`].
+///
+/// With the `derive` feature enabled, this can be derived for a struct whose
+/// fields are all `&T` inputs and which has an inherent `eval(&self) -> T`
+/// method. Each field is filled in from the corresponding input, in
+/// declaration order, and the node name defaults to the struct name in
+/// snake case. Use `#[compute_node(name = "...")]` to override the name, and
+/// `#[compute_node(value = Type)]` to specify `T` for structs without fields.
+pub trait ComputeNode<T> {
+    /// The number of inputs the node expects.
+    const ARITY: usize;
+    /// Returns the name used when inserting the node.
+    fn node_name() -> String;
+    /// Evaluates the node on its inputs.
+    fn eval_inputs(inputs: &[&T]) -> T;
+}
+
 /// A DAG that expresses a computation flow between nodes.
 #[derive(Debug)]
 pub struct ComputationGraph<T> {
     node_storage: SlotMap<ComputeGraphKey, Node<T>>,
     node_refcount: SecondaryMap<ComputeGraphKey, u32>,
     output_node: Option<ComputeGraphKey>,
+    sink_nodes: Vec<ComputeGraphKey>,
+    disabled_namespaces: Vec<String>,
+    name_generator: NameGenerator,
+    dedup_index: Option<DedupIndex>,
+    offload_executor: Option<OffloadHook<T>>,
+    scheduling_strategy: SchedulingStrategy,
+    sweep_policy: SweepPolicy,
+    node_costs: SecondaryMap<ComputeGraphKey, Duration>,
+    node_sizes: SecondaryMap<ComputeGraphKey, usize>,
+    state: ExecutionState<T>,
+    listeners: ListenerList,
+    value_dumps: Vec<ValueDump<T>>,
+    taps: Vec<Tap<T>>,
+    node_logging: SecondaryMap<ComputeGraphKey, NodeLogging>,
+    seed: Option<u64>,
+    clock: ClockHook,
+    overrides: SecondaryMap<ComputeGraphKey, NodeOverride<T>>,
+    error_mode: ErrorMode,
+    lint_hints: LintHints,
     graph_id: usize
 }
 impl<T> Default for ComputationGraph<T> {
     fn default() -> Self {
-        let mut obj = ComputationGraph {
+        // Use a process-wide counter to tie NodeHandles to ComputationGraphs
+        // Addresses are reused, e.g. by graphs built on separate threads
+        let graph_id = NEXT_GRAPH_ID.fetch_add(1, Ordering::Relaxed);
+        ComputationGraph {
             node_storage: SlotMap::default(),
             node_refcount: SecondaryMap::default(),
             output_node: None,
-            graph_id: 0
-        };
-        // Use pointer numerical value to tie NodeHandles to ComputationGraphs
-        // No potential risks here as we only need this as an opaque token
-        obj.graph_id = (&obj.node_storage as *const SlotMap<_,_>) as usize;
-        obj
+            sink_nodes: Vec::new(),
+            disabled_namespaces: Vec::new(),
+            name_generator: NameGenerator::default(),
+            dedup_index: None,
+            offload_executor: None,
+            scheduling_strategy: SchedulingStrategy::default(),
+            sweep_policy: SweepPolicy::default(),
+            node_costs: SecondaryMap::default(),
+            node_sizes: SecondaryMap::default(),
+            state: ExecutionState::new(graph_id),
+            listeners: ListenerList::default(),
+            value_dumps: Vec::new(),
+            taps: Vec::new(),
+            node_logging: SecondaryMap::default(),
+            seed: None,
+            clock: ClockHook::default(),
+            overrides: SecondaryMap::default(),
+            error_mode: ErrorMode::default(),
+            lint_hints: LintHints::default(),
+            graph_id
+        }
     }
 }
 impl<T> ComputationGraph<T> {
@@ -96,242 +512,805 @@ impl<T> ComputationGraph<T> {
     /// Inserts a new node, returning an opaque node handle.
     /// 
     /// While the library does not enforce name uniqueness, this is
-    /// highly recommended to make debugging easier.
-    pub fn insert_node(&mut self, name: String, func: BoxedEvalFn<T>) -> NodeHandle {
-        let node = Node::new(name, func);
+    /// highly recommended to make debugging easier. Names are stored as
+    /// `Arc<str>`, so nodes sharing a name, such as one obtained from a
+    /// [`NameInterner`], can share a single allocation.
+    pub fn insert_node(&mut self, name: impl Into<Arc<str>>, func: BoxedEvalFn<T>) -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::Func(func))
+    }
+    /// Inserts a new node applying a function to its single input,
+    /// returning an opaque node handle.
+    /// 
+    /// Unlike [`insert_node`](Self::insert_node), the function is stored as
+    /// a plain function pointer, which avoids a boxed closure call in graphs
+    /// made of many tiny operations. Closures that capture nothing can be
+    /// passed directly.
+    pub fn insert_unary_node(&mut self, name: impl Into<Arc<str>>, func: fn(&T) -> T)
+            -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::Unary(func))
+    }
+    /// Inserts a new node applying a function to its two inputs, returning
+    /// an opaque node handle.
+    /// 
+    /// As with [`insert_unary_node`](Self::insert_unary_node), the function
+    /// is stored as a plain function pointer.
+    pub fn insert_binary_node(&mut self, name: impl Into<Arc<str>>, func: fn(&T, &T) -> T)
+            -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::Binary(func))
+    }
+    /// Inserts a new node whose function also receives a [`NodeContext`],
+    /// returning an opaque node handle.
+    /// 
+    /// This allows stochastic nodes to seed their RNGs from the seed set
+    /// with [`set_seed`](Self::set_seed) or given to
+    /// [`compute_monte_carlo`](Self::compute_monte_carlo).
+    pub fn insert_context_node(&mut self, name: impl Into<Arc<str>>,
+            func: BoxedContextEvalFn<T>) -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::ContextFunc(func))
+    }
+    /// Inserts a new node that carries mutable state between evaluations,
+    /// returning an opaque node handle.
+    /// 
+    /// The state starts as `initial_state` and is passed to `func` on every
+    /// evaluation, which is mainly useful when the graph is evaluated
+    /// repeatedly, such as with [`compute_stream`](Self::compute_stream).
+    pub fn insert_stateful_node<S, F>(&mut self, name: impl Into<Arc<str>>, initial_state: S,
+            func: F) -> NodeHandle
+    where
+        S: Send + 'static,
+        F: Fn(&mut S, &[&T]) -> T + Send + Sync + 'static
+    {
+        let state = Mutex::new(initial_state);
+        self.insert_node(name, Box::new(move |inputs| {
+            let mut state = state.lock().unwrap();
+            func(&mut state, inputs)
+        }))
+    }
+    fn insert_node_kind(&mut self, name: impl Into<Arc<str>>, kind: NodeKind<T>)
+            -> NodeHandle {
+        let node = Node::new(name.into(), kind);
         let node_key = self.node_storage.insert(node);
         self.node_refcount.insert(node_key, 0);
+        self.index_node(node_key);
         NodeHandle {
             node_key,
             graph_id: self.graph_id
         }
     }
+    /// Inserts a node of the given [`ComputeNode`] type.
+    /// 
+    /// The node panics at computation time, naming the node, if its inputs
+    /// were not set to exactly `N::ARITY` nodes.
+    pub fn insert_compute_node<N: ComputeNode<T>>(&mut self) -> NodeHandle {
+        let name: Arc<str> = N::node_name().into();
+        let node_name = name.clone();
+        let handle = self.insert_node(name, Box::new(move |inputs| {
+            assert_eq!(inputs.len(), N::ARITY,
+                "Node {} expected {} inputs but received {}",
+                node_name, N::ARITY, inputs.len());
+            N::eval_inputs(inputs)
+        }));
+        self.lint_hints.arity.insert(handle.node_key, N::ARITY);
+        handle
+    }
+    /// Inserts a placeholder node, returning an opaque node handle.
+    /// 
+    /// Placeholders have no inputs and take on the value supplied to
+    /// [`compute_with`](Self::compute_with) at computation time.
+    pub fn insert_placeholder(&mut self, name: impl Into<Arc<str>>) -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::Placeholder)
+    }
+    /// Inserts a node that computes several outputs at once.
+    /// 
+    /// Returns a handle for setting the node's inputs, along with one handle
+    /// per output for use as inputs to other nodes or as the graph output.
+    /// The node itself cannot be used as an input or designated as output.
+    /// Output handles are named `"{name}.{index}"`, and `func` must return
+    /// exactly `output_count` values.
+    pub fn insert_multi_output_node(&mut self, name: impl Into<Arc<str>>, output_count: usize,
+            func: BoxedMultiEvalFn<T>) -> (NodeHandle, Vec<NodeHandle>) {
+        let name = name.into();
+        let output_names: Vec<_> = (0..output_count)
+            .map(|index| format!("{}.{}", name, index))
+            .collect();
+        let multi_handle = self.insert_node_kind(name,
+            NodeKind::MultiFunc(func, output_count));
+        let output_handles = output_names.into_iter().enumerate()
+            .map(|(index, output_name)| {
+                let handle = self.insert_node_kind(output_name,
+                    NodeKind::MultiOutput(index));
+                self.node_storage.get_mut(handle.node_key).unwrap()
+                    .input_nodes.push(multi_handle.node_key);
+                *self.node_refcount.get_mut(multi_handle.node_key).unwrap() += 1;
+                handle
+            })
+            .collect();
+        (multi_handle, output_handles)
+    }
+    /// Marks a node as pure, meaning that its function has no side effects
+    /// and always returns the same value for the same inputs.
+    /// 
+    /// Optimization passes such as [`ConstantFolding`] may evaluate pure
+    /// nodes ahead of time.
+    pub fn mark_pure(&mut self, node: &NodeHandle) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let node = self.node_storage.get_mut(node.node_key).unwrap();
+        assert!(!node.is_placeholder()
+                && !matches!(node.kind, NodeKind::Delay(_, _) | NodeKind::Source(_)),
+            "Placeholders, source and delay nodes cannot be marked pure");
+        node.pure = true;
+    }
+    /// Inserts a delay node, returning an opaque node handle.
+    /// 
+    /// When the graph is evaluated repeatedly with
+    /// [`compute_iterations`](Self::compute_iterations), a delay node takes on
+    /// the value its source had in the previous iteration, starting with
+    /// `initial`. This allows feedback loops such as IIR filters to be
+    /// expressed without creating a cycle.
+    pub fn insert_delay(&mut self, name: impl Into<Arc<str>>, initial: T) -> NodeHandle {
+        self.insert_node_kind(name, NodeKind::Delay(initial, None))
+    }
+    /// Sets the node whose value the given delay node carries forward.
+    /// 
+    /// Unlike inputs, the source may depend on the delay node itself.
+    pub fn set_delay_source(&mut self, delay: &mut NodeHandle, source: &NodeHandle) {
+        assert_eq!(delay.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        assert_eq!(source.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        assert!(!self.node_storage.get(source.node_key).unwrap().is_multi_func(),
+            "Multi-output nodes must be used through their output handles");
+        let delay_node = self.node_storage.get_mut(delay.node_key).unwrap();
+        let old_source = match delay_node.kind {
+            NodeKind::Delay(_, ref mut delay_source) => delay_source.replace(source.node_key),
+            _ => panic!("Node {} is not a delay node", delay_node.name)
+        };
+        if let Some(old_key) = old_source {
+            *self.node_refcount.get_mut(old_key).unwrap() -= 1;
+        }
+        *self.node_refcount.get_mut(source.node_key).unwrap() += 1;
+    }
     /// Returns a reference to a node's name.
     pub fn node_name(&self, node: &NodeHandle) -> &str {
         assert_eq!(node.graph_id, self.graph_id,
             "Received NodeHandle for different graph");
         &self.node_storage.get(node.node_key).unwrap().name
     }
+    /// Returns the numeric ID of a node, as used in DOT output and
+    /// [`PartitionPlan`]s.
+    pub fn node_id(&self, node: &NodeHandle) -> u64 {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        node.node_key.data().as_ffi()
+    }
     /// Designates the given node as the output node.
     pub fn designate_output(&mut self, node: &NodeHandle) {
         self.output_node.ok_or(()).expect_err("Output was already designated");
         assert_eq!(node.graph_id, self.graph_id,
             "Received NodeHandle for different graph");
         let node_key = node.node_key;
-        assert!(self.node_storage.contains_key(node_key));
+        assert!(!self.node_storage.get(node_key).unwrap().is_multi_func(),
+            "Multi-output nodes must be used through their output handles");
         self.output_node = Some(node_key);
         *self.node_refcount.get_mut(node_key).unwrap() += 1;
     }
+    /// Designates the given node as a sink, which is evaluated along with
+    /// the output node even though the output does not depend on it.
+    /// 
+    /// Sinks are meant for side-effecting terminals such as nodes writing
+    /// files, so that they do not need an artificial join with the output.
+    /// Their values are discarded at the end of each evaluation.
+    pub fn add_sink(&mut self, node: &NodeHandle) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let node_key = node.node_key;
+        assert!(!self.node_storage.get(node_key).unwrap().is_multi_func(),
+            "Multi-output nodes must be used through their output handles");
+        assert!(!self.sink_nodes.contains(&node_key), "Node is already a sink");
+        self.sink_nodes.push(node_key);
+        *self.node_refcount.get_mut(node_key).unwrap() += 1;
+    }
     /// Sets the given node's inputs.
     /// 
     /// It is the caller's responsibility to avoid creating loops,
-    /// which are only detected at computation time.
+    /// which are only detected at computation time. If deduplication is
+    /// enabled, `node` may be pointed to an equivalent existing node, as
+    /// described in [`set_deduplication`](Self::set_deduplication).
     pub fn set_inputs(&mut self, node: &mut NodeHandle, inputs: &[&NodeHandle]) {
         assert_eq!(node.graph_id, self.graph_id,
             "Received NodeHandle for different graph");
-        let input_keys: Vec<_> = inputs.iter().map(|handle| handle.node_key).collect();
+        match self.node_storage.get(node.node_key).unwrap().kind {
+            NodeKind::Placeholder => panic!("Placeholders cannot have inputs"),
+            NodeKind::Source(_) => panic!("Source nodes cannot have inputs"),
+            NodeKind::MultiOutput(_) => {
+                panic!("Multi-output node inputs must be set through the node handle")
+            },
+            _ => {}
+        }
+        let input_keys: InputList = inputs.iter().map(|handle| handle.node_key).collect();
+        assert!(input_keys.iter()
+                .all(|key| !self.node_storage.get(*key).unwrap().is_multi_func()),
+            "Multi-output nodes must be used through their output handles");
         // Mutability rules actually enforce the non-circular-loop case
         // Keep assert in case duplication happens elsewhere
         assert!(!input_keys.contains(&node.node_key), "Inputs would create self-loop");
         // Other cycles would be caught at computation time
+        if let Some(existing_key) = self.find_duplicate(node.node_key, &input_keys) {
+            self.remove_unused_node(node.node_key);
+            node.node_key = existing_key;
+            return;
+        }
 
         for key in input_keys.iter() {
             *self.node_refcount.get_mut(*key).unwrap() += 1;
         }
         self.node_storage.get_mut(node.node_key).unwrap().input_nodes = input_keys;
+        self.index_node(node.node_key);
+    }
+    /// Removes the nodes whose values the output does not depend on,
+    /// returning the number of nodes removed.
+    /// 
+    /// This happens automatically at computation time, but pruning ahead of
+    /// time keeps DOT output and other inspection free of dead nodes.
+    /// Handles to removed nodes become invalid.
+    pub fn prune(&mut self) -> usize {
+        debug!("Pruning unreachable nodes");
+        self.prune_names().len()
+    }
+    /// Releases memory over-allocated while constructing the graph, such as
+    /// spare capacity in node input lists.
+    /// 
+    /// This is mainly useful for graphs that are kept around for repeated
+    /// evaluation, after construction and pruning are done. Slots of removed
+    /// nodes cannot be released without invalidating handles, but are reused
+    /// by nodes inserted later.
+    pub fn shrink_to_fit(&mut self) {
+        debug!("Shrinking DAG storage");
+        for node in self.node_storage.values_mut() {
+            node.input_nodes.shrink_to_fit();
+        }
+        let mut node_refcount = SecondaryMap::with_capacity(self.node_storage.capacity());
+        node_refcount.extend(self.node_refcount.drain());
+        self.node_refcount = node_refcount;
     }
     /// Emits a DOT graph of the computation graph.
     /// 
     /// Nodes are labeled with names, and the output node is rectangular.
     pub fn dot_graph(&self) -> impl fmt::Display + '_ {
-        DAGComputeDisplay::new(self)
+        DAGComputeDisplay::new(self, None)
+    }
+    /// Emits a DOT graph of the computation graph, showing which nodes would
+    /// be swept at computation time.
+    /// 
+    /// Nodes the output depends on are drawn as in
+    /// [`dot_graph`](Self::dot_graph), while the other nodes and the edges
+    /// into them are grey and dashed.
+    pub fn dot_graph_reachability(&self) -> impl fmt::Display + '_ {
+        let out_node = self.output_node.expect("Output not yet designated");
+        let kept_set = self.toposort_from(&self.requested_roots(out_node)).into_iter()
+            .map(|key| (key, ()))
+            .collect();
+        DAGComputeDisplay::new(self, Some(kept_set))
+    }
+    /// Writes a DOT graph of the computation graph to `writer`.
+    /// 
+    /// Unlike [`dot_graph`](Self::dot_graph), nodes and edges are written
+    /// as the graph is traversed without building any intermediate
+    /// structures, so very large graphs can be exported in constant memory.
+    /// Nodes and edges are written in storage order rather than grouped by
+    /// traversal.
+    pub fn write_dot(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        writeln!(writer, "strict digraph {{")?;
+        for (node_key, node) in self.node_storage.iter() {
+            write!(writer, "{} [label=\"{}\"", node_key.data().as_ffi(),
+                EscapedLabel(&node.name))?;
+            if self.output_node == Some(node_key) {
+                write!(writer, ", shape=box")?;
+            }
+            writeln!(writer, "];")?;
+        }
+        for (node_key, node) in self.node_storage.iter() {
+            let to_id = node_key.data().as_ffi();
+            for input_key in node.input_nodes.iter() {
+                writeln!(writer, "{}->{};", input_key.data().as_ffi(), to_id)?;
+            }
+        }
+        writeln!(writer, "}}")
     }
 
     /// Determines a valid order for node evaluation.
-    fn computation_order(&mut self) -> impl IntoIterator<Item = ComputeGraphKey> {
+    fn computation_order(&mut self) -> VecDeque<ComputeGraphKey> {
         debug!("Computing node evaluation order");
         let out_node = self.output_node.expect("Output not yet designated");
 
         // Toposort the graph, marking used nodes
-        let mut sort_list = VecDeque::new();
-        let mut temporary_set = HashSet::new();
-        self.toposort_helper(out_node, &mut sort_list, &mut temporary_set);
-        debug_assert!(temporary_set.is_empty());
-
-        // Sweep phase of mark-and-sweep GC
+        let sort_list = self.evaluation_order(out_node);
+        self.sweep_unreachable(&sort_list);
+        sort_list
+    }
+    /// Sweep phase of mark-and-sweep GC, returning the names of removed nodes.
+    fn sweep(&mut self, keep_list: &VecDeque<ComputeGraphKey>) -> Vec<String> {
+        let mut swept_names = Vec::new();
+        let keep_set: SecondaryMap<ComputeGraphKey, ()> = keep_list.iter()
+            .map(|key| (*key, ()))
+            .collect();
         self.node_storage.retain(|k, del_node| {
-            let keep = sort_list.contains(&k);
+            let keep = keep_set.contains_key(k);
             if !keep {
-                trace!("Sweeping node {}", del_node.name);
-                for input_key in &del_node.input_nodes {
-                    *self.node_refcount.get_mut(*input_key).unwrap() -= 1;
+                node_log!(Trace, self, k, del_node.name, "Sweeping node");
+                // Inputs may have been swept already
+                for input_key in del_node.input_nodes.iter()
+                        .chain(del_node.delay_source().iter()) {
+                    if let Some(in_refcnt) = self.node_refcount.get_mut(*input_key) {
+                        *in_refcnt -= 1;
+                    }
                 }
                 self.node_refcount.remove(k);
+                swept_names.push(del_node.name.to_string());
             } else {
-                trace!("Keeping node {}", del_node.name)
+                node_log!(Trace, self, k, del_node.name, "Keeping node");
             }
             keep
         });
-        /*
-         * We traversed the edge in the opposite direction of the dataflow
-         * Reverse now to get the correct directions
-         * WARNING: this is valid for DFS-obtained toposort but not in general
-         */
-        sort_list.make_contiguous().reverse();
+        swept_names
+    }
+    /// Removes the nodes whose values the output does not depend on,
+    /// returning the names of the removed nodes.
+    pub(crate) fn prune_names(&mut self) -> Vec<String> {
+        let out_node = self.output_node.expect("Output not yet designated");
+        let keep_list = self.toposort_from(&self.requested_roots(out_node));
+        self.sweep(&keep_list)
+    }
+    /// Returns `root` along with the sinks if `root` is the output node, as
+    /// the nodes whose evaluation is requested.
+    fn requested_roots(&self, root: ComputeGraphKey) -> Vec<ComputeGraphKey> {
+        let mut roots = vec![root];
+        if self.output_node == Some(root) {
+            roots.extend(self.sink_nodes.iter().copied());
+        }
+        roots
+    }
+    /// Returns `roots` and their ancestors in a valid evaluation order.
+    fn toposort_from(&self, roots: &[ComputeGraphKey]) -> VecDeque<ComputeGraphKey> {
+        let mut sort_list = VecDeque::new();
+        let mut marks = SecondaryMap::new();
+        let mut pending_roots = roots.to_vec();
+        let mut root_index = 0;
+        while root_index < pending_roots.len() {
+            let prev_len = sort_list.len();
+            self.toposort_helper(pending_roots[root_index], &mut sort_list, &mut marks);
+            root_index += 1;
+            // Delay sources must be evaluated too, but are not dependencies
+            for node_key in sort_list.iter().skip(prev_len) {
+                if let Some(source) = self.node_storage.get(*node_key).unwrap().delay_source() {
+                    if !marks.contains_key(source) {
+                        pending_roots.push(source);
+                    }
+                }
+            }
+        }
+        debug_assert!(marks.values().all(|mark| *mark == DfsMark::Finished));
         sort_list
     }
+    /// Returns every node in the graph in a valid evaluation order.
+    pub(crate) fn toposort_all(&self) -> VecDeque<ComputeGraphKey> {
+        let all_keys: Vec<_> = self.node_storage.keys().collect();
+        self.toposort_from(&all_keys)
+    }
+    /// Redirects every use of `old` (as an input or as the output) to `new`.
+    pub(crate) fn replace_uses(&mut self, old: ComputeGraphKey, new: ComputeGraphKey) {
+        let mut moved_refs = 0;
+        for node in self.node_storage.values_mut() {
+            for input in node.input_nodes.iter_mut() {
+                if *input == old {
+                    *input = new;
+                    moved_refs += 1;
+                }
+            }
+        }
+        for node in self.node_storage.values_mut() {
+            if let NodeKind::Delay(_, Some(ref mut source)) = node.kind {
+                if *source == old {
+                    *source = new;
+                    moved_refs += 1;
+                }
+            }
+        }
+        if self.output_node == Some(old) {
+            self.output_node = Some(new);
+            moved_refs += 1;
+        }
+        for sink in self.sink_nodes.iter_mut().filter(|sink| **sink == old) {
+            *sink = new;
+            moved_refs += 1;
+        }
+        *self.node_refcount.get_mut(old).unwrap() -= moved_refs;
+        *self.node_refcount.get_mut(new).unwrap() += moved_refs;
+    }
+    /// Removes a node that no other node uses.
+    pub(crate) fn remove_unused_node(&mut self, key: ComputeGraphKey) -> Node<T> {
+        assert_eq!(*self.node_refcount.get(key).unwrap(), 0, "Node is still in use");
+        let node = self.node_storage.remove(key).unwrap();
+        self.node_refcount.remove(key);
+        for input_key in node.input_nodes.iter().chain(node.delay_source().iter()) {
+            *self.node_refcount.get_mut(*input_key).unwrap() -= 1;
+        }
+        node
+    }
     // Adapted from the DFS-based toposort of https://en.wikipedia.org/wiki/Topological_sorting
-    fn toposort_helper(&self, node: ComputeGraphKey,
+    // Uses an explicit stack so that deep graphs cannot overflow the call stack
+    // Nodes are appended once all of their inputs are, which is a valid order
+    fn toposort_helper(&self, root: ComputeGraphKey,
             final_list: &mut VecDeque<ComputeGraphKey>,
-            temporary_set: &mut HashSet<ComputeGraphKey>) {
-        if final_list.contains(&node) {
+            marks: &mut SecondaryMap<ComputeGraphKey, DfsMark>) {
+        if marks.contains_key(root) {
             return;
         }
-        assert!(!temporary_set.contains(&node), "Computation graph contains cycle");
-        temporary_set.insert(node);
-        for input in self.node_storage.get(node).unwrap().input_nodes.iter() {
-            self.toposort_helper(*input, final_list, temporary_set);
+        // Each entry holds a node and the index of its next input to visit
+        let mut dfs_stack = vec![(root, 0)];
+        marks.insert(root, DfsMark::InProgress);
+        while let Some((node, next_input)) = dfs_stack.last_mut() {
+            let input_nodes = &self.node_storage.get(*node).unwrap().input_nodes;
+            if let Some(input) = input_nodes.get(*next_input).copied() {
+                *next_input += 1;
+                match marks.get(input) {
+                    Some(DfsMark::Finished) => continue,
+                    Some(DfsMark::InProgress) => panic!("Computation graph contains cycle"),
+                    None => {
+                        marks.insert(input, DfsMark::InProgress);
+                        dfs_stack.push((input, 0));
+                    }
+                }
+            } else {
+                let node = *node;
+                dfs_stack.pop();
+                marks.insert(node, DfsMark::Finished);
+                final_list.push_back(node);
+            }
         }
-        temporary_set.remove(&node);
-        final_list.insert(0, node);
     }
 
-    /// Computes and returns the value of the output node.
-    pub fn compute(mut self) -> T {
-        self.output_node.expect("Output not yet designated");
-        info!("Evaluating DAG");
-        let compute_order = self.computation_order();
-        debug!("Computing node values");
-        for node_key in compute_order {
+    /// Evaluates `root` and its ancestors without consuming the graph,
+    /// returning the evaluation order and every computed value.
+    /// 
+    /// Values of multi-output nodes themselves are not retained, only those
+    /// of their output handles.
+    #[cfg_attr(not(feature = "autodiff"), allow(dead_code))]
+    fn evaluate_retained(&self, root: ComputeGraphKey, inputs: SecondaryMap<ComputeGraphKey, T>)
+            -> (VecDeque<ComputeGraphKey>, SecondaryMap<ComputeGraphKey, T>) {
+        let order = self.evaluation_order(root);
+        let values = self.execute_order(&order, None, inputs, &self.run_context(0));
+        (order, values)
+    }
+    /// Counts the uses of each node in `order`, including the use of `root`
+    /// as the output.
+    fn order_refcounts(&self, order: &VecDeque<ComputeGraphKey>, root: ComputeGraphKey)
+            -> SecondaryMap<ComputeGraphKey, u32> {
+        // Retained values keep an extra use so they are never dropped
+        let mut refcounts: SecondaryMap<_, u32> = order.iter()
+            .map(|key| {
+                let node = self.node_storage.get(*key).unwrap();
+                (*key, (node.retention != RetentionPolicy::DropEagerly) as u32)
+            })
+            .collect();
+        for node_key in order.iter() {
+            for input_key in self.node_storage.get(*node_key).unwrap().input_nodes.iter() {
+                *refcounts.entry(*input_key).unwrap().or_insert(0) += 1;
+            }
+        }
+        *refcounts.entry(root).unwrap().or_insert(0) += 1;
+        refcounts
+    }
+    /// Removes from `order` the nodes whose values are already known, along
+    /// with the ancestors only they need, returning the new order and the
+    /// known nodes whose values are used.
+    fn skip_known(&self, order: VecDeque<ComputeGraphKey>, root: ComputeGraphKey,
+            is_known: impl Fn(ComputeGraphKey) -> bool)
+            -> (VecDeque<ComputeGraphKey>, VecDeque<ComputeGraphKey>) {
+        let mut needed: SecondaryMap<ComputeGraphKey, ()> = self.requested_roots(root)
+            .into_iter()
+            .map(|key| (key, ()))
+            .collect();
+        for node_key in order.iter().rev().copied() {
+            if needed.contains_key(node_key) && !is_known(node_key) {
+                for input_key in self.node_storage.get(node_key).unwrap().input_nodes.iter() {
+                    needed.insert(*input_key, ());
+                }
+            }
+        }
+        let order_len = order.len();
+        let (known, order): (VecDeque<_>, VecDeque<_>) = order.into_iter()
+            .filter(|key| needed.contains_key(*key))
+            .partition(|key| is_known(*key));
+        debug!("Reusing {} known values, skipping {} nodes", known.len(),
+            order_len - order.len());
+        (order, known)
+    }
+    /// Evaluates the nodes in `order` without modifying the graph, taking
+    /// placeholder values and values of nodes not in `order` from `inputs`.
+    /// 
+    /// If `refcounts` is given, each value is dropped once its count of
+    /// remaining uses reaches zero, and only values still in use remain in
+    /// the returned map. Otherwise every value is retained.
+    fn execute_order(&self, order: &VecDeque<ComputeGraphKey>,
+            refcounts: Option<SecondaryMap<ComputeGraphKey, u32>>,
+            inputs: SecondaryMap<ComputeGraphKey, T>, context: &NodeContext)
+            -> SecondaryMap<ComputeGraphKey, T> {
+        self.execute_order_observed(order, refcounts, inputs, context, &mut |_, _| {})
+    }
+    /// Like [`execute_order`](Self::execute_order), additionally calling
+    /// `observer` with the key and value of each node right after it is
+    /// evaluated. Multi-output nodes are observed without a value.
+    fn execute_order_observed(&self, order: &VecDeque<ComputeGraphKey>,
+            mut refcounts: Option<SecondaryMap<ComputeGraphKey, u32>>,
+            inputs: SecondaryMap<ComputeGraphKey, T>, context: &NodeContext,
+            observer: &mut dyn FnMut(ComputeGraphKey, Option<&T>))
+            -> SecondaryMap<ComputeGraphKey, T> {
+        let mut values = inputs;
+        let mut multi_values: SecondaryMap<ComputeGraphKey, Vec<Option<T>>> =
+            SecondaryMap::new();
+        let mut spare_inputs: Vec<&T> = Vec::new();
+        let mut branches = self.lazy_branches(order, &values);
+        let mut progress = self.notify_plan(order);
+        let mut position = 0;
+        while position < order.len() {
+            let node_key = order[position];
+            position += 1;
             let node = self.node_storage.get(node_key).unwrap();
-            trace!("Evaluating node {}", node.name);
+            if branches.as_ref().is_some_and(|branches| branches.is_dead(node_key)) {
+                node_log!(Trace, self, node_key, node.name, "Skipping node no longer needed");
+                self.notify_node_skipped(&mut progress);
+                self.release_inputs(node, &mut refcounts, &mut values, &mut multi_values);
+                continue;
+            }
+            node_log!(Trace, self, node_key, node.name, "Evaluating node");
+            let node_started = self.notify_node_start(node_key);
+            let outcome = branches.as_ref().and_then(|branches| branches.outcome(node_key));
+            match node.kind {
+                NodeKind::Placeholder | NodeKind::Source(_) | NodeKind::Delay(_, _) => {
+                    let value = values.get(node_key).unwrap_or_else(|| {
+                        panic!("Node {} was not given a value", node.name)
+                    });
+                    self.publish_value(node_key, value);
+                },
+                NodeKind::MultiOutput(index) => {
+                    let source_vals = multi_values.get_mut(node.input_nodes[0]).unwrap();
+                    let value = source_vals[index].take().unwrap();
+                    self.publish_value(node_key, &value);
+                    values.insert(node_key, value);
+                },
+                NodeKind::Select(is_true, clone_value) => {
+                    // The unchosen branch may have been skipped
+                    let chosen = outcome
+                        .unwrap_or_else(|| is_true(values.get(node.input_nodes[0]).unwrap()));
+                    let branch_key = node.input_nodes[if chosen { 1 } else { 2 }];
+                    let value = clone_value(values.get(branch_key).unwrap());
+                    self.publish_value(node_key, &value);
+                    values.insert(node_key, value);
+                },
+                // Once decided, the remaining inputs may have been skipped
+                NodeKind::ShortCircuit(_, _, from_bool) if outcome.is_some() => {
+                    let value = from_bool(outcome.unwrap());
+                    self.publish_value(node_key, &value);
+                    values.insert(node_key, value);
+                },
+                _ => {
+                    let mut node_inputs = recycle_refs(std::mem::take(&mut spare_inputs));
+                    node_inputs.extend(node.input_nodes.iter()
+                        .map(|key| values.get(*key).unwrap()));
+                    if node.is_multi_func() {
+                        let outputs = node.call_multi(&node_inputs);
+                        spare_inputs = recycle_refs(node_inputs);
+                        multi_values.insert(node_key,
+                            outputs.into_iter().map(Some).collect());
+                    } else {
+                        let mut output = self.evaluate_node(node_key, node, &node_inputs,
+                            context);
+                        spare_inputs = recycle_refs(node_inputs);
+                        self.publish_value(node_key, &output);
+                        self.notify_node_finish(node_key, node_started, &mut progress);
+                        observer(node_key, Some(&output));
+                        self.release_inputs(node, &mut refcounts, &mut values, &mut multi_values);
+                        // Values consumed only by the next node in a chain are
+                        // passed along directly, bypassing the value map
+                        let mut output_key = node_key;
+                        // Chained values could decide lazy nodes unseen
+                        while let Some(link) = order.get(position).filter(|_| branches.is_none())
+                                .and_then(|next_key| {
+                            self.chain_successor(output_key, *next_key, refcounts.as_ref())
+                        }) {
+                            let link_key = order[position];
+                            node_log!(Trace, self, link_key, link.name,
+                                "Evaluating chained node");
+                            position += 1;
+                            let link_started = self.notify_node_start(link_key);
+                            output = self.evaluate_node(link_key, link, &[&output], context);
+                            self.publish_value(link_key, &output);
+                            self.notify_node_finish(link_key, link_started, &mut progress);
+                            observer(link_key, Some(&output));
+                            self.notify_value_dropped(output_key);
+                            output_key = link_key;
+                        }
+                        values.insert(output_key, output);
+                        if let Some(ref mut branches) = branches {
+                            self.resolve_branches(branches, output_key, &values, position);
+                        }
+                        continue;
+                    }
+                }
+            }
+            if let Some(ref mut branches) = branches {
+                self.resolve_branches(branches, node_key, &values, position);
+            }
+            self.notify_node_finish(node_key, node_started, &mut progress);
+            observer(node_key, values.get(node_key));
+            self.release_inputs(node, &mut refcounts, &mut values, &mut multi_values);
+        }
+        self.notify_complete(progress);
+        values
+    }
 
-            let node_input_keyvec = node.input_nodes.clone();
-            let mut nodes_cleanup = Vec::with_capacity(node_input_keyvec.len());
-            let node_input_arcs: Vec<_> = node_input_keyvec.into_iter().map(|key| {
-                let in_refcnt = self.node_refcount.get_mut(key).unwrap();
-                assert!(*in_refcnt > 0);
+    /// Decrements the remaining use counts of a node's inputs, dropping the
+    /// values that are no longer used.
+    fn release_inputs(&self, node: &Node<T>,
+            refcounts: &mut Option<SecondaryMap<ComputeGraphKey, u32>>,
+            values: &mut SecondaryMap<ComputeGraphKey, T>,
+            multi_values: &mut SecondaryMap<ComputeGraphKey, Vec<Option<T>>>) {
+        if let Some(ref mut refcounts) = refcounts {
+            for input_key in node.input_nodes.iter() {
+                let in_refcnt = refcounts.get_mut(*input_key).unwrap();
                 *in_refcnt -= 1;
-                if *in_refcnt == 0 {
-                    nodes_cleanup.push(key);
+                // Inputs in unchosen branches of select nodes have no value
+                if *in_refcnt == 0 && (values.remove(*input_key).is_some()
+                        | multi_values.remove(*input_key).is_some()) {
+                    self.notify_value_dropped(*input_key);
                 }
-                // Toposort guarantees that inputs will be ready when needed
-                self.node_storage.get(key).unwrap().computed_val()
-            }).collect();
-            // The refs in node_inputs are live as long as node_input_arcs is
-            let mut node_inputs = Vec::with_capacity(node_input_arcs.len());
-            for arc in node_input_arcs.iter() {
-                node_inputs.push(arc.deref());
             }
+        }
+    }
+    /// Returns the node at `next_key` if it is the sole consumer of the value
+    /// of `key` and can be evaluated on that value alone.
+    fn chain_successor(&self, key: ComputeGraphKey, next_key: ComputeGraphKey,
+            refcounts: Option<&SecondaryMap<ComputeGraphKey, u32>>) -> Option<&Node<T>> {
+        // Without refcounts every value is retained, so nothing can be skipped
+        if *refcounts?.get(key).unwrap() != 1 {
+            return None;
+        }
+        let next = self.node_storage.get(next_key).unwrap();
+        let single_output = !matches!(next.kind, NodeKind::MultiFunc(_, _)
+            | NodeKind::MultiOutput(_) | NodeKind::Placeholder | NodeKind::Delay(_, _));
+        (single_output && next.input_nodes.as_slice() == [key]).then_some(next)
+    }
+
+    /// Computes and returns the value of the output node.
+    pub fn compute(self) -> T {
+        self.compute_inputs(SecondaryMap::new())
+    }
+    /// Computes and returns the value of the output node, feeding the given
+    /// values to placeholders.
+    /// 
+    /// Values may be of any type convertible into `T`, so callers can pass
+    /// their own domain types once a `From` impl into `T` is declared.
+    pub fn compute_with<'a, In: Into<T>>(self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, In)>) -> T {
+        self.compute_with_adapter(inputs, Into::into)
+    }
+    /// Computes and returns the value of the output node, feeding the given
+    /// values to placeholders after converting them with `adapter`.
+    pub fn compute_with_adapter<'a, In>(self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, In)>,
+            adapter: impl Fn(In) -> T) -> T {
+        let placeholder_values = self.placeholder_values(inputs, adapter);
+        self.compute_inputs(placeholder_values)
+    }
+    /// Collects the values given to placeholders after converting them with
+    /// `adapter`.
+    fn placeholder_values<'a, In>(&self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, In)>,
+            adapter: impl Fn(In) -> T) -> SecondaryMap<ComputeGraphKey, T> {
+        let mut placeholder_values = SecondaryMap::new();
+        for (handle, value) in inputs {
+            assert_eq!(handle.graph_id, self.graph_id,
+                "Received NodeHandle for different graph");
+            let node = self.node_storage.get(handle.node_key).unwrap();
+            assert!(node.is_placeholder(), "Node {} is not a placeholder", node.name);
+            let prev_value = placeholder_values.insert(handle.node_key, adapter(value));
+            assert!(prev_value.is_none(),
+                "Placeholder {} was given multiple values", node.name);
+        }
+        placeholder_values
+    }
+    /// Computes the value of the output node, feeding `inputs` to
+    /// placeholders.
+    fn compute_inputs(mut self, inputs: SecondaryMap<ComputeGraphKey, T>) -> T {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG");
+        let compute_order = self.computation_order();
+        let refcounts = self.order_refcounts(&compute_order, out_key);
+        debug!("Computing node values");
+        // Values are dropped as soon as their last consumer is evaluated
+        let mut values = self.execute_order(&compute_order, Some(refcounts), inputs,
+            &self.run_context(0));
+        values.remove(out_key).unwrap()
+    }
+}
 
-            for old_key in nodes_cleanup {
-                self.node_storage.remove(old_key);
-                self.node_refcount.remove(old_key);
+/// Writes a node name with quotes escaped for use in a DOT label.
+struct EscapedLabel<'a>(&'a str);
+impl<'a> fmt::Display for EscapedLabel<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, part) in self.0.split('"').enumerate() {
+            if index > 0 {
+                fmt.write_str(r#"\""#)?;
             }
-            // Rebind node as &mut to perform calculation
-            let node = self.node_storage.get_mut(node_key).unwrap();
-            node.eval(node_inputs.as_slice());
-        }
-        // Assert checks that only the output node is left
-        assert_eq!(self.node_storage.len(), 1);
-        let output_key = self.output_node.take().unwrap();
-        // Remove instead of get because we want an owned Node
-        let output_node = self.node_storage.remove(output_key).unwrap();
-        let output_val_arc = output_node.computed_val();
-        drop(output_node);
-        /*
-         * We just computed the output value and didn't hand it to anyone else
-         * We dropped the output node, which would have held the only other copy
-         * There is exactly one copy of the Arc, so try_unwrap must succeed
-         */
-        Arc::try_unwrap(output_val_arc).ok().unwrap()
+            fmt.write_str(part)?;
+        }
+        Ok(())
     }
 }
 
+/// DOT attributes of nodes and edges that would be swept.
+const SWEPT_STYLE: &str = "style=dashed, color=grey, fontcolor=grey";
+
 struct DAGComputeDisplay<'a, T> {
-    /*
-     * We only really need edge_list, but hold a PhantomData to slotmap_ref
-     * This prevents changes to the DAG so we only need to compute stuff once
-     */
-    // TODO: make this actual ref?
-    slotmap_ref: PhantomData<&'a SlotMap<ComputeGraphKey, Node<T>>>,
-    names: HashMap<ComputeGraphKey, &'a str>,
-    output_node: Option<ComputeGraphKey>,
-    edge_list: Vec<(ComputeGraphKey, ComputeGraphKey)>
+    // Borrowing the graph keeps the output in sync without copying anything
+    graph: &'a ComputationGraph<T>,
+    // Nodes to draw normally, with the others greyed out if given
+    kept_set: Option<SecondaryMap<ComputeGraphKey, ()>>
 }
 impl<'a, T> DAGComputeDisplay<'a, T> {
-    fn new(map: &'a ComputationGraph<T>) -> DAGComputeDisplay<'a, T> {
-        let true_keyset: HashMap<ComputeGraphKey, &'a str> = map.node_storage
-            .keys()
-            .map(|key| (key, map.node_storage.get(key).unwrap().name.as_str()))
-            .collect();
-        let mut explored_keyset: HashSet<ComputeGraphKey> = HashSet::new();
-        let mut edge_list = Vec::new();
-        // len is more efficient than full equality
-        // We need this to account for ill-formed graphs (don't reject here)
-        while true_keyset.len() > explored_keyset.len() {
-            debug_assert!(explored_keyset.is_subset(
-                &true_keyset.keys().copied().collect()));
-            // Do BFS to make the final dot file more human-readable
-            let mut bfs_queue: VecDeque<ComputeGraphKey> = VecDeque::new();
-            let mut bfs_root: Option<ComputeGraphKey> = None;
-            for key in true_keyset.keys() {
-                if !explored_keyset.contains(key) {
-                    bfs_root = Some(*key);
-                    break;
-                }
-            }
-            let bfs_root = bfs_root.unwrap(); // Rebind and assert
-
-            bfs_queue.push_back(bfs_root);
-            explored_keyset.insert(bfs_root);
-            while !bfs_queue.is_empty() {
-                let current = bfs_queue.pop_front().unwrap();
-                for input in map.node_storage.get(current).unwrap()
-                        .input_nodes.iter() {
-                    edge_list.push((*input, current));
-                    // Insert returns true if new element was added
-                    if explored_keyset.insert(*input) {
-                        bfs_queue.push_back(*input);
-                    }
-                }
-            }
-        }
-        debug_assert_eq!(true_keyset.keys().copied().collect::<HashSet<_>>(),
-                explored_keyset);
+    fn new(graph: &'a ComputationGraph<T>,
+            kept_set: Option<SecondaryMap<ComputeGraphKey, ()>>) -> DAGComputeDisplay<'a, T> {
         DAGComputeDisplay {
-            slotmap_ref: PhantomData::default(),
-            names: true_keyset,
-            output_node: map.output_node,
-            edge_list
+            graph,
+            kept_set
         }
     }
+    fn is_swept(&self, node: ComputeGraphKey) -> bool {
+        self.kept_set.as_ref().is_some_and(|kept_set| !kept_set.contains_key(node))
+    }
 }
 impl<'a, T> fmt::Display for DAGComputeDisplay<'a, T> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node_storage = &self.graph.node_storage;
         writeln!(fmt, "strict digraph {{")?;
-        for (node, name) in self.names.iter() {
+        for (node, node_data) in node_storage.iter() {
             let node_id = node.data().as_ffi();
-            let escaped_name: String = name.chars().map(|c| {
-                match c {
-                    '"' => r#"\""#.to_owned(),
-                    c => c.to_string()
-                }
-            }).collect();
-            write!(fmt, "{} [label=\"{}\"", node_id, escaped_name)?;
-            if let Some(out) = self.output_node {
-                if out == *node {
-                    write!(fmt, ", shape=box")?;
-                }
+            write!(fmt, "{} [label=\"{}\"", node_id, EscapedLabel(&node_data.name))?;
+            if self.graph.output_node == Some(node) {
+                write!(fmt, ", shape=box")?;
+            }
+            if self.is_swept(node) {
+                write!(fmt, ", {}", SWEPT_STYLE)?;
             }
             writeln!(fmt, "];")?;
         }
-        for edge in self.edge_list.iter() {
-            // Use the u64 as_ffi to handle duplicate names
-            let from_id = edge.0.data().as_ffi();
-            let to_id = edge.1.data().as_ffi();
-            writeln!(fmt, "{}->{};", from_id, to_id)?;
+        // Do BFS to make the final dot file more human-readable
+        // Restart from unexplored nodes to account for ill-formed graphs
+        let mut explored_keyset: SecondaryMap<ComputeGraphKey, ()> = SecondaryMap::new();
+        let mut bfs_queue: VecDeque<ComputeGraphKey> = VecDeque::new();
+        for bfs_root in node_storage.keys() {
+            if explored_keyset.insert(bfs_root, ()).is_some() {
+                continue;
+            }
+            bfs_queue.push_back(bfs_root);
+            while let Some(current) = bfs_queue.pop_front() {
+                for input in node_storage.get(current).unwrap().input_nodes.iter() {
+                    // Use the u64 as_ffi to handle duplicate names
+                    write!(fmt, "{}->{}", input.data().as_ffi(), current.data().as_ffi())?;
+                    if self.is_swept(current) {
+                        write!(fmt, " [{}]", SWEPT_STYLE)?;
+                    }
+                    writeln!(fmt, ";")?;
+                    // Insert returns None if new element was added
+                    if explored_keyset.insert(*input, ()).is_none() {
+                        bfs_queue.push_back(*input);
+                    }
+                }
+            }
         }
+        debug_assert_eq!(explored_keyset.len(), node_storage.len());
         writeln!(fmt, "}}")
     }
 }
\ No newline at end of file
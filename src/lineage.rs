@@ -0,0 +1,67 @@
+use crate::Traced;
+
+use std::fmt::Write;
+use std::time::UNIX_EPOCH;
+
+impl<T> Traced<T> {
+    /// Exports the lineage of the output value as a JSON document.
+    /// 
+    /// The document has the form
+    /// `{"run": ..., "output": ..., "nodes": [...]}`, where `run` is the
+    /// given run identifier and `output` is the ID of the output node.
+    /// `nodes` lists every node contributing to the output in evaluation
+    /// order, each as an object with the keys `id`, `name`, `inputs` (the
+    /// IDs of its inputs), `external` (whether it is an external input),
+    /// `computed_at_unix_micros` and `version_tag` (`null` if unset). The
+    /// output can thus be traced back to external inputs by following
+    /// `inputs`.
+    pub fn lineage_json(&self, run_id: &str) -> String {
+        let mut json = String::new();
+        json.push_str("{\"run\":");
+        push_json_string(&mut json, run_id);
+        write!(json, ",\"output\":{},\"nodes\":[", self.output_provenance().node).unwrap();
+        for (index, record) in self.provenance.iter().enumerate() {
+            if index > 0 {
+                json.push(',');
+            }
+            write!(json, "{{\"id\":{},\"name\":", record.node).unwrap();
+            push_json_string(&mut json, &record.node_name);
+            json.push_str(",\"inputs\":[");
+            for (input_index, input) in record.inputs.iter().enumerate() {
+                if input_index > 0 {
+                    json.push(',');
+                }
+                write!(json, "{}", input).unwrap();
+            }
+            // Timestamps before the epoch are clamped to it
+            let micros = record.computed_at.duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_micros());
+            write!(json, "],\"external\":{},\"computed_at_unix_micros\":{},\"version_tag\":",
+                record.external, micros).unwrap();
+            match record.version_tag {
+                Some(ref tag) => push_json_string(&mut json, tag),
+                None => json.push_str("null")
+            }
+            json.push('}');
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+/// Appends `value` to `json` as a quoted and escaped JSON string.
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            '\n' => json.push_str(r"\n"),
+            '\r' => json.push_str(r"\r"),
+            '\t' => json.push_str(r"\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c)
+        }
+    }
+    json.push('"');
+}
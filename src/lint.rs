@@ -0,0 +1,248 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind};
+
+use std::collections::HashMap;
+use std::fmt;
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+use log::debug;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// A likely authoring mistake found by [`ComputationGraph::lint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LintFinding {
+    /// The ID of the node the finding is about.
+    pub node: u64,
+    /// The name of the node.
+    pub name: String,
+    /// What was found.
+    pub kind: LintKind
+}
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Node {} ({}): ", self.name, self.node)?;
+        match self.kind {
+            LintKind::ArityMismatch { expected, actual } =>
+                write!(f, "expects {} inputs but {} are wired", expected, actual),
+            LintKind::UnusedInput { index, input } =>
+                write!(f, "input {} is wired to node {} but declared unused", index, input),
+            LintKind::DuplicateName { first } =>
+                write!(f, "shares its name with node {}", first),
+            LintKind::DisconnectedComponent { size } =>
+                write!(f, "is in a component of {} nodes connected to neither the output \
+                    nor a sink", size),
+            LintKind::SideEffectOutput { ref tag } =>
+                write!(f, "is the output but has the side effect tag {}", tag)
+        }
+    }
+}
+
+/// The kinds of [`LintFinding`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LintKind {
+    /// The number of inputs wired to the node differs from its arity, which
+    /// is fixed for unary, binary, select and
+    /// [`ComputeNode`](crate::ComputeNode) nodes and can be declared for
+    /// others with [`ComputationGraph::declare_arity`].
+    ArityMismatch {
+        /// The arity of the node.
+        expected: usize,
+        /// The number of inputs wired to the node.
+        actual: usize
+    },
+    /// An input is wired at a position declared unused with
+    /// [`ComputationGraph::declare_unused_input`].
+    UnusedInput {
+        /// The position of the input.
+        index: usize,
+        /// The ID of the node wired at that position.
+        input: u64
+    },
+    /// The node has the same name as another node. Each group of nodes
+    /// sharing a name is reported once per node but one, which is not
+    /// necessarily the node inserted first.
+    DuplicateName {
+        /// The ID of the node of the group that is not reported.
+        first: u64
+    },
+    /// The node stands for a group of connected nodes that neither the
+    /// output nor any sink is connected to. Which node of the group is
+    /// reported is unspecified.
+    DisconnectedComponent {
+        /// The number of nodes in the group.
+        size: usize
+    },
+    /// The output node has a tag declared to mark side effects with
+    /// [`ComputationGraph::declare_side_effect_tag`].
+    SideEffectOutput {
+        /// The side effect tag.
+        tag: String
+    }
+}
+
+/// Hints about the intent of the graph's author, checked by
+/// [`ComputationGraph::lint`].
+#[derive(Debug, Default)]
+pub(crate) struct LintHints {
+    pub(crate) arity: SecondaryMap<ComputeGraphKey, usize>,
+    unused_inputs: SecondaryMap<ComputeGraphKey, Vec<usize>>,
+    side_effect_tags: Vec<String>
+}
+
+impl LintHints {
+    /// Returns whether any hint was declared about the given node.
+    pub(crate) fn has_node_hints(&self, key: ComputeGraphKey) -> bool {
+        self.arity.contains_key(key) || self.unused_inputs.contains_key(key)
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Declares the number of inputs a node expects, which
+    /// [`lint`](Self::lint) checks against its wiring.
+    pub fn declare_arity(&mut self, node: &NodeHandle, arity: usize) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.lint_hints.arity.insert(node.node_key, arity);
+    }
+    /// Declares that a node ignores its input at position `index`, so that
+    /// [`lint`](Self::lint) flags any node wired there.
+    pub fn declare_unused_input(&mut self, node: &NodeHandle, index: usize) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let unused = self.lint_hints.unused_inputs.entry(node.node_key).unwrap()
+            .or_default();
+        if !unused.contains(&index) {
+            unused.push(index);
+        }
+    }
+    /// Declares that nodes tagged with `tag` have side effects, so that
+    /// [`lint`](Self::lint) flags an output node with that tag.
+    pub fn declare_side_effect_tag(&mut self, tag: impl Into<String>) {
+        let tag = tag.into();
+        if !self.lint_hints.side_effect_tags.contains(&tag) {
+            self.lint_hints.side_effect_tags.push(tag);
+        }
+    }
+    /// Checks the graph for likely authoring mistakes, returning the
+    /// findings grouped by [`LintKind`].
+    ///
+    /// The order of findings within each kind follows the storage of the
+    /// nodes, which reuses the slots of removed nodes, so it is only stable
+    /// while no nodes are removed.
+    ///
+    /// Nodes whose wiring disagrees with their arity or with inputs declared
+    /// unused, nodes sharing a name, groups of nodes connected to neither
+    /// the output nor a sink, and an output with a side effect tag are
+    /// flagged. Disconnected groups are only checked once an output is
+    /// designated. The graph is not modified, and linting never panics.
+    pub fn lint(&self) -> Vec<LintFinding> {
+        debug!("Linting DAG");
+        let mut findings = Vec::new();
+        let finding = |node_key: ComputeGraphKey, kind: LintKind| LintFinding {
+            node: node_key.data().as_ffi(),
+            name: self.node_storage.get(node_key).unwrap().name.to_string(),
+            kind
+        };
+
+        for (node_key, node) in self.node_storage.iter() {
+            let expected = match node.kind {
+                NodeKind::Unary(_) => Some(1),
+                NodeKind::Binary(_) => Some(2),
+                NodeKind::Select(_, _) => Some(3),
+                _ => self.lint_hints.arity.get(node_key).copied()
+            };
+            let actual = node.input_nodes.len();
+            if let Some(expected) = expected.filter(|expected| *expected != actual) {
+                findings.push(finding(node_key, LintKind::ArityMismatch { expected, actual }));
+            }
+        }
+        for (node_key, node) in self.node_storage.iter() {
+            let Some(unused) = self.lint_hints.unused_inputs.get(node_key) else {
+                continue;
+            };
+            let mut unused = unused.clone();
+            unused.sort_unstable();
+            for index in unused {
+                if let Some(input_key) = node.input_nodes.get(index) {
+                    findings.push(finding(node_key, LintKind::UnusedInput {
+                        index,
+                        input: input_key.data().as_ffi()
+                    }));
+                }
+            }
+        }
+        let mut first_named: HashMap<&str, ComputeGraphKey> = HashMap::new();
+        for (node_key, node) in self.node_storage.iter() {
+            let first = *first_named.entry(&node.name).or_insert(node_key);
+            if first != node_key {
+                findings.push(finding(node_key, LintKind::DuplicateName {
+                    first: first.data().as_ffi()
+                }));
+            }
+        }
+        if let Some(out_key) = self.output_node {
+            for (first, size) in self.disconnected_components(out_key) {
+                findings.push(finding(first, LintKind::DisconnectedComponent { size }));
+            }
+            let out_tags = &self.node_storage.get(out_key).unwrap().tags;
+            for tag in self.lint_hints.side_effect_tags.iter() {
+                if out_tags.contains(tag) {
+                    findings.push(finding(out_key, LintKind::SideEffectOutput {
+                        tag: tag.clone()
+                    }));
+                }
+            }
+        }
+        findings
+    }
+    /// Returns a representative node and the size of each group of
+    /// connected nodes, ignoring edge directions, that contains neither the
+    /// output nor a sink.
+    fn disconnected_components(&self, out_key: ComputeGraphKey)
+            -> Vec<(ComputeGraphKey, usize)> {
+        // Union-find over the nodes, with each root pointing to itself
+        let mut parents: SecondaryMap<ComputeGraphKey, ComputeGraphKey> = self.node_storage.keys()
+            .map(|key| (key, key))
+            .collect();
+        fn find(parents: &mut SecondaryMap<ComputeGraphKey, ComputeGraphKey>,
+                mut key: ComputeGraphKey) -> ComputeGraphKey {
+            while parents[key] != key {
+                let grandparent = parents[parents[key]];
+                parents[key] = grandparent;
+                key = grandparent;
+            }
+            key
+        }
+        for (node_key, node) in self.node_storage.iter() {
+            for input_key in node.input_nodes.iter().chain(node.delay_source().iter()) {
+                let (root, input_root) = (find(&mut parents, node_key),
+                    find(&mut parents, *input_key));
+                parents[root] = input_root;
+            }
+        }
+        let mut anchored: SecondaryMap<ComputeGraphKey, ()> = SecondaryMap::new();
+        for key in self.requested_roots(out_key) {
+            anchored.insert(find(&mut parents, key), ());
+        }
+        // First node and size of each group, in storage order of first nodes
+        let mut components: Vec<(ComputeGraphKey, usize)> = Vec::new();
+        let mut component_index: SecondaryMap<ComputeGraphKey, usize> = SecondaryMap::new();
+        for node_key in self.node_storage.keys() {
+            let root = find(&mut parents, node_key);
+            if anchored.contains_key(root) {
+                continue;
+            }
+            match component_index.get(root) {
+                Some(index) => components[*index].1 += 1,
+                None => {
+                    component_index.insert(root, components.len());
+                    components.push((node_key, 1));
+                }
+            }
+        }
+        components
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{ComputationGraph, ComputeGraphKey};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use slotmap::Key as KeyTrait;
+use log::{log, log_enabled, Level};
+
+/// Identifies a node in the callbacks of an [`ExecutionListener`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeInfo<'a> {
+    /// The ID of the node, as returned by [`ComputationGraph::node_id`].
+    pub id: u64,
+    /// The name of the node.
+    pub name: &'a str
+}
+
+/// The progress of an evaluation, passed to
+/// [`ExecutionListener::on_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of nodes evaluated so far.
+    pub completed: usize,
+    /// The total number of nodes to evaluate.
+    pub total: usize,
+    /// The time elapsed since evaluation started.
+    pub elapsed: Duration,
+    /// The estimated time until evaluation finishes.
+    /// 
+    /// If node timings were given to
+    /// [`apply_profile`](ComputationGraph::apply_profile), this is based on
+    /// the measured cost of the remaining nodes, scaled by how the nodes
+    /// evaluated so far compare to their measurements. Otherwise it assumes
+    /// the remaining nodes take the average time of those evaluated so far.
+    pub estimated_remaining: Duration
+}
+
+// Progress of an evaluation observed by listeners
+pub(crate) struct RunProgress {
+    started: Instant,
+    completed: usize,
+    total: usize,
+    // Measured costs of all nodes to evaluate and of those evaluated so far
+    expected_total: Duration,
+    expected_completed: Duration
+}
+
+/// Callbacks invoked while a graph is evaluated, registered with
+/// [`ComputationGraph::add_listener`].
+///
+/// Every method does nothing by default, so implementations only need to
+/// override the events they are interested in. Repeated evaluations, such as
+/// those of [`compute_iterations`](ComputationGraph::compute_iterations),
+/// report each evaluation from `on_plan` to `on_complete`.
+pub trait ExecutionListener: Send + Sync {
+    /// Called before evaluation for each node that will be removed because
+    /// the output does not depend on it, if the sweep policy is
+    /// [`SweepPolicy::Warn`](crate::SweepPolicy::Warn).
+    fn on_unreachable(&self, _node: NodeInfo<'_>) {}
+    /// Called before evaluation starts with the nodes to evaluate, in order.
+    fn on_plan(&self, _order: &[NodeInfo<'_>]) {}
+    /// Called right before a node is evaluated.
+    fn on_node_start(&self, _node: NodeInfo<'_>) {}
+    /// Called right after a node is evaluated, with the time it took.
+    fn on_node_finish(&self, _node: NodeInfo<'_>, _duration: Duration) {}
+    /// Called when the value of a node is dropped because no remaining node
+    /// uses it.
+    fn on_value_dropped(&self, _node: NodeInfo<'_>) {}
+    /// Called after each node is evaluated with the progress of the
+    /// evaluation, including an estimate of the remaining time.
+    fn on_progress(&self, _progress: Progress) {}
+    /// Called once every node has been evaluated, with the total time taken.
+    fn on_complete(&self, _duration: Duration) {}
+}
+
+// Wrapper allowing the graph to keep deriving Debug
+#[derive(Default)]
+pub(crate) struct ListenerList(Vec<Arc<dyn ExecutionListener>>);
+impl fmt::Debug for ListenerList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ListenerList({} listeners)", self.0.len())
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Registers a listener to be notified of the progress of every later
+    /// evaluation of the graph.
+    pub fn add_listener(&mut self, listener: Arc<dyn ExecutionListener>) {
+        self.listeners.0.push(listener);
+    }
+    /// Removes every registered listener.
+    pub fn clear_listeners(&mut self) {
+        self.listeners.0.clear();
+    }
+    fn node_info(&self, key: ComputeGraphKey) -> NodeInfo<'_> {
+        NodeInfo {
+            id: key.data().as_ffi(),
+            name: &self.node_storage.get(key).unwrap().name
+        }
+    }
+    /// Notifies listeners of the planned order, returning the state used to
+    /// report progress if there are any listeners.
+    pub(crate) fn notify_plan(&self, order: &VecDeque<ComputeGraphKey>) -> Option<RunProgress> {
+        if self.listeners.0.is_empty() {
+            return None;
+        }
+        let infos: Vec<_> = order.iter().map(|key| self.node_info(*key)).collect();
+        for listener in self.listeners.0.iter() {
+            listener.on_plan(&infos);
+        }
+        let expected_total = order.iter()
+            .filter_map(|key| self.node_costs.get(*key).copied())
+            .fold(Duration::ZERO, Duration::saturating_add);
+        Some(RunProgress {
+            started: self.now(),
+            completed: 0,
+            total: order.len(),
+            expected_total,
+            expected_completed: Duration::ZERO
+        })
+    }
+    /// Notifies listeners that a node is starting, returning its start time
+    /// if it is needed to report the node's duration.
+    pub(crate) fn notify_node_start(&self, key: ComputeGraphKey) -> Option<Instant> {
+        if self.listeners.0.is_empty() {
+            let (target, level) = self.node_log_settings(key, module_path!(), Level::Trace);
+            if !log_enabled!(target: target, level) {
+                return None;
+            }
+        }
+        for listener in self.listeners.0.iter() {
+            listener.on_node_start(self.node_info(key));
+        }
+        Some(self.now())
+    }
+    /// Notifies listeners and the log that a node finished.
+    pub(crate) fn notify_node_finish(&self, key: ComputeGraphKey, started: Option<Instant>,
+            progress: &mut Option<RunProgress>) {
+        if let Some(started) = started {
+            let duration = self.now().saturating_duration_since(started);
+            let node = self.node_info(key);
+            let (target, level) = self.node_log_settings(key, module_path!(), Level::Trace);
+            log!(target: target, level,
+                node = node.name, node_id = node.id, graph_id = self.graph_id,
+                duration_us = duration.as_micros() as u64;
+                "Evaluated node {} in {:?}", node.name, duration);
+            for listener in self.listeners.0.iter() {
+                listener.on_node_finish(node, duration);
+            }
+        }
+        if let Some(ref mut run) = progress {
+            run.completed += 1;
+            if let Some(cost) = self.node_costs.get(key) {
+                run.expected_completed = run.expected_completed.saturating_add(*cost);
+            }
+            let progress = run.progress(self.now());
+            for listener in self.listeners.0.iter() {
+                listener.on_progress(progress);
+            }
+        }
+    }
+    /// Removes a node that will not be evaluated from the progress of the
+    /// run.
+    pub(crate) fn notify_node_skipped(&self, progress: &mut Option<RunProgress>) {
+        if let Some(ref mut run) = progress {
+            run.total -= 1;
+        }
+    }
+    pub(crate) fn notify_value_dropped(&self, key: ComputeGraphKey) {
+        for listener in self.listeners.0.iter() {
+            listener.on_value_dropped(self.node_info(key));
+        }
+    }
+    pub(crate) fn notify_unreachable(&self, key: ComputeGraphKey) {
+        for listener in self.listeners.0.iter() {
+            listener.on_unreachable(self.node_info(key));
+        }
+    }
+    pub(crate) fn notify_complete(&self, progress: Option<RunProgress>) {
+        if let Some(run) = progress {
+            let duration = self.now().saturating_duration_since(run.started);
+            for listener in self.listeners.0.iter() {
+                listener.on_complete(duration);
+            }
+        }
+    }
+}
+
+impl RunProgress {
+    fn progress(&self, now: Instant) -> Progress {
+        let elapsed = now.saturating_duration_since(self.started);
+        let estimated_remaining = if self.expected_completed > Duration::ZERO {
+            let remaining = self.expected_total.saturating_sub(self.expected_completed);
+            remaining.mul_f64(elapsed.as_secs_f64() / self.expected_completed.as_secs_f64())
+        } else {
+            let remaining = (self.total - self.completed) as f64;
+            elapsed.mul_f64(remaining / self.completed as f64)
+        };
+        Progress {
+            completed: self.completed,
+            total: self.total,
+            elapsed,
+            estimated_remaining
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle};
+
+use log::Level;
+
+// Overrides of how messages about a node are logged
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NodeLogging {
+    pub(crate) target: Option<String>,
+    pub(crate) level: Option<Level>
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets the target under which messages about a node are logged, such
+    /// as `"pipeline::resample"`, so that they can be filtered separately
+    /// with the usual `log` filtering.
+    /// 
+    /// By default, messages are logged under the target of the module
+    /// emitting them.
+    pub fn set_log_target(&mut self, node: &NodeHandle, target: impl Into<String>) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.node_logging.entry(node.node_key).unwrap().or_default().target =
+            Some(target.into());
+    }
+    /// Sets the level at which messages about a node are logged, such as
+    /// [`Level::Info`] to follow a critical stage without enabling trace
+    /// logging for every node.
+    /// 
+    /// By default, messages about evaluating individual nodes are logged at
+    /// [`Level::Trace`].
+    pub fn set_log_level(&mut self, node: &NodeHandle, level: Level) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.node_logging.entry(node.node_key).unwrap().or_default().level = Some(level);
+    }
+    /// Returns the target and level for a message about a node that would
+    /// otherwise be logged under `default_target` at `default_level`.
+    pub(crate) fn node_log_settings<'a>(&'a self, key: ComputeGraphKey,
+            default_target: &'a str, default_level: Level) -> (&'a str, Level) {
+        match self.node_logging.get(key) {
+            Some(settings) => (
+                settings.target.as_deref().unwrap_or(default_target),
+                settings.level.unwrap_or(default_level)
+            ),
+            None => (default_target, default_level)
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{ComputationGraph, ComputeGraphKey, Node, NodeKind};
+
+use std::fmt;
+use std::mem::size_of;
+use std::time::Duration;
+
+use slotmap::Key as KeyTrait;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// The projected memory footprint of computing a graph, produced by
+/// [`ComputationGraph::estimated_memory`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryEstimate {
+    /// The approximate number of bytes occupied by the graph structure
+    /// itself, excluding state captured by node functions.
+    pub graph_bytes: usize,
+    /// The projected peak number of bytes held by node values during a run.
+    pub peak_value_bytes: usize,
+    /// The number of values held at the projected peak.
+    pub peak_live_values: usize,
+    /// The ID of the node whose evaluation reaches the projected peak, if
+    /// any node is evaluated.
+    pub peak_node: Option<u64>
+}
+impl MemoryEstimate {
+    /// Returns the projected peak footprint of the graph and its values.
+    pub fn total_bytes(&self) -> usize {
+        self.graph_bytes + self.peak_value_bytes
+    }
+}
+impl fmt::Display for MemoryEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Graph structure: {} bytes", self.graph_bytes)?;
+        write!(f, "Peak values: {} bytes in {} values", self.peak_value_bytes,
+            self.peak_live_values)
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Estimates the memory needed to compute the graph without computing
+    /// it.
+    ///
+    /// The peak storage of intermediate values is projected by replaying the
+    /// planned evaluation order, with values dropped once their last
+    /// consumer is evaluated. The size of each value is taken from the
+    /// report of a [`compute_profiled_sized`](Self::compute_profiled_sized)
+    /// run passed to [`apply_profile`](Self::apply_profile), which measures
+    /// values through [`SizeHint`](crate::SizeHint), and is `size_of::<T>()`
+    /// for nodes without a measured size. Both branches of select nodes are
+    /// assumed to be evaluated, so the projection is an upper bound for
+    /// graphs using them.
+    pub fn estimated_memory(&self) -> MemoryEstimate {
+        let mut estimate = MemoryEstimate {
+            graph_bytes: self.structure_bytes(),
+            ..MemoryEstimate::default()
+        };
+        let Some(out_key) = self.output_node else {
+            return estimate;
+        };
+        let order = self.evaluation_order(out_key);
+        let mut refcounts = self.order_refcounts(&order, out_key);
+        let value_bytes = |key: ComputeGraphKey| match self.node_storage.get(key).unwrap().kind {
+            // Outputs are counted once taken by their output nodes
+            NodeKind::MultiFunc(_, _) => 0,
+            _ => self.node_sizes.get(key).copied().unwrap_or(size_of::<T>())
+        };
+        let (mut live_bytes, mut live_values) = (0, 0);
+        for node_key in order.iter().copied() {
+            live_bytes += value_bytes(node_key);
+            live_values += 1;
+            // Inputs are still held while the node is evaluated
+            if live_bytes > estimate.peak_value_bytes || estimate.peak_node.is_none() {
+                estimate.peak_value_bytes = live_bytes;
+                estimate.peak_live_values = live_values;
+                estimate.peak_node = Some(node_key.data().as_ffi());
+            }
+            for input_key in self.node_storage.get(node_key).unwrap().input_nodes.iter() {
+                let in_refcnt = refcounts.get_mut(*input_key).unwrap();
+                *in_refcnt -= 1;
+                if *in_refcnt == 0 {
+                    live_bytes -= value_bytes(*input_key);
+                    live_values -= 1;
+                }
+            }
+        }
+        estimate
+    }
+    /// Returns the approximate number of bytes occupied by the graph
+    /// structure.
+    fn structure_bytes(&self) -> usize {
+        let key_entry = size_of::<(ComputeGraphKey, u32)>();
+        let node_bytes: usize = self.node_storage.values().map(|node| {
+            let spilled_inputs = if node.input_nodes.spilled() {
+                node.input_nodes.capacity() * size_of::<ComputeGraphKey>()
+            } else {
+                0
+            };
+            node.name.len() + spilled_inputs
+                + node.tags.iter().map(|tag| size_of::<String>() + tag.capacity())
+                    .sum::<usize>()
+                + node.device.as_ref().map_or(0, String::capacity)
+                + node.version_tag.as_ref().map_or(0, String::capacity)
+        }).sum();
+        size_of::<Self>()
+            + self.node_storage.capacity() * size_of::<Node<T>>()
+            + node_bytes
+            + self.node_refcount.capacity() * key_entry
+            + self.sink_nodes.capacity() * size_of::<ComputeGraphKey>()
+            + self.node_costs.capacity() * size_of::<(ComputeGraphKey, Duration)>()
+            + self.node_sizes.capacity() * size_of::<(ComputeGraphKey, usize)>()
+    }
+}
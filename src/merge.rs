@@ -0,0 +1,67 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind};
+
+use slotmap::SecondaryMap;
+use log::debug;
+
+/// Translates handles of a graph merged with [`ComputationGraph::merge`]
+/// into handles of the graph it was merged into.
+#[derive(Debug)]
+pub struct MergedHandles {
+    source_graph_id: usize,
+    target_graph_id: usize,
+    keys: SecondaryMap<ComputeGraphKey, ComputeGraphKey>
+}
+impl MergedHandles {
+    /// Returns the handle of the merged node corresponding to a handle of
+    /// the graph that was merged.
+    pub fn translate(&self, handle: &NodeHandle) -> NodeHandle {
+        assert_eq!(handle.graph_id, self.source_graph_id,
+            "Received NodeHandle for different graph");
+        NodeHandle {
+            node_key: *self.keys.get(handle.node_key).expect("Node was not merged"),
+            graph_id: self.target_graph_id
+        }
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Moves every node of `other` into this graph, returning a map that
+    /// translates handles of `other` into handles of this graph.
+    /// 
+    /// This allows large graphs to be built in parallel: each producer
+    /// thread builds its own graph, and the results are merged and wired
+    /// together afterwards. The output and sink designations and other
+    /// settings of `other` are discarded.
+    pub fn merge(&mut self, mut other: ComputationGraph<T>) -> MergedHandles {
+        debug!("Merging {} nodes into DAG", other.node_storage.len());
+        if let Some(out_key) = other.output_node.take() {
+            *other.node_refcount.get_mut(out_key).unwrap() -= 1;
+        }
+        for sink_key in std::mem::take(&mut other.sink_nodes) {
+            *other.node_refcount.get_mut(sink_key).unwrap() -= 1;
+        }
+        let mut keys = SecondaryMap::with_capacity(other.node_storage.len());
+        let mut merged_keys = Vec::with_capacity(other.node_storage.len());
+        for (old_key, node) in other.node_storage.drain() {
+            let new_key = self.node_storage.insert(node);
+            self.node_refcount.insert(new_key, *other.node_refcount.get(old_key).unwrap());
+            keys.insert(old_key, new_key);
+            merged_keys.push(new_key);
+        }
+        // Inputs can only be rewritten once every node has a new key
+        for new_key in merged_keys {
+            let node = self.node_storage.get_mut(new_key).unwrap();
+            for input_key in node.input_nodes.iter_mut() {
+                *input_key = *keys.get(*input_key).unwrap();
+            }
+            if let NodeKind::Delay(_, Some(ref mut source)) = node.kind {
+                *source = *keys.get(*source).unwrap();
+            }
+        }
+        MergedHandles {
+            source_graph_id: other.graph_id,
+            target_graph_id: self.graph_id,
+            keys
+        }
+    }
+}
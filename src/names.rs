@@ -0,0 +1,48 @@
+use crate::{BoxedEvalFn, ComputationGraph, NodeHandle};
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// The placeholder replaced by a counter in name templates.
+const COUNTER_PLACEHOLDER: &str = "{n}";
+
+// Names generated so far and the next counter value of each template
+#[derive(Debug, Clone, Default)]
+pub(crate) struct NameGenerator {
+    names: HashSet<Arc<str>>,
+    counters: HashMap<String, usize>
+}
+
+impl<T> ComputationGraph<T> {
+    /// Returns a name generated from `template` that differs from every
+    /// name previously generated for this graph.
+    /// 
+    /// Each occurrence of `{n}` in the template is replaced by a counter
+    /// kept per template, so `"stage_{n}"` produces `"stage_0"`,
+    /// `"stage_1"` and so on. Templates without `{n}` have `_{n}`
+    /// appended. Names passed explicitly to other methods are not checked,
+    /// so they should not follow the pattern of a template in use.
+    pub fn generate_name(&mut self, template: &str) -> Arc<str> {
+        let generator = &mut self.name_generator;
+        let counter = generator.counters.entry(template.to_owned()).or_insert(0);
+        loop {
+            let name: Arc<str> = if template.contains(COUNTER_PLACEHOLDER) {
+                template.replace(COUNTER_PLACEHOLDER, &counter.to_string()).into()
+            } else {
+                format!("{}_{}", template, counter).into()
+            };
+            *counter += 1;
+            // Another template may have produced the same name
+            if generator.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+    /// Inserts a new node named `node_{n}` by
+    /// [`generate_name`](Self::generate_name), returning an opaque node
+    /// handle.
+    pub fn insert_node_anon(&mut self, func: BoxedEvalFn<T>) -> NodeHandle {
+        let name = self.generate_name("node_{n}");
+        self.insert_node(name, func)
+    }
+}
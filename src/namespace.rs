@@ -0,0 +1,141 @@
+use crate::{ComputationGraph, ComputeGraphKey, EscapedLabel, NodeHandle, TagFilter};
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use slotmap::Key as KeyTrait;
+
+/// Separates the namespaces in node names, as in `"preprocess/resample"`.
+const NAMESPACE_SEPARATOR: char = '/';
+
+impl<T> ComputationGraph<T> {
+    /// Returns handles to every node in the given namespace or one nested
+    /// in it, in storage order.
+    /// 
+    /// Namespaces are the slash-separated prefixes of node names, so the
+    /// node `"preprocess/resample/filter"` is in both `"preprocess"` and
+    /// `"preprocess/resample"`.
+    pub fn nodes_in_namespace(&self, namespace: &str) -> Vec<NodeHandle> {
+        self.node_storage.iter()
+            .filter(|(_, node)| in_namespace(&node.name, namespace))
+            .map(|(node_key, _)| NodeHandle {
+                node_key,
+                graph_id: self.graph_id
+            })
+            .collect()
+    }
+    /// Enables or disables the nodes in the given namespace and those nested
+    /// in it.
+    /// 
+    /// Disabled sinks are not evaluated, which allows optional stages such
+    /// as diagnostics to be switched off. Computing a value that depends on
+    /// a disabled node panics. Every namespace is enabled by default.
+    pub fn set_namespace_enabled(&mut self, namespace: &str, enabled: bool) {
+        let namespace = namespace.trim_end_matches(NAMESPACE_SEPARATOR);
+        self.disabled_namespaces.retain(|disabled| disabled != namespace);
+        if !enabled {
+            self.disabled_namespaces.push(namespace.to_owned());
+        }
+    }
+    /// Returns whether the nodes in the given namespace are enabled, which
+    /// they are unless it or an enclosing namespace was disabled.
+    pub fn is_namespace_enabled(&self, namespace: &str) -> bool {
+        !self.disabled_namespaces.iter().any(|disabled| {
+            namespace.trim_end_matches(NAMESPACE_SEPARATOR) == disabled
+                || in_namespace(namespace, disabled)
+        })
+    }
+    /// Returns whether a node is in a disabled namespace or rejected by the
+    /// tag filter of a run.
+    pub(crate) fn is_disabled(&self, key: ComputeGraphKey, filter: Option<&TagFilter>)
+            -> bool {
+        let node = self.node_storage.get(key).unwrap();
+        self.disabled_namespaces.iter().any(|disabled| in_namespace(&node.name, disabled))
+            || filter.is_some_and(|filter| filter.rejects(&node.tags))
+    }
+    /// Returns whether any namespace is disabled.
+    pub(crate) fn has_disabled_nodes(&self) -> bool {
+        !self.disabled_namespaces.is_empty()
+    }
+    /// Panics if any node of an evaluation order is disabled.
+    pub(crate) fn assert_enabled(&self, order: &VecDeque<ComputeGraphKey>,
+            filter: Option<&TagFilter>) {
+        if !self.has_disabled_nodes() && filter.is_none() {
+            return;
+        }
+        if let Some(key) = order.iter().copied().find(|key| self.is_disabled(*key, filter)) {
+            let node = self.node_storage.get(key).unwrap();
+            if filter.is_some_and(|filter| filter.rejects(&node.tags)) {
+                panic!("Node {} is rejected by the tag filter", node.name);
+            }
+            panic!("Node {} is in a disabled namespace", node.name);
+        }
+    }
+    /// Emits a DOT graph of the computation graph like
+    /// [`dot_graph`](Self::dot_graph), with the nodes of each namespace
+    /// grouped in a cluster nested in those of the enclosing namespaces.
+    pub fn dot_graph_clustered(&self) -> impl fmt::Display + '_ {
+        ClusteredDisplay { graph: self }
+    }
+}
+
+/// Returns whether a node name is in a namespace or one nested in it.
+fn in_namespace(name: &str, namespace: &str) -> bool {
+    let namespace = namespace.trim_end_matches(NAMESPACE_SEPARATOR);
+    name.strip_prefix(namespace)
+        .is_some_and(|rest| namespace.is_empty() || rest.starts_with(NAMESPACE_SEPARATOR))
+}
+
+/// Returns the namespaces enclosing a node, outermost first.
+fn namespace_path(name: &str) -> Vec<&str> {
+    let mut path: Vec<_> = name.split(NAMESPACE_SEPARATOR).collect();
+    path.pop();
+    path
+}
+
+struct ClusteredDisplay<'a, T> {
+    graph: &'a ComputationGraph<T>
+}
+impl<'a, T> fmt::Display for ClusteredDisplay<'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let node_storage = &self.graph.node_storage;
+        // Sorting by namespace puts nested namespaces right after their parents
+        let mut nodes: Vec<_> = node_storage.iter()
+            .map(|(node_key, node)| (namespace_path(&node.name), node_key, node))
+            .collect();
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        writeln!(fmt, "strict digraph {{")?;
+        let mut open_clusters: Vec<&str> = Vec::new();
+        let mut cluster_count = 0;
+        for (path, node_key, node) in nodes.iter() {
+            let common = open_clusters.iter().zip(path.iter())
+                .take_while(|(open, wanted)| open == wanted)
+                .count();
+            for _ in common..open_clusters.len() {
+                writeln!(fmt, "}}")?;
+            }
+            open_clusters.truncate(common);
+            for segment in path[common..].iter() {
+                writeln!(fmt, "subgraph cluster_{} {{", cluster_count)?;
+                writeln!(fmt, "label=\"{}\";", EscapedLabel(segment))?;
+                cluster_count += 1;
+                open_clusters.push(segment);
+            }
+            write!(fmt, "{} [label=\"{}\"", node_key.data().as_ffi(), EscapedLabel(&node.name))?;
+            if self.graph.output_node == Some(*node_key) {
+                write!(fmt, ", shape=box")?;
+            }
+            writeln!(fmt, "];")?;
+        }
+        for _ in open_clusters.iter() {
+            writeln!(fmt, "}}")?;
+        }
+        for (node_key, node) in node_storage.iter() {
+            for input_key in node.input_nodes.iter() {
+                writeln!(fmt, "{}->{};", input_key.data().as_ffi(), node_key.data().as_ffi())?;
+            }
+        }
+        writeln!(fmt, "}}")
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{ComputationGraph, Node, NodeHandle, NodeKind};
+
+use std::fmt;
+use std::sync::Arc;
+
+use log::trace;
+
+/// An external executor for nodes placed on a device with
+/// [`ComputationGraph::set_device`], such as a GPU.
+///
+/// The graph still determines when each node runs and which values it
+/// receives; the executor only replaces the node's own function.
+pub trait OffloadExecutor<T>: Send + Sync {
+    /// Evaluates the named node on `device` with the given inputs.
+    fn execute(&self, device: &str, node_name: &str, inputs: &[&T]) -> T;
+}
+
+// Wrapper allowing the graph to keep deriving Debug
+pub(crate) struct OffloadHook<T>(Arc<dyn OffloadExecutor<T>>);
+impl<T> fmt::Debug for OffloadHook<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OffloadHook(...)")
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Places a node on the named device, so that it is evaluated by the
+    /// executor set with [`set_offload_executor`](Self::set_offload_executor).
+    /// 
+    /// Nodes placed on a device are evaluated on the host as usual while no
+    /// executor is set. Only nodes with a single output can be placed.
+    pub fn set_device(&mut self, node: &NodeHandle, device: String) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let node = self.node_storage.get_mut(node.node_key).unwrap();
+        assert!(matches!(node.kind, NodeKind::Func(_) | NodeKind::Unary(_) | NodeKind::Binary(_)
+                | NodeKind::ContextFunc(_)),
+            "Node {} cannot be placed on a device", node.name);
+        node.device = Some(device);
+    }
+    /// Returns the device a node was placed on, if any.
+    pub fn device(&self, node: &NodeHandle) -> Option<&str> {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.node_storage.get(node.node_key).unwrap().device.as_deref()
+    }
+    /// Sets the executor evaluating nodes placed on a device, replacing any
+    /// previously set executor.
+    pub fn set_offload_executor(&mut self, executor: impl OffloadExecutor<T> + 'static) {
+        self.offload_executor = Some(OffloadHook(Arc::new(executor)));
+    }
+    /// Evaluates a node with the offload executor if it is placed on a
+    /// device and an executor is set.
+    pub(crate) fn offload(&self, node: &Node<T>, args: &[&T]) -> Option<T> {
+        let device = node.device.as_deref()?;
+        let OffloadHook(ref executor) = self.offload_executor.as_ref()?;
+        trace!(node = &*node.name, device = device; "Offloading node {} to {}", node.name, device);
+        Some(executor.execute(device, &node.name, args))
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{ComputationGraph, ComputeGraphKey, Node, NodeContext, NodeHandle, NodeKind,
+    RunOptions};
+
+use std::fmt;
+use std::sync::Arc;
+
+use slotmap::SecondaryMap;
+use log::{info, trace};
+
+type StubFn<T> = Arc<dyn Fn(&[&T]) -> T + Send + Sync>;
+
+// Wrapper allowing the graph to keep deriving Debug
+pub(crate) struct NodeOverride<T>(StubFn<T>);
+impl<T> fmt::Debug for NodeOverride<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NodeOverride(...)")
+    }
+}
+
+/// Restores the override a node had before
+/// [`with_override`](ComputationGraph::with_override) when dropped.
+struct OverrideGuard<'a, T> {
+    graph: &'a mut ComputationGraph<T>,
+    key: ComputeGraphKey,
+    previous: Option<NodeOverride<T>>
+}
+impl<T> Drop for OverrideGuard<'_, T> {
+    fn drop(&mut self) {
+        match self.previous.take() {
+            Some(previous) => self.graph.overrides.insert(self.key, previous),
+            None => self.graph.overrides.remove(self.key)
+        };
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Replaces the function of a node with `stub` until the override is
+    /// removed, so tests can exercise the real wiring of a graph without
+    /// running expensive or effectful nodes.
+    /// 
+    /// The stub receives the node's inputs like the original function, and
+    /// takes precedence over an [`OffloadExecutor`](crate::OffloadExecutor).
+    /// Only nodes with a single output can be overridden, and overriding a
+    /// node again replaces its previous stub.
+    pub fn override_node(&mut self, node: &NodeHandle,
+            stub: impl Fn(&[&T]) -> T + Send + Sync + 'static) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.insert_override(node.node_key, Arc::new(stub));
+    }
+    /// Overrides every node with the given name like
+    /// [`override_node`](Self::override_node), returning the number of
+    /// overridden nodes.
+    pub fn override_nodes_named(&mut self, name: &str,
+            stub: impl Fn(&[&T]) -> T + Send + Sync + 'static) -> usize {
+        let stub: StubFn<T> = Arc::new(stub);
+        let keys: Vec<_> = self.node_storage.iter()
+            .filter(|(_, node)| &*node.name == name)
+            .map(|(key, _)| key)
+            .collect();
+        for key in keys.iter().copied() {
+            self.insert_override(key, stub.clone());
+        }
+        keys.len()
+    }
+    /// Removes the override of a node, returning whether it was overridden.
+    pub fn remove_override(&mut self, node: &NodeHandle) -> bool {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.overrides.remove(node.node_key).is_some()
+    }
+    /// Removes every node override.
+    pub fn clear_overrides(&mut self) {
+        self.overrides.clear();
+    }
+    /// Calls `func` with a node overridden by `stub`, restoring the node's
+    /// previous function or override afterwards, even if `func` panics.
+    pub fn with_override<R>(&mut self, node: &NodeHandle,
+            stub: impl Fn(&[&T]) -> T + Send + Sync + 'static,
+            func: impl FnOnce(&Self) -> R) -> R {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let previous = self.overrides.remove(node.node_key);
+        self.insert_override(node.node_key, Arc::new(stub));
+        // Restores the previous override even if func panics
+        let guard = OverrideGuard {
+            graph: self,
+            key: node.node_key,
+            previous
+        };
+        func(guard.graph)
+    }
+    fn insert_override(&mut self, key: ComputeGraphKey, stub: StubFn<T>) {
+        let node = self.node_storage.get(key).unwrap();
+        assert!(matches!(node.kind, NodeKind::Func(_) | NodeKind::Unary(_) | NodeKind::Binary(_)
+                | NodeKind::ContextFunc(_)),
+            "Node {} cannot be overridden", node.name);
+        self.overrides.insert(key, NodeOverride(stub));
+    }
+    /// Evaluates a single-output node with its override, its offload
+    /// executor or its own function, in that order of precedence.
+    pub(crate) fn evaluate_node(&self, key: ComputeGraphKey, node: &Node<T>, args: &[&T],
+            context: &NodeContext) -> T {
+        if let Some(NodeOverride(stub)) = self.overrides.get(key) {
+            trace!(node = &*node.name; "Evaluating override of node {}", node.name);
+            return stub(args);
+        }
+        self.offload(node, args).unwrap_or_else(|| node.call(args, context))
+    }
+    /// Computes the value of the output node with the given nodes replaced
+    /// by canned values for this run only, without modifying the graph.
+    /// 
+    /// Stubbed nodes are not evaluated, nor are the ancestors only they
+    /// need, so expensive stages can be skipped while iterating on later
+    /// ones. A value kept from an earlier run, such as one from
+    /// [`RetainedValues`](crate::RetainedValues), can be passed as the
+    /// canned value. Multi-output nodes are stubbed through their output
+    /// handles.
+    pub fn compute_stubbed<'a>(&self, stubs: impl IntoIterator<Item = (&'a NodeHandle, T)>)
+            -> T {
+        self.compute_stubbed_with(stubs, Vec::new())
+    }
+    /// Computes the value of the output node like
+    /// [`compute_stubbed`](Self::compute_stubbed), feeding the given values
+    /// to placeholders.
+    pub fn compute_stubbed_with<'a, 'b: 'a>(&self,
+            stubs: impl IntoIterator<Item = (&'a NodeHandle, T)>,
+            inputs: impl IntoIterator<Item = (&'b NodeHandle, T)>) -> T {
+        let mut options = RunOptions::new();
+        for (node, value) in stubs {
+            options = options.stub(node, value);
+        }
+        for (placeholder, value) in inputs {
+            options = options.input(placeholder, value);
+        }
+        self.execute_run(options, None, None).0
+    }
+    /// Collects the canned values of stubbed nodes.
+    pub(crate) fn stub_values<'a>(&self, stubs: impl IntoIterator<Item = (&'a NodeHandle, T)>)
+            -> SecondaryMap<ComputeGraphKey, T> {
+        let mut stub_values = SecondaryMap::new();
+        for (handle, value) in stubs {
+            assert_eq!(handle.graph_id, self.graph_id,
+                "Received NodeHandle for different graph");
+            let node = self.node_storage.get(handle.node_key).unwrap();
+            assert!(!node.is_multi_func(),
+                "Multi-output nodes must be used through their output handles");
+            let prev_value = stub_values.insert(handle.node_key, value);
+            assert!(prev_value.is_none(), "Node {} was given multiple stubs", node.name);
+        }
+        if !stub_values.is_empty() {
+            info!("Evaluating DAG with {} stubbed nodes", stub_values.len());
+        }
+        stub_values
+    }
+}
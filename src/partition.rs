@@ -0,0 +1,290 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeKind};
+
+use std::collections::VecDeque;
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+use log::{info, debug, trace};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// A node in a [`SubPlan`].
+///
+/// Nodes are identified by the same numeric IDs used in DOT output, which
+/// stay the same for as long as the node exists in the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PlanNode {
+    /// The ID of the node.
+    pub id: u64,
+    /// The name of the node.
+    pub name: String,
+    /// The IDs of the node's inputs, in order.
+    pub inputs: Vec<u64>
+}
+
+/// The part of a partitioned graph assigned to one partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubPlan {
+    /// The index of the partition.
+    pub index: usize,
+    /// The nodes of the partition, in a valid evaluation order.
+    pub nodes: Vec<PlanNode>,
+    /// The IDs of nodes in other partitions whose values this partition
+    /// needs.
+    pub inputs: Vec<u64>,
+    /// The IDs of nodes in this partition whose values are needed by other
+    /// partitions or are the graph output.
+    pub outputs: Vec<u64>
+}
+
+/// A value that has to be sent from one partition to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DataDependency {
+    /// The ID of the node whose value is sent.
+    pub node: u64,
+    /// The index of the partition computing the value.
+    pub from_partition: usize,
+    /// The index of the partition using the value.
+    pub to_partition: usize
+}
+
+/// A graph split into partitions, produced by [`ComputationGraph::partition`].
+///
+/// Within a run, partitions only depend on partitions with lower indices,
+/// so evaluating them in index order always has every input available.
+/// Delay sources are the exception, as their values are only used in the
+/// following run.
+///
+/// Serialized plans record the version of their format, so that plans
+/// stored by older versions of this crate can be brought up to date with
+/// [`migrate`](Self::migrate) after they are deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartitionPlan {
+    /// The version of the format the plan was created with, which is 0 for
+    /// plans serialized before the format was versioned.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub format_version: u32,
+    /// The partitions, in index order.
+    pub partitions: Vec<SubPlan>,
+    /// Every value sent between partitions.
+    pub dependencies: Vec<DataDependency>,
+    /// The ID of the graph's output node.
+    pub output: u64
+}
+
+impl PartitionPlan {
+    /// The version of the plan format created by this version of the crate.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Upgrades a plan created with an older format version to the current
+    /// one, applying the built-in migration of each version in turn.
+    /// 
+    /// Plans already in the current format are left unchanged. Panics if the
+    /// plan was created by a newer version of the crate.
+    pub fn migrate(&mut self) {
+        self.migrate_with(|_, _| {});
+    }
+    /// Upgrades a plan like [`migrate`](Self::migrate), additionally calling
+    /// `hook` after each built-in migration with the plan and the version it
+    /// was migrated from.
+    /// 
+    /// This lets applications that store extra data alongside their plans,
+    /// or that post-process plans, upgrade that data in step with the plan.
+    pub fn migrate_with(&mut self, mut hook: impl FnMut(&mut PartitionPlan, u32)) {
+        assert!(self.format_version <= Self::FORMAT_VERSION,
+            "Plan format version {} is newer than the supported version {}",
+            self.format_version, Self::FORMAT_VERSION);
+        while self.format_version < Self::FORMAT_VERSION {
+            let from_version = self.format_version;
+            debug!("Migrating partition plan from format version {}", from_version);
+            match from_version {
+                // Unversioned plans have the same layout as version 1
+                0 => {},
+                _ => unreachable!()
+            }
+            self.format_version = from_version + 1;
+            hook(self, from_version);
+        }
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Splits the nodes the output depends on into at most `partition_count`
+    /// partitions of similar size for distributed execution.
+    /// 
+    /// Partitions start out as contiguous runs of a depth-first evaluation
+    /// order, which keeps related subtrees together. Nodes are then moved
+    /// one at a time to another partition whenever that lowers the number
+    /// of values sent between partitions, as long as no partition grows
+    /// beyond the size of the largest initial one and partitions still only
+    /// depend on lower indices. This greedy refinement stops at a local
+    /// minimum, so the number of values sent is low but not guaranteed to
+    /// be minimal. Outputs of multi-output nodes are always kept in the same
+    /// partition as the node itself. The returned plan only describes the
+    /// graph's structure, so every process executing a partition needs its
+    /// own copy of the graph.
+    pub fn partition(&self, partition_count: usize) -> PartitionPlan {
+        assert!(partition_count > 0, "At least one partition is required");
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Partitioning DAG into {} partitions", partition_count);
+        let order = self.toposort_from(&self.requested_roots(out_key));
+        let partition_count = partition_count.min(order.len());
+        let mut assignment = self.assign_partitions(&order, partition_count);
+        self.refine_partitions(&order, &mut assignment, partition_count);
+
+        let mut partitions: Vec<SubPlan> = (0..partition_count).map(|index| SubPlan {
+            index,
+            nodes: Vec::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new()
+        }).collect();
+        let mut dependencies = Vec::new();
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let partition = *assignment.get(node_key).unwrap();
+            // Delay sources are carried between runs like inputs
+            for input_key in node.input_nodes.iter().chain(node.delay_source().iter()) {
+                let input_partition = *assignment.get(*input_key).unwrap();
+                let input_id = input_key.data().as_ffi();
+                if input_partition != partition
+                        && !partitions[partition].inputs.contains(&input_id) {
+                    partitions[partition].inputs.push(input_id);
+                    if !partitions[input_partition].outputs.contains(&input_id) {
+                        partitions[input_partition].outputs.push(input_id);
+                    }
+                    dependencies.push(DataDependency {
+                        node: input_id,
+                        from_partition: input_partition,
+                        to_partition: partition
+                    });
+                }
+            }
+            partitions[partition].nodes.push(PlanNode {
+                id: node_key.data().as_ffi(),
+                name: node.name.to_string(),
+                inputs: node.input_nodes.iter().map(|key| key.data().as_ffi()).collect()
+            });
+        }
+        let output = out_key.data().as_ffi();
+        let output_partition = *assignment.get(out_key).unwrap();
+        if !partitions[output_partition].outputs.contains(&output) {
+            partitions[output_partition].outputs.push(output);
+        }
+        debug!("Partitioning requires {} cross-partition values", dependencies.len());
+        PartitionPlan {
+            format_version: PartitionPlan::FORMAT_VERSION,
+            partitions,
+            dependencies,
+            output
+        }
+    }
+    /// Assigns each node of `order` to one of `partition_count` contiguous
+    /// runs of the order, keeping the outputs of multi-output nodes with the
+    /// node itself.
+    pub(crate) fn assign_partitions(&self, order: &VecDeque<ComputeGraphKey>,
+            partition_count: usize) -> SecondaryMap<ComputeGraphKey, usize> {
+        let mut assignment: SecondaryMap<ComputeGraphKey, usize> = SecondaryMap::new();
+        for (position, node_key) in order.iter().enumerate() {
+            let node = self.node_storage.get(*node_key).unwrap();
+            // Multi-output values can only be selected where they are computed
+            let partition = match node.kind {
+                NodeKind::MultiOutput(_) => *assignment.get(node.input_nodes[0]).unwrap(),
+                _ => position * partition_count / order.len()
+            };
+            assignment.insert(*node_key, partition);
+        }
+        assignment
+    }
+    /// Greedily moves nodes of `order` between the partitions of
+    /// `assignment` to lower the number of values sent between partitions,
+    /// until no single move lowers it further.
+    /// 
+    /// Moves keep every input of a node in the same or an earlier partition,
+    /// leave no partition empty and no partition larger than the largest
+    /// one of a contiguous split. Multi-output nodes and their outputs are
+    /// never moved, so they stay together.
+    fn refine_partitions(&self, order: &VecDeque<ComputeGraphKey>,
+            assignment: &mut SecondaryMap<ComputeGraphKey, usize>, partition_count: usize) {
+        let max_size = order.len().div_ceil(partition_count);
+        let mut sizes = vec![0; partition_count];
+        // Delay sources count as values sent, but not as ordering constraints
+        let mut consumers: SecondaryMap<ComputeGraphKey, Vec<ComputeGraphKey>> =
+            order.iter().map(|key| (*key, Vec::new())).collect();
+        for node_key in order.iter().copied() {
+            sizes[assignment[node_key]] += 1;
+            let node = self.node_storage.get(node_key).unwrap();
+            for input_key in node.input_nodes.iter().chain(node.delay_source().iter()) {
+                consumers[*input_key].push(node_key);
+            }
+        }
+        // Values the producers affected by moving a node send to other
+        // partitions
+        let sent_values = |assignment: &SecondaryMap<ComputeGraphKey, usize>,
+                node_key: ComputeGraphKey| {
+            let node = self.node_storage.get(node_key).unwrap();
+            let mut producers: Vec<_> = node.input_nodes.iter().copied()
+                .chain(node.delay_source())
+                .chain([node_key])
+                .collect();
+            producers.sort_unstable();
+            producers.dedup();
+            producers.into_iter().map(|producer_key| {
+                let mut targets: Vec<_> = consumers[producer_key].iter()
+                    .map(|consumer_key| assignment[*consumer_key])
+                    .filter(|partition| *partition != assignment[producer_key])
+                    .collect();
+                targets.sort_unstable();
+                targets.dedup();
+                targets.len()
+            }).sum::<usize>()
+        };
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for node_key in order.iter().copied() {
+                let node = self.node_storage.get(node_key).unwrap();
+                let partition = assignment[node_key];
+                if matches!(node.kind, NodeKind::MultiOutput(_)) || node.is_multi_func()
+                        || sizes[partition] == 1 {
+                    continue;
+                }
+                let earliest = node.input_nodes.iter()
+                    .map(|input_key| assignment[*input_key])
+                    .max()
+                    .unwrap_or(0);
+                let latest = consumers[node_key].iter()
+                    .filter(|consumer_key| {
+                        self.node_storage.get(**consumer_key).unwrap().input_nodes
+                            .contains(&node_key)
+                    })
+                    .map(|consumer_key| assignment[*consumer_key])
+                    .min()
+                    .unwrap_or(partition_count - 1);
+                let current = sent_values(assignment, node_key);
+                let mut best = None;
+                let targets = (earliest..=latest)
+                    .filter(|target| *target != partition && sizes[*target] < max_size);
+                for target in targets {
+                    assignment[node_key] = target;
+                    let sent = sent_values(assignment, node_key);
+                    if sent < best.map_or(current, |(_, best_sent)| best_sent) {
+                        best = Some((target, sent));
+                    }
+                }
+                assignment[node_key] = partition;
+                if let Some((target, _)) = best {
+                    trace!("Moving node {} from partition {} to {}", node.name, partition,
+                        target);
+                    assignment[node_key] = target;
+                    sizes[partition] -= 1;
+                    sizes[target] += 1;
+                    moved = true;
+                }
+            }
+        }
+    }
+}
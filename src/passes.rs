@@ -0,0 +1,357 @@
+use crate::{ComputationGraph, ComputeGraphKey, InputList, Node, NodeKind, RetentionPolicy};
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+/// A rewrite of a [`ComputationGraph`] that can be run by
+/// [`ComputationGraph::optimize`].
+pub trait GraphPass<T> {
+    /// Returns a short name for the pass, used in reports and logs.
+    fn name(&self) -> &str;
+    /// Runs the pass on the graph, returning a report of what changed.
+    fn run(&self, graph: &mut ComputationGraph<T>) -> PassReport;
+}
+
+/// A record of the changes made by a [`GraphPass`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassReport {
+    pass_name: String,
+    changes: Vec<String>
+}
+impl PassReport {
+    /// Creates an empty report for the named pass.
+    pub fn new(pass_name: String) -> PassReport {
+        PassReport {
+            pass_name,
+            changes: Vec::new()
+        }
+    }
+    /// Records a human-readable description of a change.
+    pub fn record(&mut self, change: String) {
+        self.changes.push(change);
+    }
+    /// Returns the name of the pass that produced this report.
+    pub fn pass_name(&self) -> &str {
+        &self.pass_name
+    }
+    /// Returns the descriptions of all recorded changes.
+    pub fn changes(&self) -> &[String] {
+        &self.changes
+    }
+    /// Returns `true` if the pass changed the graph.
+    pub fn changed(&self) -> bool {
+        !self.changes.is_empty()
+    }
+}
+impl fmt::Display for PassReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} change(s)", self.pass_name, self.changes.len())?;
+        for change in self.changes.iter() {
+            write!(f, "\n  {}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Node<T> {
+    fn metadata(&self) -> NodeMetadata<'_> {
+        NodeMetadata {
+            name: &self.name,
+            device: self.device.as_deref(),
+            tags: &self.tags,
+            version_tag: self.version_tag.as_deref()
+        }
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Runs the given passes over the graph in order, returning one report
+    /// per pass.
+    pub fn optimize(&mut self, passes: &[Box<dyn GraphPass<T>>]) -> Vec<PassReport> {
+        info!("Optimizing DAG");
+        passes.iter().map(|pass| {
+            debug!("Running pass {}", pass.name());
+            let report = pass.run(self);
+            debug!("Pass {} made {} change(s)", pass.name(), report.changes().len());
+            report
+        }).collect()
+    }
+    /// Returns whether a node can be fused with a neighbour without losing
+    /// anything attached to it.
+    fn is_fusible(&self, key: ComputeGraphKey) -> bool {
+        let node = self.node_storage.get(key).unwrap();
+        matches!(node.kind, NodeKind::Func(_)) && node.device.is_none() && node.tags.is_empty()
+            && node.version_tag.is_none() && !self.lint_hints.has_node_hints(key)
+            && !self.is_observed(key)
+    }
+    /// Returns whether the node has an override, tap, value dump, log
+    /// setting or retention policy, which rewriting it away would lose.
+    fn is_observed(&self, key: ComputeGraphKey) -> bool {
+        self.overrides.contains_key(key) || self.node_logging.contains_key(key)
+            || self.is_tapped(key) || self.is_dumped(key)
+            || self.node_storage.get(key).unwrap().retention != RetentionPolicy::DropEagerly
+    }
+}
+
+/// A pass removing the nodes that the output does not depend on.
+///
+/// This runs [`ComputationGraph::prune`] as part of a pass pipeline.
+#[derive(Debug, Default)]
+pub struct DeadNodeElimination;
+impl DeadNodeElimination {
+    /// Creates the pass.
+    pub fn new() -> DeadNodeElimination {
+        DeadNodeElimination
+    }
+}
+impl<T> GraphPass<T> for DeadNodeElimination {
+    fn name(&self) -> &str {
+        "dead_node_elimination"
+    }
+    fn run(&self, graph: &mut ComputationGraph<T>) -> PassReport {
+        let mut report = PassReport::new(GraphPass::<T>::name(self).to_owned());
+        for name in graph.prune_names() {
+            report.record(format!("removed {}", name));
+        }
+        report
+    }
+}
+
+/// A description of a node, passed to the equivalence function of
+/// [`CommonSubexpressionElimination`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeMetadata<'a> {
+    /// The name of the node.
+    pub name: &'a str,
+    /// The device the node is placed on, if any.
+    pub device: Option<&'a str>,
+    /// The tags of the node.
+    pub tags: &'a [String],
+    /// The version tag of the node, if any.
+    pub version_tag: Option<&'a str>
+}
+
+/// A pass merging duplicate nodes and rewiring their consumers.
+///
+/// Two nodes are duplicates if they are of the same kind, have identical
+/// inputs, devices and tags, and the user-provided equivalence function,
+/// called with the metadata of both nodes, returns `true`. Only nodes
+/// marked pure with [`ComputationGraph::mark_pure`] are merged, and never
+/// nodes with an override, tap, value dump, log setting or retention
+/// policy, whose effects would be lost. Because nodes are visited in
+/// dependency order, merging inputs can expose further duplicates
+/// downstream within the same run. Handles to merged-away nodes become
+/// invalid.
+pub struct CommonSubexpressionElimination<F> {
+    equivalent: F
+}
+impl<F: Fn(&NodeMetadata, &NodeMetadata) -> bool> CommonSubexpressionElimination<F> {
+    /// Creates the pass with the given node equivalence function.
+    pub fn new(equivalent: F) -> CommonSubexpressionElimination<F> {
+        CommonSubexpressionElimination {
+            equivalent
+        }
+    }
+}
+impl<T, F> GraphPass<T> for CommonSubexpressionElimination<F>
+where
+    F: Fn(&NodeMetadata, &NodeMetadata) -> bool
+{
+    fn name(&self) -> &str {
+        "common_subexpression_elimination"
+    }
+    fn run(&self, graph: &mut ComputationGraph<T>) -> PassReport {
+        let mut report = PassReport::new(GraphPass::<T>::name(self).to_owned());
+        // Nodes already kept, grouped by their inputs
+        let mut canonical: HashMap<InputList, Vec<ComputeGraphKey>> =
+            HashMap::new();
+        for node_key in graph.toposort_all() {
+            let node = graph.node_storage.get(node_key).unwrap();
+            // Outputs of multi-output nodes are as pure as the node itself,
+            // which is their only input
+            let is_output = matches!(node.kind, NodeKind::MultiOutput(_));
+            if !(node.pure || is_output) || graph.is_observed(node_key) {
+                continue;
+            }
+            let candidates = canonical.entry(node.input_nodes.clone()).or_default();
+            let duplicate_of = candidates.iter().copied().find(|other_key| {
+                let other = graph.node_storage.get(*other_key).unwrap();
+                if mem::discriminant(&node.kind) != mem::discriminant(&other.kind)
+                        || node.device != other.device || node.tags != other.tags {
+                    return false;
+                }
+                match (&node.kind, &other.kind) {
+                    // Outputs of the same multi-output node are equal iff they
+                    // select the same output
+                    (NodeKind::MultiOutput(index), NodeKind::MultiOutput(other_index)) => {
+                        index == other_index
+                    },
+                    _ => (self.equivalent)(&node.metadata(), &other.metadata())
+                }
+            });
+            match duplicate_of {
+                Some(keep_key) => {
+                    report.record(format!("merged {} into {}", node.name,
+                        graph.node_storage.get(keep_key).unwrap().name));
+                    graph.replace_uses(node_key, keep_key);
+                    graph.remove_unused_node(node_key);
+                },
+                None => candidates.push(node_key)
+            }
+        }
+        report
+    }
+}
+
+/// A pass pre-evaluating pure nodes whose ancestors are all pure.
+///
+/// Each such node that is used by an impure node or is the output is
+/// replaced by a constant node with the same name returning a clone of the
+/// precomputed value. Pure nodes that are no longer used afterwards are
+/// removed, and handles to them become invalid. Multi-output nodes are
+/// never folded.
+#[derive(Debug, Default)]
+pub struct ConstantFolding;
+impl ConstantFolding {
+    /// Creates the pass.
+    pub fn new() -> ConstantFolding {
+        ConstantFolding
+    }
+}
+impl<T: Clone + Send + Sync + 'static> GraphPass<T> for ConstantFolding {
+    fn name(&self) -> &str {
+        "constant_folding"
+    }
+    fn run(&self, graph: &mut ComputationGraph<T>) -> PassReport {
+        let mut report = PassReport::new(GraphPass::<T>::name(self).to_owned());
+        let order = graph.toposort_all();
+
+        // Evaluate every node that only depends on pure nodes
+        let mut values: SecondaryMap<ComputeGraphKey, T> = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            let node = graph.node_storage.get(node_key).unwrap();
+            let foldable_kind = !node.is_multi_func()
+                && !matches!(node.kind, NodeKind::MultiOutput(_));
+            // Observed nodes, including overridden ones, are left for the
+            // run to evaluate
+            if !node.pure || !foldable_kind || graph.is_observed(node_key)
+                    || !node.input_nodes.iter().all(|key| values.contains_key(*key)) {
+                continue;
+            }
+            let node_inputs: Vec<&T> = node.input_nodes.iter()
+                .map(|key| values.get(*key).unwrap())
+                .collect();
+            let value = node.call(&node_inputs, &graph.run_context(0));
+            values.insert(node_key, value);
+        }
+
+        // Only fold the boundary between constant and non-constant nodes
+        let mut boundary: HashSet<ComputeGraphKey> = HashSet::new();
+        for (node_key, node) in graph.node_storage.iter() {
+            if values.contains_key(node_key) {
+                continue;
+            }
+            for input_key in node.input_nodes.iter() {
+                if values.contains_key(*input_key) {
+                    boundary.insert(*input_key);
+                }
+            }
+        }
+        if let Some(output_key) = graph.output_node {
+            if values.contains_key(output_key) {
+                boundary.insert(output_key);
+            }
+        }
+        for node_key in order.iter().copied() {
+            if !boundary.contains(&node_key)
+                    || graph.node_storage.get(node_key).unwrap().input_nodes.is_empty() {
+                continue;
+            }
+            let value = values.remove(node_key).unwrap();
+            let node = graph.node_storage.get_mut(node_key).unwrap();
+            node.kind = NodeKind::Func(Box::new(move |_| value.clone()));
+            let old_inputs = std::mem::take(&mut node.input_nodes);
+            report.record(format!("folded {}", node.name));
+            for input_key in old_inputs {
+                *graph.node_refcount.get_mut(input_key).unwrap() -= 1;
+            }
+        }
+
+        // Remove the constant nodes that are now unused
+        for node_key in order.iter().rev().copied() {
+            if values.contains_key(node_key) && !boundary.contains(&node_key)
+                    && *graph.node_refcount.get(node_key).unwrap() == 0 {
+                let node = graph.remove_unused_node(node_key);
+                report.record(format!("removed {}", node.name));
+            }
+        }
+        report
+    }
+}
+
+/// A pass fusing linear chains of nodes into single composite nodes.
+///
+/// A node whose only input is used by nothing else absorbs that input,
+/// composing their functions so the intermediate value never has to be
+/// stored in the graph. Fused nodes keep the name of the last node of the
+/// chain, and handles to absorbed nodes become invalid. Only nodes inserted
+/// with [`ComputationGraph::insert_node`] are fused, and never nodes placed
+/// on a device or with tags, a version tag, lint hints, an override, tap,
+/// value dump, log setting or retention policy.
+#[derive(Debug, Default)]
+pub struct NodeFusion;
+impl NodeFusion {
+    /// Creates the pass.
+    pub fn new() -> NodeFusion {
+        NodeFusion
+    }
+}
+impl<T: 'static> GraphPass<T> for NodeFusion {
+    fn name(&self) -> &str {
+        "node_fusion"
+    }
+    fn run(&self, graph: &mut ComputationGraph<T>) -> PassReport {
+        let mut report = PassReport::new(GraphPass::<T>::name(self).to_owned());
+        // Visiting in dependency order lets whole chains fuse in one run
+        for node_key in graph.toposort_all() {
+            let node = graph.node_storage.get(node_key).unwrap();
+            if !graph.is_fusible(node_key) || node.input_nodes.len() != 1 {
+                continue;
+            }
+            let input_key = node.input_nodes[0];
+            // The output designation also counts towards the refcount
+            if !graph.is_fusible(input_key) || *graph.node_refcount.get(input_key).unwrap() != 1 {
+                continue;
+            }
+
+            // Move the inputs of the absorbed node over before removing it
+            let fused_inputs = graph.node_storage.get(input_key).unwrap().input_nodes.clone();
+            for fused_input in fused_inputs.iter() {
+                *graph.node_refcount.get_mut(*fused_input).unwrap() += 1;
+            }
+            *graph.node_refcount.get_mut(input_key).unwrap() -= 1;
+            let input_node = graph.remove_unused_node(input_key);
+            let node = graph.node_storage.get_mut(node_key).unwrap();
+            report.record(format!("fused {} into {}", input_node.name, node.name));
+            let first = match input_node.kind {
+                NodeKind::Func(func) => func,
+                _ => unreachable!()
+            };
+            let second = match std::mem::replace(&mut node.kind, NodeKind::Placeholder) {
+                NodeKind::Func(func) => func,
+                _ => unreachable!()
+            };
+            node.kind = NodeKind::Func(Box::new(move |args| {
+                let intermediate = first(args);
+                second(&[&intermediate])
+            }));
+            node.input_nodes = fused_inputs;
+            node.pure = node.pure && input_node.pure;
+        }
+        report
+    }
+}
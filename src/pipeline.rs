@@ -0,0 +1,156 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind,
+    RetentionPolicy};
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+// Values of one block passed from a stage to the next
+type StageMessage<T> = (usize, SecondaryMap<ComputeGraphKey, T>);
+
+// The part of the evaluation order run by one pipeline worker
+struct Stage {
+    order: VecDeque<ComputeGraphKey>,
+    // Remaining uses within the stage, plus one for forwarded values
+    refcounts: SecondaryMap<ComputeGraphKey, u32>,
+    // Values later stages or the caller need
+    forwarded: SecondaryMap<ComputeGraphKey, ()>
+}
+
+impl<T: Send + Sync> ComputationGraph<T> {
+    /// Evaluates the graph once per block pulled from `blocks` like
+    /// [`compute_stream`](Self::compute_stream), with the evaluation split
+    /// into up to `stages` stages running on separate threads, returning
+    /// the output values in block order.
+    /// 
+    /// Each stage evaluates a contiguous part of the evaluation order and
+    /// passes the values later stages need to the next stage through a
+    /// channel holding at most `capacity` blocks. Stages therefore work on
+    /// different blocks at the same time, while a slow stage, such as one
+    /// containing a sink writing to disk, blocks the stages before it
+    /// instead of letting blocks pile up in memory. Blocks are only pulled
+    /// from `blocks` as the first stage is ready for them.
+    /// 
+    /// Nodes are always evaluated in block order, so stateful nodes see the
+    /// blocks in sequence. Panics if the output depends on a delay node.
+    pub fn compute_pipelined<I>(&self, input: &NodeHandle, blocks: I, stages: usize,
+            capacity: usize) -> Vec<T>
+            where I: IntoIterator<Item = T>, I::IntoIter: Send {
+        assert_eq!(input.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        assert!(self.node_storage.get(input.node_key).unwrap().is_placeholder(),
+            "Stream input must be a placeholder");
+        let out_key = self.output_node.expect("Output not yet designated");
+        let order = self.evaluation_order(out_key);
+        info!("Evaluating DAG over stream in up to {} stages", stages);
+        let input_key = input.node_key;
+        let runs = blocks.into_iter().map(move |block| {
+            let mut values = SecondaryMap::new();
+            values.insert(input_key, block);
+            values
+        });
+        self.run_pipeline(order, out_key, stages, capacity, runs)
+    }
+    /// Evaluates `order` once per set of input values pulled from `runs`,
+    /// split into up to `stages` pipelined stages, returning the values of
+    /// `out_key` in run order.
+    /// 
+    /// Panics if `order` contains delay nodes, whose values would have to be
+    /// carried from one block to the next across stages.
+    pub(crate) fn run_pipeline(&self, order: VecDeque<ComputeGraphKey>,
+            out_key: ComputeGraphKey, stages: usize, capacity: usize,
+            runs: impl Iterator<Item = SecondaryMap<ComputeGraphKey, T>> + Send) -> Vec<T> {
+        assert!(stages > 0, "At least one stage is required");
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            assert!(!matches!(node.kind, NodeKind::Delay(_, _)),
+                "Node {} cannot be evaluated in a pipeline", node.name);
+        }
+        let stages = self.pipeline_stages(order, out_key, stages);
+        debug!("Split evaluation order into {} stages", stages.len());
+        thread::scope(|scope| {
+            let (first_sender, mut receiver) = mpsc::sync_channel::<StageMessage<T>>(capacity);
+            scope.spawn(move || {
+                for (block_index, values) in runs.enumerate() {
+                    // A failed send means a later stage panicked
+                    if first_sender.send((block_index, values)).is_err() {
+                        break;
+                    }
+                }
+            });
+            for (stage_index, stage) in stages.into_iter().enumerate() {
+                let (sender, next_receiver) = mpsc::sync_channel(capacity);
+                let stage_receiver = std::mem::replace(&mut receiver, next_receiver);
+                scope.spawn(move || self.run_stage(stage_index, &stage, stage_receiver, sender));
+            }
+            receiver.iter()
+                .map(|(_, mut values)| values.remove(out_key).unwrap())
+                .collect()
+        })
+    }
+    /// Evaluates one stage on each block received, passing the values later
+    /// stages need on to `sender`.
+    fn run_stage(&self, stage_index: usize, stage: &Stage,
+            receiver: Receiver<StageMessage<T>>, sender: SyncSender<StageMessage<T>>) {
+        for (block_index, inputs) in receiver {
+            debug!("Evaluating stage {} on block {}", stage_index, block_index);
+            let mut values = self.execute_order(&stage.order, Some(stage.refcounts.clone()),
+                inputs, &self.run_context(block_index));
+            values.retain(|key, _| stage.forwarded.contains_key(key));
+            if sender.send((block_index, values)).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Splits an evaluation order into up to `stage_count` stages.
+    fn pipeline_stages(&self, order: VecDeque<ComputeGraphKey>, out_key: ComputeGraphKey,
+            stage_count: usize) -> Vec<Stage> {
+        let stage_count = stage_count.min(order.len());
+        let assignment = self.assign_partitions(&order, stage_count);
+        // The last stage using each value, where the output is used by the caller
+        let mut last_use: SecondaryMap<ComputeGraphKey, usize> = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            let stage = *assignment.get(node_key).unwrap();
+            for input_key in self.node_storage.get(node_key).unwrap().input_nodes.iter() {
+                let last = last_use.entry(*input_key).unwrap().or_insert(stage);
+                *last = (*last).max(stage);
+            }
+        }
+        last_use.insert(out_key, stage_count);
+
+        let mut stages: Vec<Stage> = (0..stage_count).map(|_| Stage {
+            order: VecDeque::new(),
+            refcounts: SecondaryMap::new(),
+            forwarded: SecondaryMap::new()
+        }).collect();
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            let stage_index = *assignment.get(node_key).unwrap();
+            let stage = &mut stages[stage_index];
+            stage.order.push_back(node_key);
+            let retained = (node.retention != RetentionPolicy::DropEagerly) as u32;
+            *stage.refcounts.entry(node_key).unwrap().or_insert(0) += retained;
+            for input_key in node.input_nodes.iter() {
+                *stage.refcounts.entry(*input_key).unwrap().or_insert(0) += 1;
+            }
+            // Values are passed through every stage up to their last use, and
+            // supplied values enter at the first stage
+            let first = match node.kind {
+                NodeKind::Placeholder | NodeKind::Source(_) => 0,
+                _ => stage_index
+            };
+            let last = last_use.get(node_key).copied().unwrap_or(stage_index);
+            for later_stage in stages[first..last.min(stage_count)].iter_mut() {
+                *later_stage.refcounts.entry(node_key).unwrap().or_insert(0) += 1;
+                later_stage.forwarded.insert(node_key, ());
+            }
+        }
+        stages
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{ComputationGraph, ComputeGraphKey, RunOptions, SizeHint};
+
+use std::time::Duration;
+
+use slotmap::KeyData;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// How long a single node took to evaluate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeTiming {
+    /// The ID of the node.
+    pub node: u64,
+    /// The name of the node.
+    pub node_name: String,
+    /// The time taken to evaluate the node.
+    pub duration: Duration,
+    /// The approximate size in bytes of the value the node produced, if
+    /// measured by [`ComputationGraph::compute_profiled_sized`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub output_bytes: Option<usize>
+}
+
+/// Per-node timings of a run, produced by
+/// [`ComputationGraph::compute_profiled`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExecutionReport {
+    /// The timing of each evaluated node, in evaluation order.
+    pub timings: Vec<NodeTiming>,
+    /// The wall time of the whole run, including planning.
+    pub total: Duration
+}
+impl ExecutionReport {
+    /// Returns the time the node with the given ID took to evaluate.
+    pub fn duration_of(&self, node: u64) -> Option<Duration> {
+        self.timings.iter()
+            .find(|timing| timing.node == node)
+            .map(|timing| timing.duration)
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Computes the value of the output node, measuring how long each node
+    /// takes to evaluate.
+    /// 
+    /// The graph is not consumed. Runs combining profiling with other
+    /// options, such as placeholder inputs, are made with
+    /// [`run`](Self::run) and [`RunOptions::profiled`].
+    pub fn compute_profiled(&self) -> (T, ExecutionReport) {
+        let (value, _, report) = self.execute_run(RunOptions::new().profiled(), None, None);
+        (value, report.unwrap())
+    }
+    /// Computes the value of the output node like
+    /// [`compute_profiled`](Self::compute_profiled), additionally recording
+    /// the approximate size of each node's value.
+    pub fn compute_profiled_sized(&self) -> (T, ExecutionReport) where T: SizeHint {
+        let (value, _, report) = self.execute_run(RunOptions::new().profiled_sized(), None,
+            None);
+        (value, report.unwrap())
+    }
+    /// Records the node timings of a previous run as the costs of the
+    /// nodes, used to estimate progress and to order evaluations with
+    /// [`SchedulingStrategy::ProfileGuided`](crate::SchedulingStrategy::ProfileGuided).
+    /// 
+    /// Applying a profile does not change the scheduling strategy, so that
+    /// profiles can also be applied only for estimates. Scheduling by the
+    /// profile takes two steps: apply the report of a run such as
+    /// [`compute_profiled`](Self::compute_profiled), then switch to
+    /// [`ProfileGuided`](crate::SchedulingStrategy::ProfileGuided) with
+    /// [`set_scheduling_strategy`](Self::set_scheduling_strategy).
+    /// Timings of nodes that are no longer in the graph are ignored, and
+    /// nodes without a timing are assumed to take no time. Value sizes in
+    /// the report are kept for [`estimated_memory`](Self::estimated_memory).
+    pub fn apply_profile(&mut self, report: &ExecutionReport) {
+        self.node_costs.clear();
+        self.node_sizes.clear();
+        for timing in report.timings.iter() {
+            let key = ComputeGraphKey::from(KeyData::from_ffi(timing.node));
+            if self.node_storage.contains_key(key) {
+                self.node_costs.insert(key, timing.duration);
+                if let Some(bytes) = timing.output_bytes {
+                    self.node_sizes.insert(key, bytes);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::{ComputationGraph, NodeHandle};
+
+use std::time::SystemTime;
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+use log::info;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// Where a computed value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Provenance {
+    /// The ID of the node that produced the value.
+    pub node: u64,
+    /// The name of the node that produced the value.
+    pub node_name: String,
+    /// The IDs of the node's inputs that were evaluated, in order. The
+    /// unchosen branches of select nodes and the skipped inputs of
+    /// short-circuit nodes are left out.
+    pub inputs: Vec<u64>,
+    /// Whether the value was supplied from outside the graph through a
+    /// placeholder.
+    pub external: bool,
+    /// When the node finished evaluating.
+    pub computed_at: SystemTime,
+    /// The node's version tag, if one was set with
+    /// [`ComputationGraph::set_version_tag`].
+    pub version_tag: Option<String>
+}
+
+/// A computed value together with the provenance of every value it was
+/// derived from, produced by [`ComputationGraph::compute_traced`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Traced<T> {
+    /// The value of the output node.
+    pub value: T,
+    /// The provenance of each evaluated node, in evaluation order. Nodes
+    /// skipped by select and short-circuit nodes have none. The provenance
+    /// of the output comes last.
+    pub provenance: Vec<Provenance>
+}
+impl<T> Traced<T> {
+    /// Returns the provenance of the output value.
+    pub fn output_provenance(&self) -> &Provenance {
+        self.provenance.last().unwrap()
+    }
+    /// Returns the provenance of the value of the node with the given ID.
+    pub fn provenance_of(&self, node: u64) -> Option<&Provenance> {
+        self.provenance.iter().find(|record| record.node == node)
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets a tag identifying the version of a node's function, which is
+    /// recorded in the [`Provenance`] of its values.
+    pub fn set_version_tag(&mut self, node: &NodeHandle, tag: String) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.node_storage.get_mut(node.node_key).unwrap().version_tag = Some(tag);
+    }
+    /// Computes the value of the output node, recording the provenance of
+    /// every value it was derived from.
+    /// 
+    /// The graph is not consumed, and must not contain placeholders.
+    pub fn compute_traced(&self) -> Traced<T> {
+        self.compute_traced_with(Vec::new())
+    }
+    /// Computes the value of the output node, feeding the given values to
+    /// placeholders and recording the provenance of every value it was
+    /// derived from.
+    /// 
+    /// The graph is not consumed.
+    pub fn compute_traced_with<'a>(&self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>) -> Traced<T> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG with provenance");
+        let order = self.evaluation_order(out_key);
+        let refcounts = self.order_refcounts(&order, out_key);
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        let mut computed_at = SecondaryMap::new();
+        let mut values = self.execute_order_observed(&order, Some(refcounts),
+            placeholder_values, &self.run_context(0), &mut |node_key, _| {
+                computed_at.insert(node_key, SystemTime::now());
+            });
+        // Nodes skipped as unchosen branches were never observed
+        let provenance = order.iter().filter_map(|node_key| {
+            let node = self.node_storage.get(*node_key).unwrap();
+            Some(Provenance {
+                node: node_key.data().as_ffi(),
+                node_name: node.name.to_string(),
+                inputs: node.input_nodes.iter()
+                    .filter(|key| computed_at.contains_key(**key))
+                    .map(|key| key.data().as_ffi())
+                    .collect(),
+                external: node.is_placeholder(),
+                computed_at: *computed_at.get(*node_key)?,
+                version_tag: node.version_tag.clone()
+            })
+        }).collect();
+        Traced {
+            value: values.remove(out_key).unwrap(),
+            provenance
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle};
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+use log::info;
+
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+/// The recorded evaluation of one node, as written by
+/// [`ComputationGraph::compute_recorded`].
+///
+/// The values of the node's inputs are the outputs recorded for the nodes
+/// listed in `inputs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceRecord<T> {
+    /// The ID of the node.
+    pub node: u64,
+    /// The name of the node.
+    pub name: String,
+    /// The IDs of the node's inputs, in order.
+    pub inputs: Vec<u64>,
+    /// The value the node produced.
+    pub output: T
+}
+impl<T: DeserializeOwned> TraceRecord<T> {
+    /// Reads the record of the node with the given ID from a trace directory.
+    pub fn read(trace_dir: impl AsRef<Path>, node: u64) -> io::Result<TraceRecord<T>> {
+        let file = File::open(record_path(trace_dir.as_ref(), node))?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+}
+
+fn record_path(trace_dir: &Path, node: u64) -> PathBuf {
+    trace_dir.join(format!("{}.json", node))
+}
+
+impl<T: Serialize> ComputationGraph<T> {
+    /// Computes the value of the output node, writing a [`TraceRecord`] of
+    /// every evaluated node to `trace_dir` as JSON.
+    /// 
+    /// The graph is not consumed, and must not contain placeholders. Every
+    /// value is kept until the end of the run so that it can be recorded.
+    pub fn compute_recorded(&self, trace_dir: impl AsRef<Path>) -> io::Result<T> {
+        self.compute_recorded_with(trace_dir, Vec::new())
+    }
+    /// Computes the value of the output node, feeding the given values to
+    /// placeholders and writing a [`TraceRecord`] of every evaluated node,
+    /// including the placeholders, to `trace_dir` as JSON.
+    /// 
+    /// The graph is not consumed.
+    pub fn compute_recorded_with<'a>(&self, trace_dir: impl AsRef<Path>,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>) -> io::Result<T> {
+        let trace_dir = trace_dir.as_ref();
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG while recording to {}", trace_dir.display());
+        fs::create_dir_all(trace_dir)?;
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        let order = self.evaluation_order(out_key);
+        let mut values = self.execute_order(&order, None, placeholder_values,
+            &self.run_context(0));
+        for node_key in order.iter().copied() {
+            // Multi-output nodes are recorded through their output handles
+            let Some(output) = values.get(node_key) else {
+                continue;
+            };
+            let node = self.node_storage.get(node_key).unwrap();
+            let record = TraceRecord {
+                node: node_key.data().as_ffi(),
+                name: node.name.to_string(),
+                inputs: node.input_nodes.iter().map(|key| key.data().as_ffi()).collect(),
+                output
+            };
+            let file = File::create(record_path(trace_dir, record.node))?;
+            serde_json::to_writer(BufWriter::new(file), &record)?;
+        }
+        Ok(values.remove(out_key).unwrap())
+    }
+}
+
+impl<T: DeserializeOwned> ComputationGraph<T> {
+    /// Computes the value of the output node, substituting the values
+    /// recorded in `trace_dir` by
+    /// [`compute_recorded`](Self::compute_recorded) for the given nodes and
+    /// for every placeholder.
+    /// 
+    /// Ancestors needed only by substituted nodes are not evaluated, so a
+    /// node can be re-run on exactly the inputs it saw in the recorded run.
+    /// The graph is not consumed.
+    pub fn replay(&self, trace_dir: impl AsRef<Path>, nodes: &[&NodeHandle]) -> io::Result<T> {
+        let trace_dir = trace_dir.as_ref();
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Replaying DAG from {}", trace_dir.display());
+        let order = self.evaluation_order(out_key);
+        let placeholders = order.iter().copied()
+            .filter(|key| self.node_storage.get(*key).unwrap().is_placeholder());
+        let replayed_keys = nodes.iter()
+            .map(|handle| {
+                assert_eq!(handle.graph_id, self.graph_id,
+                    "Received NodeHandle for different graph");
+                handle.node_key
+            });
+        let mut recorded: SecondaryMap<ComputeGraphKey, T> = SecondaryMap::new();
+        for node_key in placeholders.chain(replayed_keys) {
+            let record = TraceRecord::read(trace_dir, node_key.data().as_ffi())?;
+            recorded.insert(node_key, record.output);
+        }
+
+        let (order, _) = self.skip_known(order, out_key, |key| recorded.contains_key(key));
+        let refcounts = self.order_refcounts(&order, out_key);
+        let mut values = self.execute_order(&order, Some(refcounts), recorded,
+            &self.run_context(0));
+        Ok(values.remove(out_key).unwrap())
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{ComputationGraph, ComputeGraphKey, PartitionPlan, SubPlan};
+
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+
+use slotmap::{KeyData, SecondaryMap};
+use log::{info, debug};
+
+/// A backend that evaluates [`SubPlan`]s, typically on another machine.
+///
+/// This is deliberately independent of any transport: implementations are
+/// expected to ship the sub-plan and its input values to a worker holding
+/// its own copy of the graph, which evaluates them with
+/// [`ComputationGraph::execute_subplan`] and sends back the outputs.
+pub trait RemoteExecutor<T> {
+    /// The error returned when a sub-plan could not be evaluated.
+    type Error;
+    /// The future resolving to the sub-plan's output values.
+    type Future: Future<Output = Result<HashMap<u64, T>, Self::Error>>;
+    /// Submits a sub-plan for evaluation with the values of its inputs,
+    /// keyed by node ID.
+    /// 
+    /// The future must resolve to the values of every node listed in the
+    /// sub-plan's outputs.
+    fn submit(&self, subplan: &SubPlan, inputs: HashMap<u64, T>) -> Self::Future;
+}
+
+impl<T> ComputationGraph<T> {
+    /// Evaluates the nodes of a sub-plan of this graph, taking the values of
+    /// its inputs from `inputs` and returning the values of its outputs.
+    /// 
+    /// The graph is not consumed. The graph must not contain placeholders or
+    /// delay nodes.
+    pub fn execute_subplan(&self, subplan: &SubPlan, inputs: HashMap<u64, T>)
+            -> HashMap<u64, T> {
+        debug!("Evaluating partition {}", subplan.index);
+        let to_key = |id: u64| {
+            let key = ComputeGraphKey::from(KeyData::from_ffi(id));
+            assert!(self.node_storage.contains_key(key), "Node {} is not in the graph", id);
+            key
+        };
+        let order: VecDeque<_> = subplan.nodes.iter().map(|node| to_key(node.id)).collect();
+        let mut refcounts: SecondaryMap<ComputeGraphKey, u32> = SecondaryMap::new();
+        for node_key in order.iter() {
+            for input_key in self.node_storage.get(*node_key).unwrap().input_nodes.iter() {
+                *refcounts.entry(*input_key).unwrap().or_insert(0) += 1;
+            }
+        }
+        for output_id in subplan.outputs.iter() {
+            *refcounts.entry(to_key(*output_id)).unwrap().or_insert(0) += 1;
+        }
+        for node_key in order.iter() {
+            refcounts.entry(*node_key).unwrap().or_insert(0);
+        }
+        let inputs = inputs.into_iter().map(|(id, value)| (to_key(id), value)).collect();
+        let mut values = self.execute_order(&order, Some(refcounts), inputs,
+            &self.run_context(0));
+        subplan.outputs.iter()
+            .map(|id| (*id, values.remove(to_key(*id)).unwrap()))
+            .collect()
+    }
+}
+
+impl PartitionPlan {
+    /// Evaluates the plan by submitting its partitions to `executor`,
+    /// returning the value of the graph's output.
+    /// 
+    /// Every partition is submitted as soon as the values of all its inputs
+    /// are available, so independent partitions run concurrently and a
+    /// partition never waits for partitions it does not depend on. The
+    /// futures returned by the executor are driven on the calling thread,
+    /// which blocks until they complete. Once the executor returns an error,
+    /// no more partitions are submitted, and the first error is returned
+    /// once the partitions running alongside it have finished.
+    pub fn execute_remote<T, E>(&self, executor: &E) -> Result<T, E::Error>
+    where
+        T: Clone,
+        E: RemoteExecutor<T>
+    {
+        assert_eq!(self.format_version, PartitionPlan::FORMAT_VERSION,
+            "Partition plan must be migrated before it is executed");
+        info!("Evaluating DAG over {} partitions", self.partitions.len());
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut context = Context::from_waker(&waker);
+        let mut values: HashMap<u64, T> = HashMap::new();
+        let mut pending: Vec<&SubPlan> = self.partitions.iter().collect();
+        let mut running: Vec<(&SubPlan, Pin<Box<E::Future>>)> = Vec::new();
+        let mut error = None;
+        loop {
+            if error.is_none() {
+                let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter()
+                    .partition(|subplan| {
+                        subplan.inputs.iter().all(|id| values.contains_key(id))
+                    });
+                pending = waiting;
+                for subplan in ready {
+                    debug!("Submitting partition {}", subplan.index);
+                    let inputs = subplan.inputs.iter()
+                        .map(|id| (*id, values.get(id).unwrap().clone()))
+                        .collect();
+                    running.push((subplan, Box::pin(executor.submit(subplan, inputs))));
+                }
+            }
+            if running.is_empty() {
+                break;
+            }
+            let mut completed = false;
+            let mut index = 0;
+            while index < running.len() {
+                let Poll::Ready(result) = running[index].1.as_mut().poll(&mut context) else {
+                    index += 1;
+                    continue;
+                };
+                let (subplan, _) = running.swap_remove(index);
+                completed = true;
+                match result {
+                    Ok(mut outputs) => for id in subplan.outputs.iter() {
+                        let value = outputs.remove(id).unwrap_or_else(|| {
+                            panic!("Partition {} did not return node {}", subplan.index, id)
+                        });
+                        values.insert(*id, value);
+                    },
+                    Err(err) => {
+                        error.get_or_insert(err);
+                    }
+                }
+            }
+            // Newly available values may make more partitions ready
+            if !completed {
+                // Spurious wakeups only cause another round of polling
+                thread::park();
+            }
+        }
+        if let Some(err) = error {
+            return Err(err);
+        }
+        assert!(pending.is_empty(), "Partitions depend on each other cyclically");
+        Ok(values.remove(&self.output).unwrap())
+    }
+}
+
+// Wakes the thread blocked in execute_remote
+struct ThreadWaker(Thread);
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{ComputationGraph, ComputeGraphKey, ExecutionState, NodeHandle, RunOptions};
+
+use slotmap::SecondaryMap;
+use log::info;
+
+/// How long the value of a node is kept once computed, set with
+/// [`ComputationGraph::set_retention`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RetentionPolicy {
+    /// Drop the value as soon as its last consumer has been evaluated.
+    #[default]
+    DropEagerly,
+    /// Keep the value until the end of the run, so that it can be inspected
+    /// through [`RetainedValues`].
+    RetainUntilEnd,
+    /// Keep the value across the runs of an
+    /// [`ExecutionState`], which reuse it instead of evaluating the node
+    /// and its ancestors again while the version of the node is unchanged.
+    RetainForever
+}
+
+/// The values of retained nodes at the end of a run, produced by
+/// [`ComputationGraph::run`] and [`ComputationGraph::compute_retaining`].
+#[derive(Debug, Clone)]
+pub struct RetainedValues<T> {
+    values: SecondaryMap<ComputeGraphKey, T>,
+    graph_id: usize
+}
+impl<T> RetainedValues<T> {
+    pub(crate) fn new(values: SecondaryMap<ComputeGraphKey, T>, graph_id: usize)
+            -> RetainedValues<T> {
+        RetainedValues { values, graph_id }
+    }
+    /// Returns the value of the given node, if it was retained.
+    pub fn get(&self, node: &NodeHandle) -> Option<&T> {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.values.get(node.node_key)
+    }
+    /// Returns the number of retained values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    /// Returns whether no values were retained.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets how long the value of a node is kept once computed.
+    /// 
+    /// Changing the policy of a node discards any value kept for it by
+    /// [`RetentionPolicy::RetainForever`].
+    pub fn set_retention(&mut self, node: &NodeHandle, policy: RetentionPolicy) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let graph_node = self.node_storage.get_mut(node.node_key).unwrap();
+        assert!(!graph_node.is_multi_func(),
+            "Multi-output nodes must be used through their output handles");
+        graph_node.retention = policy;
+        self.state.discard(node.node_key);
+    }
+    /// Returns how long the value of a node is kept once computed.
+    pub fn retention(&self, node: &NodeHandle) -> RetentionPolicy {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.node_storage.get(node.node_key).unwrap().retention
+    }
+    /// Discards the values kept across runs for nodes with
+    /// [`RetentionPolicy::RetainForever`], so that they are evaluated again.
+    pub fn clear_retained(&mut self) {
+        self.state.clear_retained();
+    }
+}
+
+impl<T: Clone> ComputationGraph<T> {
+    /// Computes the value of the output node, returning it along with the
+    /// values of every node whose [`RetentionPolicy`] is not
+    /// [`DropEagerly`](RetentionPolicy::DropEagerly).
+    /// 
+    /// This is a [`run`](Self::run) in the graph's own [`ExecutionState`],
+    /// so values of nodes with
+    /// [`RetainForever`](RetentionPolicy::RetainForever) are reused by later
+    /// calls, skipping any ancestors not needed otherwise, until the node,
+    /// one of its ancestors or their inputs change. Runs with other options
+    /// keep their values in a state passed to [`RunOptions::in_state`].
+    pub fn compute_retaining(&mut self) -> (T, RetainedValues<T>) {
+        info!("Evaluating DAG with retained values");
+        let mut state = std::mem::replace(&mut self.state, ExecutionState::new(self.graph_id));
+        let output = self.run(RunOptions::new().in_state(&mut state));
+        self.state = state;
+        (output.value, output.retained)
+    }
+}
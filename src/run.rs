@@ -0,0 +1,204 @@
+use crate::{ComputationGraph, ComputeGraphKey, ExecutionReport, ExecutionState, NodeHandle,
+    NodeTiming, RetainedValues, SizeHint, TagFilter};
+
+use std::collections::VecDeque;
+
+use slotmap::Key as KeyTrait;
+use log::{info, debug};
+
+type SizeFn<T> = fn(&T) -> usize;
+
+/// The options of a single run of a graph with [`ComputationGraph::run`].
+///
+/// Every option can be combined with the others, and none of them modify
+/// the graph, so the same graph can be run with different options in turn.
+pub struct RunOptions<'a, T> {
+    inputs: Vec<(&'a NodeHandle, T)>,
+    stubs: Vec<(&'a NodeHandle, T)>,
+    tag_filter: Option<TagFilter>,
+    state: Option<&'a mut ExecutionState<T>>,
+    // Outer option for whether to profile, inner one for measuring sizes
+    profile: Option<Option<SizeFn<T>>>
+}
+impl<T> Default for RunOptions<'_, T> {
+    fn default() -> Self {
+        RunOptions {
+            inputs: Vec::new(),
+            stubs: Vec::new(),
+            tag_filter: None,
+            state: None,
+            profile: None
+        }
+    }
+}
+impl<T> std::fmt::Debug for RunOptions<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RunOptions {{ inputs: {}, stubs: {}, tag_filter: {:?}, state: {}, \
+            profile: {} }}", self.inputs.len(), self.stubs.len(), self.tag_filter,
+            self.state.is_some(), self.profile.is_some())
+    }
+}
+impl<'a, T> RunOptions<'a, T> {
+    /// Creates options for a plain run.
+    pub fn new() -> RunOptions<'a, T> {
+        RunOptions::default()
+    }
+    /// Feeds a value to a placeholder.
+    pub fn input(mut self, placeholder: &'a NodeHandle, value: T) -> RunOptions<'a, T> {
+        self.inputs.push((placeholder, value));
+        self
+    }
+    /// Replaces a node by a canned value, like
+    /// [`compute_stubbed`](ComputationGraph::compute_stubbed).
+    pub fn stub(mut self, node: &'a NodeHandle, value: T) -> RunOptions<'a, T> {
+        self.stubs.push((node, value));
+        self
+    }
+    /// Evaluates only the nodes whose tags are accepted by `filter`, like
+    /// [`compute_with_filter`](ComputationGraph::compute_with_filter).
+    pub fn filter(mut self, filter: impl Fn(&[String]) -> bool + Send + Sync + 'static)
+            -> RunOptions<'a, T> {
+        self.tag_filter = Some(TagFilter::new(filter));
+        self
+    }
+    /// Evaluates the run as the next run of `state`, reusing and updating
+    /// the values it keeps for nodes with
+    /// [`RetentionPolicy::RetainForever`](crate::RetentionPolicy::RetainForever).
+    pub fn in_state(mut self, state: &'a mut ExecutionState<T>) -> RunOptions<'a, T> {
+        self.state = Some(state);
+        self
+    }
+    /// Measures how long each node takes to evaluate, like
+    /// [`compute_profiled`](ComputationGraph::compute_profiled).
+    pub fn profiled(mut self) -> RunOptions<'a, T> {
+        self.profile = Some(None);
+        self
+    }
+}
+impl<'a, T: SizeHint> RunOptions<'a, T> {
+    /// Measures how long each node takes to evaluate and the size of its
+    /// value, like
+    /// [`compute_profiled_sized`](ComputationGraph::compute_profiled_sized).
+    pub fn profiled_sized(mut self) -> RunOptions<'a, T> {
+        self.profile = Some(Some(T::approx_bytes));
+        self
+    }
+}
+
+/// The results of a run with [`ComputationGraph::run`].
+#[derive(Debug, Clone)]
+pub struct RunOutput<T> {
+    /// The value of the output node.
+    pub value: T,
+    /// The values of every node whose
+    /// [`RetentionPolicy`](crate::RetentionPolicy) is not
+    /// [`DropEagerly`](crate::RetentionPolicy::DropEagerly).
+    pub retained: RetainedValues<T>,
+    /// The timings of the run, if it was profiled.
+    pub report: Option<ExecutionReport>
+}
+
+impl<T: Clone> ComputationGraph<T> {
+    /// Computes the value of the output node with the given options,
+    /// without consuming or modifying the graph.
+    ///
+    /// Nodes the output does not depend on are left in the graph whatever
+    /// the [`SweepPolicy`](crate::SweepPolicy).
+    pub fn run(&self, options: RunOptions<'_, T>) -> RunOutput<T> {
+        let (value, retained, report) = self.execute_run(options, None, Some(T::clone));
+        RunOutput {
+            value,
+            retained: retained.unwrap(),
+            report
+        }
+    }
+    /// Creates a state for a sequence of runs of the graph with
+    /// [`RunOptions::in_state`].
+    pub fn new_state(&self) -> ExecutionState<T> {
+        ExecutionState::new(self.graph_id)
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Evaluates a run with the given options, returning the value of the
+    /// output node along with the retained values if `clone_value` is given,
+    /// and the timings of the run if it was profiled.
+    ///
+    /// `planned_order` replaces the evaluation order of the output node, and
+    /// is used by frozen graphs, which plan their order once.
+    pub(crate) fn execute_run(&self, options: RunOptions<'_, T>,
+            planned_order: Option<&VecDeque<ComputeGraphKey>>,
+            clone_value: Option<fn(&T) -> T>)
+            -> (T, Option<RetainedValues<T>>, Option<ExecutionReport>) {
+        let start = self.now();
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG");
+        let RunOptions { inputs, stubs, tag_filter, mut state, profile } = options;
+        let mut values = self.placeholder_values(inputs, |value| value);
+        let mut stub_values = self.stub_values(stubs);
+        let order = match planned_order {
+            Some(order) => order.clone(),
+            None => self.filtered_evaluation_order(out_key, tag_filter.as_ref())
+        };
+        let run_index = state.as_mut().map_or(0, |state| {
+            assert_eq!(state.graph_id, self.graph_id,
+                "Received ExecutionState for different graph");
+            state.begin_run()
+        });
+        let versions = state.as_ref().map(|_| self.retention_versions(&order));
+        let kept = |key: ComputeGraphKey| state.as_deref().zip(versions.as_ref())
+            .and_then(|(state, versions)| state.retained_value(key, versions));
+
+        // Stubs and values kept by the state are used instead of evaluating
+        // those nodes and the ancestors only they need
+        let (order, known) = self.skip_known(order, out_key, |key| {
+            stub_values.contains_key(key)
+                || (!values.contains_key(key) && kept(key).is_some())
+        });
+        for node_key in known {
+            let value = match stub_values.remove(node_key) {
+                Some(value) => value,
+                None => clone_value.expect("Reusing kept values requires cloning")(
+                    kept(node_key).unwrap())
+            };
+            values.insert(node_key, value);
+        }
+
+        let refcounts = self.order_refcounts(&order, out_key);
+        let mut timings = Vec::new();
+        let mut last_finish = self.now();
+        debug!("Computing node values");
+        let mut values = self.execute_order_observed(&order, Some(refcounts), values,
+            &self.run_context(run_index), &mut |node_key, value| {
+                let Some(size_of) = profile else {
+                    return;
+                };
+                let now = self.now();
+                timings.push(NodeTiming {
+                    node: node_key.data().as_ffi(),
+                    node_name: self.node_storage.get(node_key).unwrap().name.to_string(),
+                    duration: now - last_finish,
+                    output_bytes: size_of.zip(value).map(|(size_of, value)| size_of(value))
+                });
+                // Time spent recording is not attributed to the next node
+                last_finish = self.now();
+            });
+
+        if let (Some(state), Some(versions)) = (state.as_deref_mut(), versions.as_ref()) {
+            let clone_value = clone_value.expect("Keeping values requires cloning");
+            self.keep_retained(&values, state, versions, clone_value);
+        }
+        let retained = clone_value.map(|clone_value| {
+            self.retained_values(&values, |key| {
+                state.as_deref().zip(versions.as_ref())
+                    .and_then(|(state, versions)| state.retained_value(key, versions))
+            }, clone_value)
+        });
+        let value = values.remove(out_key).unwrap();
+        let report = profile.map(|_| ExecutionReport {
+            timings,
+            total: self.now() - start
+        });
+        (value, retained, report)
+    }
+}
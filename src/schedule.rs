@@ -0,0 +1,203 @@
+use crate::{ComputationGraph, ComputeGraphKey, TagFilter};
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use slotmap::SecondaryMap;
+use log::debug;
+
+/// The strategy used to order node evaluations, set with
+/// [`ComputationGraph::set_scheduling_strategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchedulingStrategy {
+    /// Evaluate nodes in depth-first order from the output.
+    #[default]
+    DepthFirst,
+    /// Evaluate the consumers of a value back-to-back where possible, so
+    /// the value stays hot in cache and can be released sooner. Once a
+    /// node is evaluated, every consumer it readies is evaluated before the
+    /// nodes those consumers ready in turn. This helps when several
+    /// consumers share a large intermediate value.
+    Locality,
+    /// Evaluate a node as soon as its last input is produced, descending
+    /// into the consumers of the most recently evaluated node first. Unlike
+    /// [`Locality`](Self::Locality), a consumer's own consumers are
+    /// evaluated before its siblings, which keeps chains together at the
+    /// cost of keeping a shared value alive across them. Both strategies
+    /// take time linear in the number of edges to plan.
+    ConsumerFirst,
+    /// Evaluate nodes in depth-first order, visiting the inputs with the
+    /// most expensive ancestry first according to the timings given to
+    /// [`ComputationGraph::apply_profile`]. Each value then waits only for
+    /// the cheaper inputs evaluated after it, so it is freed sooner.
+    ProfileGuided
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets the strategy used to order node evaluations.
+    pub fn set_scheduling_strategy(&mut self, strategy: SchedulingStrategy) {
+        self.scheduling_strategy = strategy;
+    }
+    /// Returns the strategy used to order node evaluations.
+    pub fn scheduling_strategy(&self) -> SchedulingStrategy {
+        self.scheduling_strategy
+    }
+    /// Returns `root` and its ancestors, along with the enabled sinks and
+    /// their ancestors if `root` is the output node, in the evaluation order
+    /// given by the scheduling strategy.
+    pub(crate) fn evaluation_order(&self, root: ComputeGraphKey) -> VecDeque<ComputeGraphKey> {
+        self.filtered_evaluation_order(root, None)
+    }
+    /// Returns the evaluation order of `root` like
+    /// [`evaluation_order`](Self::evaluation_order), leaving out the sinks
+    /// rejected by `filter`.
+    pub(crate) fn filtered_evaluation_order(&self, root: ComputeGraphKey,
+            filter: Option<&TagFilter>) -> VecDeque<ComputeGraphKey> {
+        let mut roots = self.requested_roots(root);
+        roots.retain(|key| *key == root || !self.is_disabled(*key, filter));
+        let order = self.toposort_from(&roots);
+        self.assert_enabled(&order, filter);
+        match self.scheduling_strategy {
+            SchedulingStrategy::DepthFirst => order,
+            SchedulingStrategy::Locality => self.locality_order(order),
+            SchedulingStrategy::ConsumerFirst => self.consumer_first_order(order),
+            SchedulingStrategy::ProfileGuided => self.profile_guided_order(order)
+        }
+    }
+    /// Reorders a valid evaluation order so that the consumers a node
+    /// readies are evaluated back-to-back, before any of their own
+    /// consumers.
+    fn locality_order(&self, order: VecDeque<ComputeGraphKey>) -> VecDeque<ComputeGraphKey> {
+        debug!("Reordering nodes for locality");
+        let (consumers, mut missing_inputs) = self.consumer_lists(&order);
+        // Groups of nodes readied together, next to evaluate last, starting
+        // with each node without inputs in the original order
+        let mut groups: Vec<VecDeque<ComputeGraphKey>> = order.iter().rev()
+            .filter(|key| *missing_inputs.get(**key).unwrap() == 0)
+            .map(|key| VecDeque::from([*key]))
+            .collect();
+
+        let mut new_order = VecDeque::with_capacity(order.len());
+        while let Some(mut group) = groups.pop() {
+            // The consumers readied by each node of the group, in order
+            let mut readied = Vec::new();
+            while let Some(node_key) = group.pop_front() {
+                new_order.push_back(node_key);
+                let mut ready = VecDeque::new();
+                for consumer in consumers.get(node_key).unwrap().iter() {
+                    let missing = missing_inputs.get_mut(*consumer).unwrap();
+                    *missing -= 1;
+                    if *missing == 0 {
+                        ready.push_back(*consumer);
+                    }
+                }
+                if !ready.is_empty() {
+                    readied.push(ready);
+                }
+            }
+            groups.extend(readied.into_iter().rev());
+        }
+        debug_assert_eq!(new_order.len(), order.len());
+        new_order
+    }
+    /// Reorders a valid evaluation order so that nodes are evaluated as soon
+    /// as they become ready, most recently readied first.
+    fn consumer_first_order(&self, order: VecDeque<ComputeGraphKey>)
+            -> VecDeque<ComputeGraphKey> {
+        debug!("Reordering nodes to evaluate consumers first");
+        let (consumers, mut missing_inputs) = self.consumer_lists(&order);
+        // Ready nodes, next to evaluate last, starting in the original order
+        let mut ready: Vec<ComputeGraphKey> = order.iter().rev()
+            .filter(|key| *missing_inputs.get(**key).unwrap() == 0)
+            .copied()
+            .collect();
+
+        let mut new_order = VecDeque::with_capacity(order.len());
+        while let Some(node_key) = ready.pop() {
+            new_order.push_back(node_key);
+            for consumer in consumers.get(node_key).unwrap().iter().rev() {
+                let missing = missing_inputs.get_mut(*consumer).unwrap();
+                *missing -= 1;
+                if *missing == 0 {
+                    ready.push(*consumer);
+                }
+            }
+        }
+        debug_assert_eq!(new_order.len(), order.len());
+        new_order
+    }
+    /// Returns the consumers of each node of `order` in that order, along
+    /// with the number of inputs of each node.
+    fn consumer_lists(&self, order: &VecDeque<ComputeGraphKey>)
+            -> (SecondaryMap<ComputeGraphKey, Vec<ComputeGraphKey>>,
+                SecondaryMap<ComputeGraphKey, usize>) {
+        let mut consumers: SecondaryMap<ComputeGraphKey, Vec<ComputeGraphKey>> =
+            SecondaryMap::new();
+        let mut missing_inputs: SecondaryMap<ComputeGraphKey, usize> = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            consumers.insert(node_key, Vec::new());
+        }
+        for node_key in order.iter().copied() {
+            let input_nodes = &self.node_storage.get(node_key).unwrap().input_nodes;
+            missing_inputs.insert(node_key, input_nodes.len());
+            for input_key in input_nodes.iter() {
+                consumers.get_mut(*input_key).unwrap().push(node_key);
+            }
+        }
+        (consumers, missing_inputs)
+    }
+    /// Reorders a valid evaluation order depth-first, visiting the inputs of
+    /// each node in decreasing order of the measured cost of their ancestry.
+    fn profile_guided_order(&self, order: VecDeque<ComputeGraphKey>)
+            -> VecDeque<ComputeGraphKey> {
+        debug!("Reordering nodes by measured cost");
+        // Shared ancestors are counted once per path, which is good enough
+        // for ranking inputs against each other
+        let mut cone_costs: SecondaryMap<ComputeGraphKey, Duration> = SecondaryMap::new();
+        let mut is_input: SecondaryMap<ComputeGraphKey, ()> = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            let own_cost = self.node_costs.get(node_key).copied().unwrap_or_default();
+            let cone_cost = self.node_storage.get(node_key).unwrap().input_nodes.iter()
+                .map(|input_key| *cone_costs.get(*input_key).unwrap())
+                .fold(own_cost, Duration::saturating_add);
+            cone_costs.insert(node_key, cone_cost);
+            for input_key in self.node_storage.get(node_key).unwrap().input_nodes.iter() {
+                is_input.insert(*input_key, ());
+            }
+        }
+
+        let mut visited: SecondaryMap<ComputeGraphKey, ()> = SecondaryMap::new();
+        let mut new_order = VecDeque::with_capacity(order.len());
+        // Nodes without consumers are the output, the sinks and the delay sources
+        for root in order.iter().copied().filter(|key| !is_input.contains_key(*key)) {
+            // Each entry holds a node and its unvisited inputs, next to visit last
+            let mut dfs_stack = vec![(root, self.inputs_by_cost(root, &cone_costs))];
+            visited.insert(root, ());
+            while let Some((node_key, inputs)) = dfs_stack.last_mut() {
+                match inputs.pop() {
+                    Some(input_key) => {
+                        if visited.insert(input_key, ()).is_none() {
+                            let input_inputs = self.inputs_by_cost(input_key, &cone_costs);
+                            dfs_stack.push((input_key, input_inputs));
+                        }
+                    },
+                    None => {
+                        new_order.push_back(*node_key);
+                        dfs_stack.pop();
+                    }
+                }
+            }
+        }
+        debug_assert_eq!(new_order.len(), order.len());
+        new_order
+    }
+    /// Returns the inputs of a node sorted by increasing cone cost.
+    fn inputs_by_cost(&self, node_key: ComputeGraphKey,
+            cone_costs: &SecondaryMap<ComputeGraphKey, Duration>) -> Vec<ComputeGraphKey> {
+        let mut inputs = self.node_storage.get(node_key).unwrap().input_nodes.to_vec();
+        // Stable sort keeps ties in their original order once reversed
+        inputs.reverse();
+        inputs.sort_by_key(|input_key| *cone_costs.get(*input_key).unwrap());
+        inputs
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{ComputationGraph, NodeContext};
+use crate::batch::derive_seed;
+use crate::hash::StableHasher;
+
+/// A small, fast RNG for stochastic nodes, returned by
+/// [`NodeContext::rng`].
+///
+/// This is SplitMix64, which is not cryptographically secure. Nodes that
+/// need other distributions or generators can seed them from
+/// [`NodeContext::node_seed`] instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRng(u64);
+impl NodeRng {
+    /// Creates an RNG from a seed.
+    pub fn new(seed: u64) -> NodeRng {
+        NodeRng(seed)
+    }
+    /// Returns the next random 64-bit integer.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    /// Returns a random float uniformly distributed in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        // The top 53 bits fill the mantissa exactly
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Derives the seed of a node from the seed of the run and the node name.
+pub(crate) fn node_seed(run_seed: u64, name: &str) -> u64 {
+    let mut hasher = StableHasher::new();
+    hasher.write_str(name);
+    derive_seed(run_seed, hasher.finish())
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets the seed from which the [`NodeContext`] of every later
+    /// evaluation derives its seeds, making nodes that draw their randomness
+    /// from the context reproducible.
+    /// 
+    /// Each run of a repeated evaluation, such as
+    /// [`compute_stream`](Self::compute_stream), gets a different seed
+    /// derived from this one and its run index.
+    /// [`compute_monte_carlo`](Self::compute_monte_carlo) derives its seeds
+    /// from its own base seed instead.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+    /// Removes the seed set with [`set_seed`](Self::set_seed), so that runs
+    /// have a seed of 0.
+    pub fn clear_seed(&mut self) {
+        self.seed = None;
+    }
+    /// Returns the seed set with [`set_seed`](Self::set_seed), if any.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+    /// Returns the context of the run with the given index.
+    pub(crate) fn run_context(&self, run_index: usize) -> NodeContext {
+        let seed = self.seed.map_or(0, |seed| derive_seed(seed, run_index as u64));
+        NodeContext::new(run_index, seed, self.clock.clone())
+    }
+}
@@ -0,0 +1,114 @@
+/// Approximates the memory occupied by values, used to report the size of
+/// node outputs with
+/// [`compute_profiled_sized`](crate::ComputationGraph::compute_profiled_sized).
+///
+/// With the `size-hint` feature, this is implemented for primitives and
+/// common standard library containers.
+pub trait SizeHint {
+    /// Returns the approximate number of bytes the value occupies, including
+    /// the heap allocations it owns.
+    fn approx_bytes(&self) -> usize;
+}
+
+#[cfg(feature = "size-hint")]
+mod std_impls {
+    use super::SizeHint;
+
+    use std::collections::{HashMap, VecDeque};
+    use std::mem::size_of;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    macro_rules! impl_inline_size {
+        ($($ty:ty),*) => {
+            $(impl SizeHint for $ty {
+                fn approx_bytes(&self) -> usize {
+                    size_of::<$ty>()
+                }
+            })*
+        };
+    }
+    impl_inline_size!(bool, char, i8, i16, i32, i64, i128, isize,
+        u8, u16, u32, u64, u128, usize, f32, f64, ());
+
+    // Size of a container holding `items`, with room for `capacity` items
+    fn container_bytes<'a, C, T: SizeHint + 'a>(items: impl Iterator<Item = &'a T>,
+            len: usize, capacity: usize) -> usize {
+        size_of::<C>() + items.map(T::approx_bytes).sum::<usize>()
+            + capacity.saturating_sub(len) * size_of::<T>()
+    }
+
+    impl SizeHint for String {
+        fn approx_bytes(&self) -> usize {
+            size_of::<String>() + self.capacity()
+        }
+    }
+    impl SizeHint for str {
+        fn approx_bytes(&self) -> usize {
+            self.len()
+        }
+    }
+    impl<T: SizeHint> SizeHint for [T] {
+        fn approx_bytes(&self) -> usize {
+            self.iter().map(T::approx_bytes).sum()
+        }
+    }
+    impl<T: SizeHint, const N: usize> SizeHint for [T; N] {
+        fn approx_bytes(&self) -> usize {
+            self.iter().map(T::approx_bytes).sum()
+        }
+    }
+    impl<T: SizeHint> SizeHint for Vec<T> {
+        fn approx_bytes(&self) -> usize {
+            container_bytes::<Self, T>(self.iter(), self.len(), self.capacity())
+        }
+    }
+    impl<T: SizeHint> SizeHint for VecDeque<T> {
+        fn approx_bytes(&self) -> usize {
+            container_bytes::<Self, T>(self.iter(), self.len(), self.capacity())
+        }
+    }
+    impl<K: SizeHint, V: SizeHint, S> SizeHint for HashMap<K, V, S> {
+        fn approx_bytes(&self) -> usize {
+            size_of::<Self>()
+                + self.iter().map(|(k, v)| k.approx_bytes() + v.approx_bytes()).sum::<usize>()
+                + self.capacity().saturating_sub(self.len()) * size_of::<(K, V)>()
+        }
+    }
+    impl<T: SizeHint> SizeHint for Option<T> {
+        fn approx_bytes(&self) -> usize {
+            match self {
+                Some(value) => size_of::<Self>() - size_of::<T>() + value.approx_bytes(),
+                None => size_of::<Self>()
+            }
+        }
+    }
+    // Shared values are counted in full by every owner
+    impl<T: SizeHint + ?Sized> SizeHint for Box<T> {
+        fn approx_bytes(&self) -> usize {
+            size_of::<Self>() + T::approx_bytes(self)
+        }
+    }
+    impl<T: SizeHint + ?Sized> SizeHint for Arc<T> {
+        fn approx_bytes(&self) -> usize {
+            size_of::<Self>() + T::approx_bytes(self)
+        }
+    }
+    impl<T: SizeHint + ?Sized> SizeHint for Rc<T> {
+        fn approx_bytes(&self) -> usize {
+            size_of::<Self>() + T::approx_bytes(self)
+        }
+    }
+    impl<A: SizeHint, B: SizeHint> SizeHint for (A, B) {
+        fn approx_bytes(&self) -> usize {
+            size_of::<Self>() - size_of::<A>() - size_of::<B>()
+                + self.0.approx_bytes() + self.1.approx_bytes()
+        }
+    }
+    impl<A: SizeHint, B: SizeHint, C: SizeHint> SizeHint for (A, B, C) {
+        fn approx_bytes(&self) -> usize {
+            size_of::<Self>() - size_of::<A>() - size_of::<B>() - size_of::<C>()
+                + self.0.approx_bytes() + self.1.approx_bytes() + self.2.approx_bytes()
+        }
+    }
+}
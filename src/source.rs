@@ -0,0 +1,89 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle, NodeKind};
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+// Mutex so that the graph stays Sync while sources are pulled through &self
+pub(crate) type BoxedSource<T> = Mutex<Box<dyn Iterator<Item = T> + Send>>;
+
+impl<T> ComputationGraph<T> {
+    /// Inserts a source node pulling its values from `items`, returning an
+    /// opaque node handle.
+    /// 
+    /// Source nodes have no inputs and take on the next item of their
+    /// iterator in each run of [`compute_sources`](Self::compute_sources)
+    /// or [`compute_sources_pipelined`](Self::compute_sources_pipelined).
+    /// Any `IntoIterator`, including a channel [`Receiver`](std::sync::mpsc::Receiver),
+    /// can be used as a source.
+    pub fn insert_source<I>(&mut self, name: impl Into<Arc<str>>, items: I) -> NodeHandle
+            where I: IntoIterator<Item = T>, I::IntoIter: Send + 'static {
+        let source: Box<dyn Iterator<Item = T> + Send> = Box::new(items.into_iter());
+        self.insert_node_kind(name, NodeKind::Source(Mutex::new(source)))
+    }
+    /// Evaluates the graph once per item of its source nodes, returning the
+    /// output values in order.
+    /// 
+    /// Evaluation stops as soon as any source node the output depends on is
+    /// exhausted, so the number of runs is that of the shortest source.
+    /// Items already pulled from other sources in the last attempted run are
+    /// discarded. The graph is not consumed, but its sources are, so a later
+    /// call continues where this one stopped.
+    pub fn compute_sources(&self) -> Vec<T> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG over sources");
+        let order = self.evaluation_order(out_key);
+        let refcounts = self.order_refcounts(&order, out_key);
+        let sources = self.source_keys(&order);
+        let mut outputs = Vec::new();
+        while let Some(inputs) = self.pull_sources(&sources) {
+            debug!("Evaluating run {}", outputs.len());
+            let mut values = self.execute_order(&order, Some(refcounts.clone()), inputs,
+                &self.run_context(outputs.len()));
+            outputs.push(values.remove(out_key).unwrap());
+        }
+        debug!("Sources exhausted after {} runs", outputs.len());
+        outputs
+    }
+    /// Returns the source nodes in `order`, failing if there are none.
+    fn source_keys(&self, order: &VecDeque<ComputeGraphKey>) -> Vec<ComputeGraphKey> {
+        let sources: Vec<_> = order.iter().copied()
+            .filter(|key| matches!(self.node_storage.get(*key).unwrap().kind, NodeKind::Source(_)))
+            .collect();
+        assert!(!sources.is_empty(), "Output does not depend on any source node");
+        sources
+    }
+    /// Pulls the next item of every source in `sources`, returning `None` if
+    /// any of them is exhausted.
+    fn pull_sources(&self, sources: &[ComputeGraphKey])
+            -> Option<SecondaryMap<ComputeGraphKey, T>> {
+        sources.iter().map(|key| {
+            let NodeKind::Source(ref source) = self.node_storage.get(*key).unwrap().kind else {
+                unreachable!()
+            };
+            let item = source.lock().unwrap().next()?;
+            Some((*key, item))
+        }).collect()
+    }
+}
+
+impl<T: Send + Sync> ComputationGraph<T> {
+    /// Evaluates the graph once per item of its source nodes like
+    /// [`compute_sources`](Self::compute_sources), with the evaluation split
+    /// into up to `stages` stages running on separate threads as in
+    /// [`compute_pipelined`](Self::compute_pipelined).
+    /// 
+    /// Items are pulled one run at a time as the first stage is ready for
+    /// them, and the pipeline drains and returns the collected outputs once
+    /// any source is exhausted.
+    pub fn compute_sources_pipelined(&self, stages: usize, capacity: usize) -> Vec<T> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        let order = self.evaluation_order(out_key);
+        info!("Evaluating DAG over sources in up to {} stages", stages);
+        let sources = self.source_keys(&order);
+        let runs = std::iter::from_fn(move || self.pull_sources(&sources));
+        self.run_pipeline(order, out_key, stages, capacity, runs)
+    }
+}
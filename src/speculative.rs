@@ -0,0 +1,316 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeContext, NodeHandle, NodeKind};
+
+use std::collections::VecDeque;
+use std::panic;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+impl<T> ComputationGraph<T> {
+    /// Sets whether both branches of a select node are evaluated while its
+    /// predicate is, when the graph is computed with
+    /// [`compute_speculative`](Self::compute_speculative).
+    pub fn set_speculative(&mut self, select: &NodeHandle, speculative: bool) {
+        assert_eq!(select.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let node = self.node_storage.get_mut(select.node_key).unwrap();
+        assert!(matches!(node.kind, NodeKind::Select(..)),
+            "Node {} is not a select node", node.name);
+        node.speculative = speculative;
+    }
+    /// Returns whether both branches of a select node are evaluated while
+    /// its predicate is.
+    pub fn is_speculative(&self, select: &NodeHandle) -> bool {
+        assert_eq!(select.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        self.node_storage.get(select.node_key).unwrap().speculative
+    }
+}
+
+/// The output nodes of each multi-output node, with the index of the
+/// output each of them selects.
+type MultiOutputs = SecondaryMap<ComputeGraphKey, Vec<(ComputeGraphKey, usize)>>;
+
+/// A branch left running once cancelled, until its current node finishes.
+type DetachedBranch<T> = JoinHandle<Option<SecondaryMap<ComputeGraphKey, T>>>;
+
+impl<T: Send + Sync + 'static> ComputationGraph<T> {
+    /// Computes and returns the value of the output node, evaluating the
+    /// branches of speculative select nodes in parallel with their
+    /// predicates.
+    ///
+    /// For each select node marked with
+    /// [`set_speculative`](Self::set_speculative), the nodes both branches
+    /// need are evaluated first. Each branch then starts on its own thread
+    /// while the predicate is evaluated on the calling thread, and the
+    /// losing branch is cancelled as soon as the predicate is known. A
+    /// cancelled branch stops before its next node, and panics in it are
+    /// ignored. A node it is already evaluating runs to completion in the
+    /// background without holding up the run, so listeners and taps may
+    /// see that node after this returns. The run only waits for cancelled
+    /// branches if a value they are reading is needed once speculation is
+    /// over. Other select and short-circuit nodes are evaluated lazily as
+    /// usual, including those within the branches of a speculative select
+    /// node.
+    ///
+    /// This trades work for latency when the predicate is slow. Values
+    /// computed before the last speculative select node are kept until the
+    /// end of the run.
+    pub fn compute_speculative(self) -> T {
+        self.compute_speculative_inputs(SecondaryMap::new())
+    }
+    /// Computes the value of the output node like
+    /// [`compute_speculative`](Self::compute_speculative), feeding the given
+    /// values to placeholders.
+    pub fn compute_speculative_with<'a, In: Into<T>>(self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, In)>) -> T {
+        let placeholder_values = self.placeholder_values(inputs, Into::into);
+        self.compute_speculative_inputs(placeholder_values)
+    }
+    fn compute_speculative_inputs(mut self, inputs: SecondaryMap<ComputeGraphKey, T>) -> T {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG with speculative branches");
+        let full_order = self.computation_order();
+        // Cancelled branches may outlive the run, so they share the graph
+        let graph = Arc::new(self);
+        let multi_outputs = Arc::new(graph.multi_outputs());
+        let mut values: SecondaryMap<ComputeGraphKey, Arc<T>> = inputs.into_iter()
+            .map(|(key, value)| (key, Arc::new(value)))
+            .collect();
+        let mut detached: Vec<DetachedBranch<T>> = Vec::new();
+        loop {
+            let (order, known) = graph.skip_known(full_order.clone(), out_key,
+                |key| values.contains_key(key));
+            let speculative_select = order.iter().copied()
+                .find(|key| graph.node_storage.get(*key).unwrap().speculative);
+            let Some(select_key) = speculative_select else {
+                let mut inputs = SecondaryMap::new();
+                for node_key in known {
+                    let value = values.remove(node_key).unwrap();
+                    let value = Arc::try_unwrap(value).unwrap_or_else(|value| {
+                        debug!("Waiting for cancelled branches to release a value");
+                        for branch in detached.drain(..) {
+                            let _ = branch.join();
+                        }
+                        Arc::into_inner(value).unwrap()
+                    });
+                    inputs.insert(node_key, value);
+                }
+                let refcounts = graph.order_refcounts(&order, out_key);
+                let mut values = graph.execute_order(&order, Some(refcounts), inputs,
+                    &graph.run_context(0));
+                return values.remove(out_key).unwrap();
+            };
+            detached.push(Self::speculate(&graph, select_key, &order, &mut values,
+                &multi_outputs));
+        }
+    }
+    /// Evaluates a speculative select node along with the nodes in `order`
+    /// it needs, adding their values to `values`, and returns the cancelled
+    /// branch.
+    fn speculate(graph: &Arc<Self>, select_key: ComputeGraphKey,
+            order: &VecDeque<ComputeGraphKey>, values: &mut SecondaryMap<ComputeGraphKey, Arc<T>>,
+            multi_outputs: &Arc<MultiOutputs>) -> DetachedBranch<T> {
+        let select = graph.node_storage.get(select_key).unwrap();
+        let NodeKind::Select(is_true, clone_value) = select.kind else {
+            unreachable!("Only select nodes are speculative");
+        };
+        debug!("Speculating on both branches of node {}", select.name);
+        let context = graph.run_context(0);
+        let [pred_key, then_key, else_key] = [0, 1, 2].map(|index| select.input_nodes[index]);
+        let pred_needs = graph.unknown_ancestors(order, pred_key, values);
+        let then_needs = graph.unknown_ancestors(order, then_key, values);
+        let else_needs = graph.unknown_ancestors(order, else_key, values);
+        // Nodes needed by both branches, or by the predicate and a branch,
+        // are evaluated before speculating
+        let (mut common, mut pred_only) = (VecDeque::new(), VecDeque::new());
+        let (mut then_only, mut else_only) = (VecDeque::new(), VecDeque::new());
+        for node_key in order.iter().copied() {
+            let in_then = then_needs.contains_key(node_key);
+            let in_else = else_needs.contains_key(node_key);
+            let in_pred = pred_needs.contains_key(node_key);
+            let group = match (in_pred, in_then, in_else) {
+                (_, true, true) | (true, true, false) | (true, false, true) => &mut common,
+                (true, false, false) => &mut pred_only,
+                (false, true, false) => &mut then_only,
+                (false, false, true) => &mut else_only,
+                (false, false, false) => continue
+            };
+            group.push_back(node_key);
+        }
+        let never = AtomicBool::new(false);
+        let common_values = graph.evaluate_cancellable(&common, values, &context, &never,
+            multi_outputs).unwrap();
+        values.extend(common_values.into_iter().map(|(key, value)| (key, Arc::new(value))));
+
+        // Each branch owns the values it reads, so it can be left running
+        // once cancelled
+        let spawn_branch = |branch: VecDeque<ComputeGraphKey>, cancel: &Arc<AtomicBool>| {
+            let known = graph.branch_inputs(&branch, values);
+            let (graph, multi_outputs, cancel) =
+                (graph.clone(), multi_outputs.clone(), cancel.clone());
+            let context = context.clone();
+            thread::spawn(move || {
+                graph.evaluate_cancellable(&branch, &known, &context, &cancel, &multi_outputs)
+            })
+        };
+        let (then_cancel, else_cancel) =
+            (Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(false)));
+        let then_worker = spawn_branch(then_only, &then_cancel);
+        let else_worker = spawn_branch(else_only, &else_cancel);
+        let pred_values = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            graph.evaluate_cancellable(&pred_only, values, &context, &never, multi_outputs)
+                .unwrap()
+        })).unwrap_or_else(|err| {
+            // Neither branch is needed, so stop both before unwinding
+            then_cancel.store(true, Ordering::Relaxed);
+            else_cancel.store(true, Ordering::Relaxed);
+            panic::resume_unwind(err)
+        });
+        let chosen = is_true(pred_values.get(pred_key)
+            .or_else(|| values.get(pred_key).map(|value| &**value))
+            .unwrap());
+        let (winner, loser, loser_cancel) = if chosen {
+            (then_worker, else_worker, &else_cancel)
+        } else {
+            (else_worker, then_worker, &then_cancel)
+        };
+        loser_cancel.store(true, Ordering::Relaxed);
+        debug!("Node {} chose its {} branch", select.name,
+            if chosen { "true" } else { "false" });
+        // Propagate panics from nodes with their original payload
+        let branch_values = winner.join().unwrap_or_else(|err| panic::resume_unwind(err))
+            .unwrap();
+        values.extend(pred_values.into_iter().chain(branch_values)
+            .map(|(key, value)| (key, Arc::new(value))));
+        let value = clone_value(values.get(if chosen { then_key } else { else_key }).unwrap());
+        graph.publish_value(select_key, &value);
+        values.insert(select_key, Arc::new(value));
+        loser
+    }
+    /// Returns the output nodes of every multi-output node.
+    fn multi_outputs(&self) -> MultiOutputs {
+        let mut multi_outputs = MultiOutputs::new();
+        for (output_key, output_node) in self.node_storage.iter() {
+            if let NodeKind::MultiOutput(index) = output_node.kind {
+                multi_outputs.entry(output_node.input_nodes[0]).unwrap().or_default()
+                    .push((output_key, index));
+            }
+        }
+        multi_outputs
+    }
+    /// Returns the values in `values` that the nodes in `order` take as
+    /// inputs.
+    fn branch_inputs(&self, order: &VecDeque<ComputeGraphKey>,
+            values: &SecondaryMap<ComputeGraphKey, Arc<T>>)
+            -> SecondaryMap<ComputeGraphKey, Arc<T>> {
+        let mut inputs = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            for input_key in self.node_storage.get(node_key).unwrap().input_nodes.iter() {
+                if let Some(value) = values.get(*input_key) {
+                    inputs.insert(*input_key, value.clone());
+                }
+            }
+        }
+        inputs
+    }
+    /// Returns the nodes of `order` without a value in `values` that `root`
+    /// depends on, including `root` itself.
+    fn unknown_ancestors(&self, order: &VecDeque<ComputeGraphKey>, root: ComputeGraphKey,
+            values: &SecondaryMap<ComputeGraphKey, Arc<T>>)
+            -> SecondaryMap<ComputeGraphKey, ()> {
+        let mut needed = SecondaryMap::new();
+        needed.insert(root, ());
+        for node_key in order.iter().rev().copied() {
+            if needed.contains_key(node_key) && !values.contains_key(node_key) {
+                for input_key in self.node_storage.get(node_key).unwrap().input_nodes.iter() {
+                    needed.insert(*input_key, ());
+                }
+            }
+        }
+        needed.retain(|key, _| !values.contains_key(key));
+        needed
+    }
+    /// Evaluates the nodes in `order`, taking the values of other nodes from
+    /// `known`, and returns their values unless `cancel` is set before all
+    /// of them are evaluated.
+    ///
+    /// Select and short-circuit nodes in `order` are evaluated lazily, so
+    /// the nodes that only their unchosen or skipped inputs need are not
+    /// evaluated and have no value. The outputs of multi-output nodes are
+    /// stored under the keys of their output nodes as soon as they are
+    /// computed, so that output nodes evaluated elsewhere can find them.
+    fn evaluate_cancellable(&self, order: &VecDeque<ComputeGraphKey>,
+            known: &SecondaryMap<ComputeGraphKey, Arc<T>>, context: &NodeContext,
+            cancel: &AtomicBool, multi_outputs: &MultiOutputs)
+            -> Option<SecondaryMap<ComputeGraphKey, T>> {
+        let mut own: SecondaryMap<ComputeGraphKey, T> = SecondaryMap::new();
+        let mut branches = self.lazy_branches(order, known);
+        for (position, node_key) in order.iter().copied().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                debug!("Cancelled speculative branch");
+                return None;
+            }
+            let node = self.node_storage.get(node_key).unwrap();
+            if let NodeKind::MultiOutput(_) = node.kind {
+                continue;
+            }
+            if branches.as_ref().is_some_and(|branches| branches.is_dead(node_key)) {
+                continue;
+            }
+            let node_started = self.notify_node_start(node_key);
+            let input_value = |key: ComputeGraphKey| own.get(key)
+                .or_else(|| known.get(key).map(|value| &**value));
+            let outcome = branches.as_ref().and_then(|branches| branches.outcome(node_key));
+            let mut decided = vec![node_key];
+            match node.kind {
+                NodeKind::Select(is_true, clone_value) => {
+                    // The unchosen branch may have been skipped
+                    let chosen = outcome
+                        .unwrap_or_else(|| is_true(input_value(node.input_nodes[0]).unwrap()));
+                    let branch_key = node.input_nodes[if chosen { 1 } else { 2 }];
+                    let value = clone_value(input_value(branch_key).unwrap());
+                    self.publish_value(node_key, &value);
+                    own.insert(node_key, value);
+                },
+                // Once decided, the remaining inputs may have been skipped
+                NodeKind::ShortCircuit(_, _, from_bool) if outcome.is_some() => {
+                    let value = from_bool(outcome.unwrap());
+                    self.publish_value(node_key, &value);
+                    own.insert(node_key, value);
+                },
+                _ => {
+                    let node_inputs: Vec<&T> = node.input_nodes.iter()
+                        .map(|key| input_value(*key).unwrap())
+                        .collect();
+                    if node.is_multi_func() {
+                        let outputs = node.call_multi(&node_inputs);
+                        let mut outputs: Vec<_> = outputs.into_iter().map(Some).collect();
+                        decided.clear();
+                        for (output_key, index) in multi_outputs.get(node_key).into_iter()
+                                .flatten().copied() {
+                            let value = outputs[index].take().unwrap();
+                            self.publish_value(output_key, &value);
+                            own.insert(output_key, value);
+                            decided.push(output_key);
+                        }
+                    } else {
+                        let value = self.evaluate_node(node_key, node, &node_inputs, context);
+                        self.publish_value(node_key, &value);
+                        own.insert(node_key, value);
+                    }
+                }
+            }
+            if let Some(ref mut branches) = branches {
+                for key in decided {
+                    self.resolve_branches(branches, key, &own, position + 1);
+                }
+            }
+            self.notify_node_finish(node_key, node_started, &mut None);
+        }
+        Some(own)
+    }
+}
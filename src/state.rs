@@ -0,0 +1,132 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeKind, RetainedValues, RetentionPolicy};
+use crate::hash::StableHasher;
+
+use std::collections::VecDeque;
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+
+/// The mutable state carried from one evaluation of a graph to the next,
+/// kept apart from the graph so that concurrent and re-entrant evaluations
+/// of one graph never interfere.
+///
+/// A state holds the values kept for nodes with
+/// [`RetentionPolicy::RetainForever`] and the index of the next run, which
+/// seeds the [`NodeContext`](crate::NodeContext) of each run. States are
+/// created with [`ComputationGraph::new_state`] and
+/// [`FrozenGraph::new_state`](crate::FrozenGraph::new_state), while
+/// [`ComputationGraph::compute_retaining`] uses a state owned by the graph.
+/// Values used only within a run, such as the remaining uses of each value,
+/// are never kept in a state.
+///
+/// Each kept value is tagged with the version of the node it was computed
+/// for, derived from the node's version tag and inputs and those of its
+/// ancestors, and is only reused while that version is unchanged. Values
+/// derived from placeholders, sources or delays differ between runs and are
+/// never kept.
+#[derive(Debug, Clone)]
+pub struct ExecutionState<T> {
+    retained: SecondaryMap<ComputeGraphKey, (u64, T)>,
+    runs: usize,
+    pub(crate) graph_id: usize
+}
+impl<T> ExecutionState<T> {
+    pub(crate) fn new(graph_id: usize) -> ExecutionState<T> {
+        ExecutionState {
+            retained: SecondaryMap::new(),
+            runs: 0,
+            graph_id
+        }
+    }
+    /// Returns the number of runs evaluated with this state.
+    pub fn runs(&self) -> usize {
+        self.runs
+    }
+    /// Discards the values kept for nodes with
+    /// [`RetentionPolicy::RetainForever`], so that they are evaluated again.
+    pub fn clear_retained(&mut self) {
+        self.retained.clear();
+    }
+    /// Counts a new run, returning its index.
+    pub(crate) fn begin_run(&mut self) -> usize {
+        self.runs += 1;
+        self.runs - 1
+    }
+    /// Returns the value kept for a node if it was computed for the node's
+    /// version in `versions`.
+    pub(crate) fn retained_value(&self, key: ComputeGraphKey,
+            versions: &SecondaryMap<ComputeGraphKey, u64>) -> Option<&T> {
+        let (kept_version, value) = self.retained.get(key)?;
+        (versions.get(key) == Some(kept_version)).then_some(value)
+    }
+    /// Discards the value kept for a node.
+    pub(crate) fn discard(&mut self, key: ComputeGraphKey) {
+        self.retained.remove(key);
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Returns the version of each node of `order` whose value can be kept
+    /// across runs, hashing its ID and version tag with the versions of its
+    /// inputs.
+    ///
+    /// Placeholders, sources and delays produce a different value in each
+    /// run, so neither they nor the nodes derived from them have a version.
+    pub(crate) fn retention_versions(&self, order: &VecDeque<ComputeGraphKey>)
+            -> SecondaryMap<ComputeGraphKey, u64> {
+        let mut versions = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            if matches!(node.kind, NodeKind::Placeholder | NodeKind::Source(_)
+                    | NodeKind::Delay(_, _)) {
+                continue;
+            }
+            let mut hasher = StableHasher::new();
+            hasher.write_u64(node_key.data().as_ffi());
+            hasher.write_str(node.version_tag.as_deref().unwrap_or_default());
+            let has_inputs = node.input_nodes.iter().all(|input_key| {
+                versions.get(*input_key).map(|version| hasher.write_u64(*version)).is_some()
+            });
+            if has_inputs {
+                versions.insert(node_key, hasher.finish());
+            }
+        }
+        versions
+    }
+    /// Keeps in `state` the values of nodes with
+    /// [`RetentionPolicy::RetainForever`] that have a version, replacing
+    /// values kept for older versions.
+    pub(crate) fn keep_retained(&self, values: &SecondaryMap<ComputeGraphKey, T>,
+            state: &mut ExecutionState<T>, versions: &SecondaryMap<ComputeGraphKey, u64>,
+            clone_value: fn(&T) -> T) {
+        for (node_key, value) in values.iter() {
+            let Some(version) = versions.get(node_key) else {
+                continue;
+            };
+            if self.node_storage.get(node_key).unwrap().retention
+                    == RetentionPolicy::RetainForever
+                    && state.retained_value(node_key, versions).is_none() {
+                state.retained.insert(node_key, (*version, clone_value(value)));
+            }
+        }
+    }
+    /// Collects the values of every node whose [`RetentionPolicy`] is not
+    /// [`DropEagerly`](RetentionPolicy::DropEagerly) from the values of a
+    /// run, falling back to `kept` for those reused from a state.
+    pub(crate) fn retained_values<'a>(&self, values: &'a SecondaryMap<ComputeGraphKey, T>,
+            kept: impl Fn(ComputeGraphKey) -> Option<&'a T>, clone_value: fn(&T) -> T)
+            -> RetainedValues<T> {
+        let mut retained = SecondaryMap::new();
+        for (node_key, node) in self.node_storage.iter() {
+            let value = match node.retention {
+                RetentionPolicy::DropEagerly => None,
+                RetentionPolicy::RetainUntilEnd => values.get(node_key),
+                RetentionPolicy::RetainForever => values.get(node_key)
+                    .or_else(|| kept(node_key))
+            };
+            if let Some(value) = value {
+                retained.insert(node_key, clone_value(value));
+            }
+        }
+        RetainedValues::new(retained, self.graph_id)
+    }
+}
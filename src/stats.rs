@@ -0,0 +1,113 @@
+use crate::{ExecutionListener, ExecutionReport, NodeInfo};
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// Timing statistics of a single node over many runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeStats {
+    /// The ID of the node.
+    pub node: u64,
+    /// The name of the node.
+    pub node_name: String,
+    /// The number of times the node was evaluated.
+    pub count: usize,
+    /// The mean time taken to evaluate the node.
+    pub mean: Duration,
+    /// The 95th percentile of the time taken to evaluate the node.
+    pub p95: Duration,
+    /// The longest time taken to evaluate the node.
+    pub max: Duration
+}
+
+/// Per-node timing statistics aggregated over many runs, produced by
+/// [`StatsCollector::report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StatsReport {
+    /// The statistics of each evaluated node, in the order the nodes were
+    /// first evaluated.
+    pub nodes: Vec<NodeStats>
+}
+impl StatsReport {
+    /// Returns the statistics of the node with the given ID.
+    pub fn stats_of(&self, node: u64) -> Option<&NodeStats> {
+        self.nodes.iter().find(|stats| stats.node == node)
+    }
+}
+
+#[derive(Default)]
+struct StatsState {
+    // Index of each node in `samples`
+    positions: HashMap<u64, usize>,
+    samples: Vec<(u64, String, Vec<Duration>)>
+}
+impl StatsState {
+    fn record(&mut self, node: u64, node_name: &str, duration: Duration) {
+        let samples = &mut self.samples;
+        let position = *self.positions.entry(node).or_insert_with(|| {
+            samples.push((node, node_name.to_owned(), Vec::new()));
+            samples.len() - 1
+        });
+        self.samples[position].2.push(duration);
+    }
+}
+
+/// Aggregates node timings over every run it observes.
+///
+/// Register the collector with
+/// [`add_listener`](crate::ComputationGraph::add_listener) to record every
+/// later evaluation, including those of
+/// [`compute_stream`](crate::ComputationGraph::compute_stream) and
+/// [`compute_pipelined`](crate::ComputationGraph::compute_pipelined), or
+/// add reports of [`compute_profiled`](crate::ComputationGraph::compute_profiled)
+/// with [`add_report`](Self::add_report).
+#[derive(Default)]
+pub struct StatsCollector(Mutex<StatsState>);
+impl StatsCollector {
+    /// Creates a collector without any recorded timings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Records the node timings of a profiled run.
+    pub fn add_report(&self, report: &ExecutionReport) {
+        let mut state = self.0.lock().unwrap();
+        for timing in report.timings.iter() {
+            state.record(timing.node, &timing.node_name, timing.duration);
+        }
+    }
+    /// Discards every recorded timing.
+    pub fn reset(&self) {
+        *self.0.lock().unwrap() = StatsState::default();
+    }
+    /// Returns the statistics of the timings recorded so far.
+    pub fn report(&self) -> StatsReport {
+        let state = self.0.lock().unwrap();
+        let nodes = state.samples.iter().map(|(node, node_name, durations)| {
+            let mut sorted = durations.clone();
+            sorted.sort_unstable();
+            let total: Duration = sorted.iter().sum();
+            // Nearest-rank percentile
+            let p95_rank = (sorted.len() * 95).div_ceil(100);
+            NodeStats {
+                node: *node,
+                node_name: node_name.clone(),
+                count: sorted.len(),
+                mean: total / sorted.len() as u32,
+                p95: sorted[p95_rank - 1],
+                max: *sorted.last().unwrap()
+            }
+        }).collect();
+        StatsReport { nodes }
+    }
+}
+impl ExecutionListener for StatsCollector {
+    fn on_node_finish(&self, node: NodeInfo<'_>, duration: Duration) {
+        self.0.lock().unwrap().record(node.id, node.name, duration);
+    }
+}
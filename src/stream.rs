@@ -0,0 +1,131 @@
+use crate::{ComputationGraph, NodeHandle, NodeKind};
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+/// The result of [`ComputationGraph::compute_until`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedPoint<T> {
+    /// The output value of the last iteration.
+    pub value: T,
+    /// The number of iterations that were evaluated.
+    pub iterations: usize,
+    /// Whether the convergence predicate was satisfied before the iteration
+    /// cap was reached.
+    pub converged: bool
+}
+
+impl<T> ComputationGraph<T> {
+    /// Evaluates the graph once per block pulled from `blocks`, feeding each
+    /// block to the `input` placeholder and yielding the output values.
+    /// 
+    /// The graph is not consumed, and blocks are only pulled as the returned
+    /// iterator is advanced, so long signals can be processed in bounded
+    /// memory. Intermediate values are dropped as soon as each evaluation no
+    /// longer needs them, while nodes inserted with
+    /// [`insert_stateful_node`](Self::insert_stateful_node) keep their state
+    /// from one block to the next.
+    pub fn compute_stream<'a>(&'a self, input: &'a NodeHandle,
+            blocks: impl IntoIterator<Item = T> + 'a) -> impl Iterator<Item = T> + 'a {
+        assert_eq!(input.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        assert!(self.node_storage.get(input.node_key).unwrap().is_placeholder(),
+            "Stream input must be a placeholder");
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG over stream");
+        let order = self.evaluation_order(out_key);
+        let refcounts = self.order_refcounts(&order, out_key);
+        blocks.into_iter().enumerate().map(move |(block_index, block)| {
+            debug!("Evaluating block {}", block_index);
+            let mut inputs = SecondaryMap::new();
+            inputs.insert(input.node_key, block);
+            let mut values = self.execute_order(&order, Some(refcounts.clone()), inputs,
+                &self.run_context(block_index));
+            values.remove(out_key).unwrap()
+        })
+    }
+}
+
+impl<T: Clone> ComputationGraph<T> {
+    /// Evaluates the graph `steps` times, returning the output value of
+    /// each iteration.
+    /// 
+    /// In each iteration, delay nodes inserted with
+    /// [`insert_delay`](Self::insert_delay) take on the value their source
+    /// had in the previous iteration, or their initial value in the first
+    /// iteration. The graph is not consumed.
+    pub fn compute_iterations(&self, steps: usize) -> Vec<T> {
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG for {} iterations", steps);
+        let order = self.evaluation_order(out_key);
+        let mut refcounts = self.order_refcounts(&order, out_key);
+
+        let mut delays = Vec::new();
+        let mut delay_values = SecondaryMap::new();
+        for node_key in order.iter().copied() {
+            let node = self.node_storage.get(node_key).unwrap();
+            if let NodeKind::Delay(ref initial, source) = node.kind {
+                let source = source.unwrap_or_else(|| {
+                    panic!("Delay node {} has no source", node.name)
+                });
+                // Keep source values alive past the end of each iteration
+                *refcounts.get_mut(source).unwrap() += 1;
+                delays.push((node_key, source));
+                delay_values.insert(node_key, initial.clone());
+            }
+        }
+
+        (0..steps).map(|step| {
+            debug!("Evaluating iteration {}", step);
+            let mut values = self.execute_order(&order, Some(refcounts.clone()),
+                std::mem::take(&mut delay_values), &self.run_context(step));
+            for (delay_key, source) in delays.iter() {
+                delay_values.insert(*delay_key, values.get(*source).unwrap().clone());
+            }
+            values.remove(out_key).unwrap()
+        }).collect()
+    }
+    /// Repeatedly evaluates the graph, feeding each output value back into
+    /// the `input` placeholder, until `converged` returns `true` or
+    /// `max_iterations` iterations have been evaluated.
+    /// 
+    /// The first iteration is fed `initial`. `converged` is called with the
+    /// previous and the newly computed value after each iteration. The graph
+    /// is not consumed.
+    pub fn compute_until(&self, input: &NodeHandle, initial: T, max_iterations: usize,
+            mut converged: impl FnMut(&T, &T) -> bool) -> FixedPoint<T> {
+        assert_eq!(input.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        assert!(self.node_storage.get(input.node_key).unwrap().is_placeholder(),
+            "Feedback input must be a placeholder");
+        assert!(max_iterations > 0, "At least one iteration is required");
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG until convergence");
+        let order = self.evaluation_order(out_key);
+        let refcounts = self.order_refcounts(&order, out_key);
+
+        let mut prev = initial;
+        for iteration in 1..=max_iterations {
+            debug!("Evaluating iteration {}", iteration);
+            let mut inputs = SecondaryMap::new();
+            inputs.insert(input.node_key, prev.clone());
+            let mut values = self.execute_order(&order, Some(refcounts.clone()), inputs,
+                &self.run_context(iteration - 1));
+            let next = values.remove(out_key).unwrap();
+            if converged(&prev, &next) {
+                debug!("Converged after {} iterations", iteration);
+                return FixedPoint {
+                    value: next,
+                    iterations: iteration,
+                    converged: true
+                };
+            }
+            prev = next;
+        }
+        FixedPoint {
+            value: prev,
+            iterations: max_iterations,
+            converged: false
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{ComputationGraph, ComputeGraphKey, ExecutionListener, NodeHandle,
+    NodeInfo, NodeTiming};
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use slotmap::SecondaryMap;
+use log::{info, debug};
+
+/// The number of slowest nodes listed in an [`ExecutionSummary`].
+const SLOWEST_NODE_COUNT: usize = 5;
+
+/// An overview of a run, produced by
+/// [`ComputationGraph::compute_summarized`] and meant to be printed to job
+/// logs with `Display`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionSummary {
+    /// The number of nodes evaluated, including placeholders.
+    pub nodes_evaluated: usize,
+    /// The number of nodes removed because the output did not depend on
+    /// them.
+    pub nodes_swept: usize,
+    /// The wall time of the whole run, including planning.
+    pub wall_time: Duration,
+    /// The slowest nodes, at most five, from slowest to fastest.
+    pub slowest: Vec<NodeTiming>,
+    /// The largest number of node values held at once.
+    pub peak_live_values: usize
+}
+impl fmt::Display for ExecutionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Evaluated {} nodes ({} swept) in {:?}",
+            self.nodes_evaluated, self.nodes_swept, self.wall_time)?;
+        write!(f, "Peak live values: {}", self.peak_live_values)?;
+        if !self.slowest.is_empty() {
+            write!(f, "\nSlowest nodes:")?;
+            for timing in self.slowest.iter() {
+                write!(f, "\n  {} ({}): {:?}", timing.node_name, timing.node, timing.duration)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct SummaryState {
+    timings: Vec<NodeTiming>,
+    live_values: usize,
+    peak_live_values: usize
+}
+
+// Collects node timings and live value counts during a run
+#[derive(Default)]
+struct SummaryListener(Mutex<SummaryState>);
+impl ExecutionListener for SummaryListener {
+    fn on_node_finish(&self, node: NodeInfo<'_>, duration: Duration) {
+        let mut state = self.0.lock().unwrap();
+        state.timings.push(NodeTiming {
+            node: node.id,
+            node_name: node.name.to_owned(),
+            duration,
+            output_bytes: None
+        });
+        state.live_values += 1;
+        state.peak_live_values = state.peak_live_values.max(state.live_values);
+    }
+    fn on_value_dropped(&self, _node: NodeInfo<'_>) {
+        let mut state = self.0.lock().unwrap();
+        state.live_values = state.live_values.saturating_sub(1);
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Computes the value of the output node, along with a summary of the
+    /// run.
+    pub fn compute_summarized(self) -> (T, ExecutionSummary) {
+        self.compute_inputs_summarized(SecondaryMap::new())
+    }
+    /// Computes the value of the output node, feeding the given values to
+    /// placeholders, along with a summary of the run.
+    pub fn compute_summarized_with<'a>(self,
+            inputs: impl IntoIterator<Item = (&'a NodeHandle, T)>) -> (T, ExecutionSummary) {
+        let placeholder_values = self.placeholder_values(inputs, |value| value);
+        self.compute_inputs_summarized(placeholder_values)
+    }
+    fn compute_inputs_summarized(mut self, inputs: SecondaryMap<ComputeGraphKey, T>)
+            -> (T, ExecutionSummary) {
+        let start = self.now();
+        let out_key = self.output_node.expect("Output not yet designated");
+        info!("Evaluating DAG with summary");
+        let listener = Arc::new(SummaryListener::default());
+        self.add_listener(listener.clone());
+        let order = self.evaluation_order(out_key);
+        let nodes_swept = self.sweep_unreachable(&order);
+        let refcounts = self.order_refcounts(&order, out_key);
+        debug!("Computing node values");
+        let mut values = self.execute_order(&order, Some(refcounts), inputs,
+            &self.run_context(0));
+        let value = values.remove(out_key).unwrap();
+        let wall_time = self.now() - start;
+
+        let mut state = listener.0.lock().unwrap();
+        let mut slowest = std::mem::take(&mut state.timings);
+        let nodes_evaluated = slowest.len();
+        slowest.sort_by_key(|timing| std::cmp::Reverse(timing.duration));
+        slowest.truncate(SLOWEST_NODE_COUNT);
+        (value, ExecutionSummary {
+            nodes_evaluated,
+            nodes_swept,
+            wall_time,
+            slowest,
+            peak_live_values: state.peak_live_values
+        })
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{ComputationGraph, ComputeGraphKey};
+
+use std::collections::VecDeque;
+
+use slotmap::{Key as KeyTrait, SecondaryMap};
+use log::warn;
+
+/// What happens to nodes the output does not depend on when the graph is
+/// computed, set with [`ComputationGraph::set_sweep_policy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SweepPolicy {
+    /// Silently remove the unreachable nodes.
+    #[default]
+    Delete,
+    /// Remove the unreachable nodes, logging a warning and notifying
+    /// listeners through
+    /// [`ExecutionListener::on_unreachable`](crate::ExecutionListener::on_unreachable)
+    /// for each of them.
+    Warn,
+    /// Panic before evaluating anything if any node is unreachable, as this
+    /// usually means the graph was wired incorrectly.
+    Fail
+}
+
+impl<T> ComputationGraph<T> {
+    /// Sets what happens to nodes the output does not depend on when the
+    /// graph is computed.
+    /// 
+    /// Explicit calls to [`prune`](Self::prune) always remove such nodes
+    /// silently.
+    pub fn set_sweep_policy(&mut self, policy: SweepPolicy) {
+        self.sweep_policy = policy;
+    }
+    /// Returns what happens to nodes the output does not depend on when the
+    /// graph is computed.
+    pub fn sweep_policy(&self) -> SweepPolicy {
+        self.sweep_policy
+    }
+    /// Removes the nodes that neither the output nor a sink depends on
+    /// according to the sweep policy, returning the number of nodes removed.
+    /// 
+    /// `order` is the evaluation order, which contains every node to keep
+    /// unless some sinks are disabled.
+    pub(crate) fn sweep_unreachable(&mut self, order: &VecDeque<ComputeGraphKey>) -> usize {
+        let out_node = self.output_node.expect("Output not yet designated");
+        let full_order;
+        let keep_list = if !self.has_disabled_nodes() {
+            order
+        } else {
+            full_order = self.toposort_from(&self.requested_roots(out_node));
+            &full_order
+        };
+        if self.sweep_policy != SweepPolicy::Delete && keep_list.len() < self.node_storage.len() {
+            let keep_set: SecondaryMap<ComputeGraphKey, ()> = keep_list.iter()
+                .map(|key| (*key, ()))
+                .collect();
+            let unreachable: Vec<_> = self.node_storage.iter()
+                .filter(|(key, _)| !keep_set.contains_key(*key))
+                .collect();
+            if self.sweep_policy == SweepPolicy::Fail {
+                let names: Vec<_> = unreachable.iter().map(|(_, node)| &*node.name).collect();
+                panic!("Nodes not reachable from the output: {}", names.join(", "));
+            }
+            for (key, node) in unreachable {
+                warn!(node = &*node.name, node_id = key.data().as_ffi(),
+                    graph_id = self.graph_id;
+                    "Node {} is not reachable from the output", node.name);
+                self.notify_unreachable(key);
+            }
+        }
+        self.sweep(keep_list).len()
+    }
+}
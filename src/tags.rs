@@ -0,0 +1,70 @@
+use crate::{ComputationGraph, NodeHandle, RunOptions};
+
+use std::fmt;
+
+use log::info;
+
+type BoxedTagFilter = Box<dyn Fn(&[String]) -> bool + Send + Sync>;
+
+// Wrapper allowing the graph to keep deriving Debug
+pub(crate) struct TagFilter(BoxedTagFilter);
+impl TagFilter {
+    pub(crate) fn new(filter: impl Fn(&[String]) -> bool + Send + Sync + 'static) -> TagFilter {
+        TagFilter(Box::new(filter))
+    }
+    /// Returns whether the filter rejects a node with the given tags.
+    pub(crate) fn rejects(&self, tags: &[String]) -> bool {
+        !(self.0)(tags)
+    }
+}
+impl fmt::Debug for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TagFilter(...)")
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Adds a tag to a node, which runs of
+    /// [`compute_with_filter`](Self::compute_with_filter) use to select the
+    /// nodes to evaluate. Adding a tag a node already has does nothing.
+    pub fn add_tag(&mut self, node: &NodeHandle, tag: impl Into<String>) {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let tags = &mut self.node_storage.get_mut(node.node_key).unwrap().tags;
+        let tag = tag.into();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    /// Returns the tags of a node, in the order they were added.
+    pub fn tags(&self, node: &NodeHandle) -> &[String] {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        &self.node_storage.get(node.node_key).unwrap().tags
+    }
+    /// Returns handles to every node with the given tag, in storage order.
+    pub fn nodes_tagged(&self, tag: &str) -> Vec<NodeHandle> {
+        self.node_storage.iter()
+            .filter(|(_, node)| node.tags.iter().any(|node_tag| node_tag == tag))
+            .map(|(node_key, _)| NodeHandle {
+                node_key,
+                graph_id: self.graph_id
+            })
+            .collect()
+    }
+    /// Computes and returns the value of the output node, evaluating only
+    /// the nodes whose tags are accepted by `filter`.
+    ///
+    /// Like nodes in a disabled namespace, sinks rejected by the filter are
+    /// not evaluated, nor are the nodes only they depend on, which allows
+    /// side effects such as uploads to be switched off for a single run.
+    /// Computing an output that depends on a rejected node panics. Untagged
+    /// nodes are passed an empty slice. The graph is not consumed, and runs
+    /// combining a filter with other options are made with
+    /// [`run`](Self::run) and [`RunOptions::filter`].
+    pub fn compute_with_filter(&self,
+            filter: impl Fn(&[String]) -> bool + Send + Sync + 'static) -> T {
+        info!("Evaluating DAG with a tag filter");
+        self.execute_run(RunOptions::new().filter(filter), None, None).0
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{ComputationGraph, ComputeGraphKey, NodeHandle};
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+
+type TapSender<T> = Box<dyn Fn(&T) + Send + Sync>;
+
+// Forwards the values of one node, type-erased so that the graph stays Send
+// for any value type
+pub(crate) struct Tap<T> {
+    node_key: ComputeGraphKey,
+    send: TapSender<T>
+}
+impl<T> fmt::Debug for Tap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Tap {{ node_key: {:?} }}", self.node_key)
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> ComputationGraph<T> {
+    /// Returns a receiver of the values of a node, sent as soon as the node
+    /// is evaluated.
+    /// 
+    /// A value is sent every time the node is evaluated, including each
+    /// iteration of [`compute_iterations`](Self::compute_iterations), but
+    /// not when a previously retained value is reused. Values are cloned to
+    /// be sent, so the node's consumers are not delayed by the receiver.
+    pub fn tap(&mut self, node: &NodeHandle) -> Receiver<Arc<T>> {
+        assert_eq!(node.graph_id, self.graph_id,
+            "Received NodeHandle for different graph");
+        let (sender, receiver) = mpsc::channel();
+        self.taps.push(Tap {
+            node_key: node.node_key,
+            send: Box::new(move |value: &T| {
+                // The receiver may have been dropped, which only stops the values
+                let _ = sender.send(Arc::new(value.clone()));
+            })
+        });
+        receiver
+    }
+}
+
+impl<T> ComputationGraph<T> {
+    /// Stops sending values to the receivers returned by
+    /// [`tap`](Self::tap), closing their channels.
+    pub fn clear_taps(&mut self) {
+        self.taps.clear();
+    }
+    /// Returns whether the values of a node are sent to a receiver.
+    pub(crate) fn is_tapped(&self, key: ComputeGraphKey) -> bool {
+        self.taps.iter().any(|tap| tap.node_key == key)
+    }
+    /// Hands a newly computed value to the dumps and taps selecting its
+    /// node.
+    pub(crate) fn publish_value(&self, key: ComputeGraphKey, value: &T) {
+        self.dump_value(key, value);
+        for tap in self.taps.iter().filter(|tap| tap.node_key == key) {
+            (tap.send)(value);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{Clock, ExecutionListener, ExecutionReport, NodeInfo, SystemClock};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+
+use log::warn;
+
+type StuckCallback = Box<dyn Fn(NodeInfo<'_>, Duration) + Send + Sync>;
+
+// A node being evaluated, as seen by the watchdog thread
+struct RunningNode {
+    id: u64,
+    name: String,
+    thread: ThreadId,
+    started: Instant,
+    reported: bool
+}
+
+struct WatchdogState {
+    running: Vec<RunningNode>,
+    expected: HashMap<u64, Duration>,
+    default_expected: Duration,
+    multiple: f64,
+    check_interval: Duration,
+    callback: Option<Arc<StuckCallback>>,
+    clock: Arc<dyn Clock>,
+    stopped: bool
+}
+
+struct WatchdogShared {
+    state: Mutex<WatchdogState>,
+    wakeup: Condvar
+}
+
+/// An [`ExecutionListener`] that reports nodes running for much longer than
+/// expected, such as nodes stuck on hung IO.
+///
+/// A background thread checks the running nodes periodically, and reports
+/// each node once when it has been running for longer than `multiple` times
+/// its expected duration. By default stuck nodes are logged as warnings.
+/// The thread stops when the watchdog is dropped.
+pub struct Watchdog {
+    shared: Arc<WatchdogShared>,
+    thread: Option<JoinHandle<()>>
+}
+impl Watchdog {
+    /// Creates a watchdog reporting nodes running for longer than `multiple`
+    /// times their expected duration, which is `default_expected` unless set
+    /// otherwise.
+    pub fn new(multiple: f64, default_expected: Duration) -> Watchdog {
+        Self::with_clock(multiple, default_expected, Arc::new(SystemClock))
+    }
+    /// Creates a watchdog like [`new`](Self::new), measuring how long nodes
+    /// have been running with the given clock.
+    /// 
+    /// Checks still happen periodically in real time, so with a clock that
+    /// does not follow real time, such as a
+    /// [`ManualClock`](crate::ManualClock), use [`check`](Self::check) to
+    /// check the running nodes at the right moments.
+    pub fn with_clock(multiple: f64, default_expected: Duration, clock: Arc<dyn Clock>)
+            -> Watchdog {
+        let shared = Arc::new(WatchdogShared {
+            state: Mutex::new(WatchdogState {
+                running: Vec::new(),
+                expected: HashMap::new(),
+                default_expected,
+                multiple,
+                check_interval: Duration::from_millis(100),
+                callback: None,
+                clock,
+                stopped: false
+            }),
+            wakeup: Condvar::new()
+        });
+        let thread_shared = shared.clone();
+        let thread = thread::spawn(move || watch(&thread_shared));
+        Watchdog {
+            shared,
+            thread: Some(thread)
+        }
+    }
+    /// Sets the expected duration of the node with the given ID.
+    pub fn set_expected_duration(&self, node: u64, duration: Duration) {
+        self.shared.state.lock().unwrap().expected.insert(node, duration);
+    }
+    /// Takes the expected duration of every node from the timings of a
+    /// previous run.
+    pub fn use_profile(&self, report: &ExecutionReport) {
+        let mut state = self.shared.state.lock().unwrap();
+        for timing in report.timings.iter() {
+            state.expected.insert(timing.node, timing.duration);
+        }
+    }
+    /// Sets how often the running nodes are checked, which is every 100 ms
+    /// by default.
+    pub fn set_check_interval(&self, interval: Duration) {
+        self.shared.state.lock().unwrap().check_interval = interval;
+        self.shared.wakeup.notify_all();
+    }
+    /// Calls `callback` with each stuck node and how long it has been
+    /// running, instead of logging a warning.
+    pub fn set_callback(&self, callback: impl Fn(NodeInfo<'_>, Duration) + Send + Sync + 'static) {
+        self.shared.state.lock().unwrap().callback = Some(Arc::new(Box::new(callback)));
+    }
+    /// Checks the running nodes immediately instead of waiting for the next
+    /// periodic check.
+    pub fn check(&self) {
+        check_running(&self.shared);
+    }
+}
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().stopped = true;
+        self.shared.wakeup.notify_all();
+        if let Some(thread) = self.thread.take() {
+            // A panicking callback has already been reported by its thread
+            let _ = thread.join();
+        }
+    }
+}
+impl ExecutionListener for Watchdog {
+    fn on_node_start(&self, node: NodeInfo<'_>) {
+        let mut state = self.shared.state.lock().unwrap();
+        let started = state.clock.now();
+        state.running.push(RunningNode {
+            id: node.id,
+            name: node.name.to_owned(),
+            thread: thread::current().id(),
+            started,
+            reported: false
+        });
+    }
+    fn on_node_finish(&self, node: NodeInfo<'_>, _duration: Duration) {
+        let current = thread::current().id();
+        self.shared.state.lock().unwrap().running
+            .retain(|running| running.id != node.id || running.thread != current);
+    }
+}
+
+// Body of the watchdog thread
+fn watch(shared: &WatchdogShared) {
+    let mut state = shared.state.lock().unwrap();
+    while !state.stopped {
+        let interval = state.check_interval;
+        state = shared.wakeup.wait_timeout(state, interval).unwrap().0;
+        if state.stopped {
+            break;
+        }
+        drop(state);
+        check_running(shared);
+        state = shared.state.lock().unwrap();
+    }
+}
+
+// Reports the running nodes that are newly found to be stuck
+fn check_running(shared: &WatchdogShared) {
+    let mut state = shared.state.lock().unwrap();
+    let now = state.clock.now();
+    let mut stuck = Vec::new();
+    let WatchdogState { ref mut running, ref expected, default_expected, multiple, .. } =
+        *state;
+    for node in running.iter_mut().filter(|node| !node.reported) {
+        let expected = expected.get(&node.id).copied().unwrap_or(default_expected);
+        let elapsed = now.saturating_duration_since(node.started);
+        if elapsed.as_secs_f64() > expected.as_secs_f64() * multiple {
+            node.reported = true;
+            stuck.push((node.id, node.name.clone(), elapsed));
+        }
+    }
+    if stuck.is_empty() {
+        return;
+    }
+    // Callbacks run without the lock so that they cannot block the graph
+    let callback = state.callback.clone();
+    drop(state);
+    for (id, name, elapsed) in stuck {
+        let node = NodeInfo { id, name: &name };
+        match callback {
+            Some(ref callback) => callback(node, elapsed),
+            None => warn!(node = name.as_str(), node_id = id,
+                elapsed_us = elapsed.as_micros() as u64;
+                "Node {} has been running for {:?}", name, elapsed)
+        }
+    }
+}
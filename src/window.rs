@@ -0,0 +1,82 @@
+use crate::{ComputationGraph, NodeHandle};
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The items a window node aggregates over, set when inserting the node with
+/// [`ComputationGraph::insert_window_node`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// The last `n` items, including the current one.
+    Sliding(usize),
+    /// The items since the last multiple of `n` items, including the current
+    /// one, so that consecutive windows do not overlap.
+    Tumbling(usize),
+    /// The items received within the given time of the current one.
+    Time(Duration)
+}
+
+// Items of a window along with the time each was received
+struct WindowState<T> {
+    items: VecDeque<T>,
+    received_at: VecDeque<Instant>,
+    received: usize
+}
+
+impl<T: Clone + Send + 'static> ComputationGraph<T> {
+    /// Inserts a stateful node aggregating the values of its single input
+    /// over a window of runs, returning an opaque node handle.
+    /// 
+    /// On every evaluation, the input value is added to the window and items
+    /// that fell out of it are discarded before `func` is called with the
+    /// items of the window, oldest first. The window is kept from one run to
+    /// the next like the state of
+    /// [`insert_stateful_node`](Self::insert_stateful_node), so it is mainly
+    /// useful with [`compute_stream`](Self::compute_stream) or
+    /// [`compute_pipelined`](Self::compute_pipelined), where runs are always
+    /// evaluated in order.
+    pub fn insert_window_node<F>(&mut self, name: impl Into<Arc<str>>, window: Window,
+            func: F) -> NodeHandle
+            where F: Fn(&[T]) -> T + Send + Sync + 'static {
+        assert!(!matches!(window, Window::Sliding(0) | Window::Tumbling(0)),
+            "Windows must hold at least one item");
+        let state = Mutex::new(WindowState {
+            items: VecDeque::new(),
+            received_at: VecDeque::new(),
+            received: 0
+        });
+        // The clock is read through the context so that it follows set_clock
+        self.insert_context_node(name, Box::new(move |context, inputs| {
+            assert_eq!(inputs.len(), 1,
+                "Window node expected 1 input but received {}", inputs.len());
+            let mut state = state.lock().unwrap();
+            let now = context.now();
+            match window {
+                Window::Sliding(size) => {
+                    if state.items.len() == size {
+                        state.items.pop_front();
+                        state.received_at.pop_front();
+                    }
+                },
+                Window::Tumbling(size) => {
+                    if state.received.is_multiple_of(size) {
+                        state.items.clear();
+                        state.received_at.clear();
+                    }
+                },
+                Window::Time(span) => {
+                    while state.received_at.front()
+                            .is_some_and(|received| now.duration_since(*received) > span) {
+                        state.items.pop_front();
+                        state.received_at.pop_front();
+                    }
+                }
+            }
+            state.items.push_back(inputs[0].clone());
+            state.received_at.push_back(now);
+            state.received += 1;
+            func(state.items.make_contiguous())
+        }))
+    }
+}
@@ -0,0 +1,63 @@
+use dag_compute::ComputationGraph;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+// Counts allocations per thread, so tests running in parallel don't interfere
+struct CountingAllocator;
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn count_allocations(func: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    func();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_no_per_node_allocations() {
+    const NODES: usize = 1000;
+    let mut graph = ComputationGraph::<u64>::new();
+    let mut prev = graph.insert_node("node0".to_owned(), Box::new(|_| 0));
+    for i in 1..NODES {
+        let mut next = graph.insert_node(format!("node{}", i),
+            Box::new(|x| x[0] + x[1]));
+        graph.set_inputs(&mut next, &[&prev, &prev]);
+        prev = next;
+    }
+    graph.designate_output(&prev);
+
+    // Iterations after the first only allocate per-run bookkeeping
+    let one_step = count_allocations(|| assert_eq!(graph.compute_iterations(1).len(), 1));
+    let two_steps = count_allocations(|| assert_eq!(graph.compute_iterations(2).len(), 2));
+    assert!(two_steps - one_step < NODES / 10,
+        "Steady-state iteration made {} allocations", two_steps - one_step);
+
+    // Consuming evaluation only allocates bookkeeping for the whole graph
+    let consuming = count_allocations(|| assert_eq!(graph.compute(), 0));
+    assert!(consuming < NODES / 10, "compute made {} allocations", consuming);
+}
+
+#[test]
+fn test_inline_inputs() {
+    let mut graph = ComputationGraph::<u64>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 1));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 2));
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x[0] + x[1]));
+    // Short input lists are stored inline in the node
+    assert_eq!(count_allocations(|| graph.set_inputs(&mut sum, &[&a, &b])), 0);
+    graph.designate_output(&sum);
+    assert_eq!(graph.compute(), 3);
+}
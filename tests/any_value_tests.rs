@@ -0,0 +1,108 @@
+use dag_compute::{AnyValue, ComputationGraph};
+
+use std::num::TryFromIntError;
+
+#[derive(Debug, PartialEq)]
+struct Samples(Vec<f32>);
+#[derive(Debug, PartialEq)]
+struct Histogram(usize);
+
+#[test]
+fn test_compute_as() {
+    let mut graph = ComputationGraph::<AnyValue>::new();
+    let src = graph.insert_node(
+        "samples".to_owned(),
+        Box::new(|_| AnyValue::new(Samples(vec![0.5, 1.5])))
+    );
+    let mut hist = graph.insert_node(
+        "histogram".to_owned(),
+        Box::new(|x| {
+            let samples = x[0].value::<Samples>();
+            AnyValue::new(Histogram(samples.0.len()))
+        })
+    );
+    graph.set_inputs(&mut hist, &[&src]);
+    graph.designate_output(&hist);
+    assert_eq!(graph.compute_as::<Histogram>(), Ok(Histogram(2)));
+}
+
+#[test]
+fn test_compute_as_mismatch() {
+    let mut graph = ComputationGraph::<AnyValue>::new();
+    let src = graph.insert_node(
+        "samples".to_owned(),
+        Box::new(|_| AnyValue::new(Samples(vec![])))
+    );
+    graph.designate_output(&src);
+    let err = graph.compute_as::<Histogram>().unwrap_err();
+    assert_eq!(err.node_name(), "samples");
+    assert!(err.expected().ends_with("Histogram"));
+    assert!(err.actual().ends_with("Samples"));
+}
+
+#[test]
+fn test_wire_with_into() {
+    let mut graph = ComputationGraph::<AnyValue>::new();
+    let src = graph.insert_node(
+        "count".to_owned(),
+        Box::new(|_| AnyValue::new(7_u8))
+    );
+    let widened = graph.wire_with_into::<u8, u64>(&src);
+    let mut double = graph.insert_node(
+        "double".to_owned(),
+        Box::new(|x| AnyValue::new(x[0].value::<u64>() * 2))
+    );
+    graph.set_inputs(&mut double, &[&widened]);
+    graph.designate_output(&double);
+    assert_eq!(graph.compute_as::<u64>(), Ok(14));
+}
+
+#[test]
+fn test_wire_with_try_into() {
+    for (count, expected) in [(300_u16, "out of range"), (7, "7")] {
+        let mut graph = ComputationGraph::<AnyValue>::new();
+        let src = graph.insert_node(
+            "count".to_owned(),
+            Box::new(move |_| AnyValue::new(count))
+        );
+        let narrowed = graph.wire_with_try_into::<u16, u8>(&src);
+        let mut describe = graph.insert_node(
+            "describe".to_owned(),
+            Box::new(|x| AnyValue::new(match x[0].value::<Result<u8, TryFromIntError>>() {
+                Ok(val) => val.to_string(),
+                Err(_) => "out of range".to_owned()
+            }))
+        );
+        graph.set_inputs(&mut describe, &[&narrowed]);
+        graph.designate_output(&describe);
+        assert_eq!(graph.compute_as::<String>().unwrap(), expected);
+    }
+}
+
+#[test]
+#[should_panic(expected = "Expected a value of type u64 but found u8")]
+fn test_value_mismatch() {
+    AnyValue::new(7_u8).value::<u64>();
+}
+
+#[test]
+fn test_value_of() {
+    let mut graph = ComputationGraph::<AnyValue>::new();
+    let src = graph.insert_node(
+        "samples".to_owned(),
+        Box::new(|_| AnyValue::new(Samples(vec![0.5, 1.5])))
+    );
+    let mut hist = graph.insert_node(
+        "histogram".to_owned(),
+        Box::new(|x| AnyValue::new(Histogram(x[0].value::<Samples>().0.len())))
+    );
+    graph.set_inputs(&mut hist, &[&src]);
+    graph.designate_output(&hist);
+    assert_eq!(graph.value_of::<Samples>(&src), Ok(Samples(vec![0.5, 1.5])));
+    let err = graph.value_of::<Samples>(&hist).unwrap_err();
+    assert_eq!(err.node_name(), "histogram");
+    assert_eq!(err.to_string(), format!(
+        "node \"histogram\" produced a value of type {} but {} was expected",
+        std::any::type_name::<Histogram>(), std::any::type_name::<Samples>()));
+    assert_eq!(graph.compute_as::<Histogram>(), Ok(Histogram(2)));
+}
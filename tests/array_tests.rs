@@ -0,0 +1,40 @@
+use dag_compute::ArrayGraphBuilder;
+
+use ndarray::{arr2, ArrayD, IxDyn};
+
+#[test]
+fn test_array_ops() {
+    let mut builder = ArrayGraphBuilder::<f64>::new();
+    let weights = builder.constant("weights".to_owned(),
+        arr2(&[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]]).into_dyn());
+    let input = builder.input("input".to_owned(), &[2, 1]);
+    let product = builder.matmul("product".to_owned(), &weights, &input).unwrap();
+    assert_eq!(builder.shape(&product), &[3, 1]);
+    let squared = builder.mul("squared".to_owned(), &product, &product).unwrap();
+    let halved = builder.map("halved".to_owned(), &squared, |x| x / 2.0).unwrap();
+    let col_sum = builder.sum_axis("col_sum".to_owned(), &halved, 0).unwrap();
+    assert_eq!(builder.shape(&col_sum), &[1]);
+    let total = builder.sum("total".to_owned(), &col_sum).unwrap();
+    assert_eq!(builder.shape(&total), &[] as &[usize]);
+
+    let mut graph = builder.into_graph();
+    graph.designate_output(&total);
+    let input_val = arr2(&[[1.0], [1.0]]).into_dyn();
+    let out = graph.compute_with([(&input, input_val)]);
+    // weights . [1, 1] = [3, 7, 11]
+    assert_eq!(out, ArrayD::from_elem(IxDyn(&[]), (9.0 + 49.0 + 121.0) / 2.0));
+}
+
+#[test]
+fn test_shape_mismatch() {
+    let mut builder = ArrayGraphBuilder::<f32>::new();
+    let a = builder.input("a".to_owned(), &[2, 3]);
+    let b = builder.input("b".to_owned(), &[2, 3]);
+    let err = builder.matmul("bad_matmul".to_owned(), &a, &b).unwrap_err();
+    assert_eq!(err.node_name(), "bad_matmul");
+    assert!(err.to_string().contains("[2, 3]"));
+    let c = builder.input("c".to_owned(), &[3]);
+    assert!(builder.add("bad_add".to_owned(), &a, &c).is_err());
+    assert!(builder.sum_axis("bad_axis".to_owned(), &c, 1).is_err());
+    assert!(builder.add("good_add".to_owned(), &a, &b).is_ok());
+}
@@ -0,0 +1,127 @@
+use dag_compute::{ComputationGraph, DiffOp};
+
+struct Constant(f64);
+impl DiffOp<f64> for Constant {
+    fn eval(&self, _: &[&f64]) -> f64 {
+        self.0
+    }
+    fn vjp(&self, _: &[&f64], _: &f64, _: &f64) -> Vec<f64> {
+        Vec::new()
+    }
+    fn jvp(&self, _: &[&f64], _: &f64, _: &[&f64]) -> f64 {
+        0.0
+    }
+}
+
+struct Mul;
+impl DiffOp<f64> for Mul {
+    fn eval(&self, inputs: &[&f64]) -> f64 {
+        inputs[0] * inputs[1]
+    }
+    fn vjp(&self, inputs: &[&f64], _: &f64, cotangent: &f64) -> Vec<f64> {
+        vec![cotangent * inputs[1], cotangent * inputs[0]]
+    }
+    fn jvp(&self, inputs: &[&f64], _: &f64, tangents: &[&f64]) -> f64 {
+        tangents[0] * inputs[1] + inputs[0] * tangents[1]
+    }
+}
+
+struct Add;
+impl DiffOp<f64> for Add {
+    fn eval(&self, inputs: &[&f64]) -> f64 {
+        inputs[0] + inputs[1]
+    }
+    fn vjp(&self, _: &[&f64], _: &f64, cotangent: &f64) -> Vec<f64> {
+        vec![*cotangent, *cotangent]
+    }
+    fn jvp(&self, _: &[&f64], _: &f64, tangents: &[&f64]) -> f64 {
+        tangents[0] + tangents[1]
+    }
+}
+
+#[test]
+fn test_backward() {
+    // f(x, y) = x*y + x
+    let mut graph = ComputationGraph::<f64>::new();
+    let x = graph.insert_diff_node("x".to_owned(), Constant(3.0));
+    let y = graph.insert_diff_node("y".to_owned(), Constant(5.0));
+    let unused = graph.insert_diff_node("unused".to_owned(), Constant(1.0));
+    let mut mul = graph.insert_diff_node("mul".to_owned(), Mul);
+    graph.set_inputs(&mut mul, &[&x, &y]);
+    let mut add = graph.insert_diff_node("add".to_owned(), Add);
+    graph.set_inputs(&mut add, &[&mul, &x]);
+
+    let grads = graph.backward(&add, &[&x, &y, &unused]);
+    assert_eq!(grads, vec![Some(6.0), Some(3.0), None]);
+
+    // The graph is still usable afterwards
+    graph.designate_output(&add);
+    assert_eq!(graph.compute(), 18.0);
+}
+
+#[test]
+fn test_backward_through_constant() {
+    let mut graph = ComputationGraph::<f64>::new();
+    let x = graph.insert_diff_node("x".to_owned(), Constant(2.0));
+    let mut opaque = graph.insert_node(
+        "opaque".to_owned(),
+        Box::new(|x| x[0] * 10.0)
+    );
+    graph.set_inputs(&mut opaque, &[&x]);
+    let mut mul = graph.insert_diff_node("mul".to_owned(), Mul);
+    graph.set_inputs(&mut mul, &[&opaque, &x]);
+    assert_eq!(graph.backward(&mul, &[&x]), vec![Some(20.0)]);
+}
+
+#[test]
+fn test_forward_derivative() {
+    // f(x, y) = x*y + x
+    let mut graph = ComputationGraph::<f64>::new();
+    let x = graph.insert_diff_node("x".to_owned(), Constant(3.0));
+    let y = graph.insert_diff_node("y".to_owned(), Constant(5.0));
+    let mut mul = graph.insert_diff_node("mul".to_owned(), Mul);
+    graph.set_inputs(&mut mul, &[&x, &y]);
+    let mut add = graph.insert_diff_node("add".to_owned(), Add);
+    graph.set_inputs(&mut add, &[&mul, &x]);
+
+    assert_eq!(graph.forward_derivative(&add, &[(&x, 1.0)]), (18.0, 6.0));
+    assert_eq!(graph.forward_derivative(&add, &[(&x, 1.0), (&y, 2.0)]), (18.0, 12.0));
+    assert_eq!(graph.forward_derivative(&add, &[]), (18.0, 0.0));
+}
+
+#[test]
+fn test_gradient_graph() {
+    // f(x, y) = x*y + x, with x fed through a placeholder
+    let mut graph = ComputationGraph::<f64>::new();
+    let x = graph.insert_placeholder("x".to_owned());
+    let y = graph.insert_diff_node("y".to_owned(), Constant(5.0));
+    let mut mul = graph.insert_diff_node("mul".to_owned(), Mul);
+    graph.set_inputs(&mut mul, &[&x, &y]);
+    let mut add = graph.insert_diff_node("add".to_owned(), Add);
+    graph.set_inputs(&mut add, &[&mul, &x]);
+
+    let mut grad = graph.gradient_graph(&add, &[&x, &y]);
+    assert!(grad.gradients.iter().all(Option::is_some));
+    let x_copy = grad.take_forward_node(&x);
+    let dot = grad.graph.dot_graph().to_string();
+    assert!(dot.contains("vjp mul"));
+
+    grad.graph.designate_output(grad.gradients[0].as_ref().unwrap());
+    assert_eq!(grad.graph.compute_with([(&x_copy, 3.0)]), 6.0);
+}
+
+#[test]
+fn test_derivatives_with_placeholders() {
+    // f(x, y) = x*y + x, with x fed through a placeholder
+    let mut graph = ComputationGraph::<f64>::new();
+    let x = graph.insert_placeholder("x".to_owned());
+    let y = graph.insert_diff_node("y".to_owned(), Constant(5.0));
+    let mut mul = graph.insert_diff_node("mul".to_owned(), Mul);
+    graph.set_inputs(&mut mul, &[&x, &y]);
+    let mut add = graph.insert_diff_node("add".to_owned(), Add);
+    graph.set_inputs(&mut add, &[&mul, &x]);
+
+    assert_eq!(graph.backward_with(&add, &[&x, &y], [(&x, 3.0)]), vec![Some(6.0), Some(3.0)]);
+    assert_eq!(graph.forward_derivative_with(&add, &[(&x, 1.0)], [(&x, 2.0)]), (12.0, 6.0));
+    assert_eq!(graph.forward_derivative_with(&add, &[(&y, 1.0)], [(&x, 2.0)]), (12.0, 2.0));
+}
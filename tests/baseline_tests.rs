@@ -0,0 +1,77 @@
+use dag_compute::{ComputationGraph, NodeHandle};
+
+use std::fs;
+use std::path::PathBuf;
+
+fn baseline_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir()
+        .join(format!("dag_compute_{}_{}.json", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn build_pipeline(scale: i64) -> ComputationGraph<i64> {
+    let mut graph = ComputationGraph::<i64>::new();
+    let input = graph.insert_node("input", Box::new(|_| 3));
+    let mut scaled = graph.insert_node("scaled", Box::new(move |x| x[0] * scale));
+    graph.set_inputs(&mut scaled, &[&input]);
+    let mut offset = graph.insert_node("offset", Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut offset, &[&scaled]);
+    graph.designate_output(&offset);
+    graph
+}
+
+#[test]
+fn test_baseline_comparison() {
+    let path = baseline_path("baseline_comparison");
+    // The first run records the baseline
+    let (value, divergence) = build_pipeline(2).compute_against_baseline(&path).unwrap();
+    assert_eq!(value, 7);
+    assert_eq!(divergence, None);
+    assert!(path.exists());
+
+    let (value, divergence) = build_pipeline(2).compute_against_baseline(&path).unwrap();
+    assert_eq!(value, 7);
+    assert_eq!(divergence, None);
+
+    let graph = build_pipeline(5);
+    let (value, divergence) = graph.compute_against_baseline(&path).unwrap();
+    assert_eq!(value, 16);
+    let divergence = divergence.unwrap();
+    assert_eq!(divergence.name, "scaled");
+    assert_eq!((divergence.expected, divergence.actual), (6, 15));
+    assert_eq!(divergence.to_string(), format!(
+        "Node scaled ({}) diverged from the baseline: expected 6, got 15", divergence.node));
+    fs::remove_file(&path).unwrap();
+}
+
+fn build_doubler(with_extra: bool) -> (ComputationGraph<i64>, NodeHandle) {
+    let mut graph = ComputationGraph::<i64>::new();
+    let input = graph.insert_placeholder("input");
+    let mut double = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut double, &[&input]);
+    if with_extra {
+        let mut extra = graph.insert_unary_node("extra", |x| x + 1);
+        graph.set_inputs(&mut extra, &[&double]);
+        graph.designate_output(&extra);
+    } else {
+        graph.designate_output(&double);
+    }
+    (graph, input)
+}
+
+#[test]
+fn test_baseline_with_inputs() {
+    let path = baseline_path("baseline_with_inputs");
+    let (graph, input) = build_doubler(false);
+    graph.compute_against_baseline_with(&path, [(&input, 1)]).unwrap();
+
+    // Nodes missing from the baseline are ignored
+    let (graph, input) = build_doubler(true);
+    let (value, divergence) = graph.compute_against_baseline_with(&path, [(&input, 1)])
+        .unwrap();
+    assert_eq!((value, divergence), (3, None));
+    let (_, divergence) = graph.compute_against_baseline_with(&path, [(&input, 2)]).unwrap();
+    assert_eq!(divergence.unwrap().name, "input");
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,89 @@
+use dag_compute::{ComputationGraph, NodeHandle};
+
+use rand::prelude::*;
+
+fn noisy_graph() -> ComputationGraph<f64> {
+    let mut graph = ComputationGraph::<f64>::new();
+    let noise = graph.insert_context_node(
+        "noise".to_owned(),
+        Box::new(|context, _| {
+            let mut rng = SmallRng::seed_from_u64(context.seed());
+            rng.gen_range(-1.0..1.0)
+        })
+    );
+    let mut offset = graph.insert_node("offset".to_owned(), Box::new(|x| x[0] + 10.0));
+    graph.set_inputs(&mut offset, &[&noise]);
+    graph.designate_output(&offset);
+    graph
+}
+
+#[test]
+fn test_monte_carlo_reproducible() {
+    let graph = noisy_graph();
+    let sequential = graph.compute_monte_carlo(50, 1234, 1);
+    let parallel = graph.compute_monte_carlo(50, 1234, 4);
+    assert_eq!(sequential.len(), 50);
+    assert_eq!(sequential, parallel);
+    assert!(sequential.iter().all(|x| (9.0..11.0).contains(x)));
+    // Runs should differ from each other and across base seeds
+    assert_ne!(sequential[0], sequential[1]);
+    assert_ne!(sequential, graph.compute_monte_carlo(50, 4321, 1));
+}
+
+#[test]
+fn test_context_run_index() {
+    let mut graph = ComputationGraph::<usize>::new();
+    let index = graph.insert_context_node(
+        "index".to_owned(),
+        Box::new(|context, _| context.run_index())
+    );
+    graph.designate_output(&index);
+    assert_eq!(graph.compute_monte_carlo(5, 0, 2), vec![0, 1, 2, 3, 4]);
+}
+
+fn seeded_graph(seed: Option<u64>) -> (ComputationGraph<f64>, NodeHandle) {
+    let mut graph = ComputationGraph::<f64>::new();
+    let input = graph.insert_placeholder("input");
+    let first = graph.insert_context_node("first".to_owned(),
+        Box::new(|context, _| context.rng().next_f64()));
+    let second = graph.insert_context_node("second".to_owned(),
+        Box::new(|context, _| context.rng().next_f64()));
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x[0] + x[1] * 10.0 + x[2]));
+    graph.set_inputs(&mut sum, &[&first, &second, &input]);
+    graph.designate_output(&sum);
+    if let Some(seed) = seed {
+        graph.set_seed(seed);
+    }
+    (graph, input)
+}
+
+#[test]
+fn test_graph_seed() {
+    let (graph, input) = seeded_graph(Some(42));
+    assert_eq!(graph.seed(), Some(42));
+    let outputs: Vec<_> = graph.compute_stream(&input, vec![0.0; 4]).collect();
+    // Rebuilding the graph reproduces every run
+    let (graph, input) = seeded_graph(Some(42));
+    assert_eq!(graph.compute_stream(&input, vec![0.0; 4]).collect::<Vec<_>>(), outputs);
+    // Runs draw different values
+    assert_ne!(outputs[0], outputs[1]);
+    let (mut graph, input) = seeded_graph(Some(43));
+    assert_ne!(graph.compute_stream(&input, vec![0.0; 4]).collect::<Vec<_>>(), outputs);
+    graph.clear_seed();
+    assert_eq!(graph.seed(), None);
+}
+
+#[test]
+fn test_node_seeds() {
+    let mut graph = ComputationGraph::<f64>::new();
+    let first = graph.insert_context_node("first".to_owned(),
+        Box::new(|context, _| context.node_seed() as f64));
+    let second = graph.insert_context_node("second".to_owned(),
+        Box::new(|context, _| context.node_seed() as f64));
+    let mut diff = graph.insert_node("diff".to_owned(), Box::new(|x| x[0] - x[1]));
+    graph.set_inputs(&mut diff, &[&first, &second]);
+    graph.designate_output(&diff);
+    graph.set_seed(7);
+    // Nodes with different names get different seeds
+    assert_ne!(graph.compute(), 0.0);
+}
@@ -0,0 +1,125 @@
+use dag_compute::{ComputationGraph, ExecutionListener, ManualClock, Progress, Watchdog,
+    Window};
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Builds a chain of nodes that each advance the clock by their cost in ms
+fn build_timed_graph(clock: &Arc<ManualClock>, costs: &[u64]) -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    graph.set_clock(clock.clone());
+    let mut prev = None;
+    for (index, cost) in costs.iter().copied().enumerate() {
+        let node_clock = clock.clone();
+        let mut node = graph.insert_node(format!("node{}", index), Box::new(move |x| {
+            node_clock.advance(Duration::from_millis(cost));
+            x.first().map_or(0, |x| *x + 1)
+        }));
+        if let Some(prev) = prev {
+            graph.set_inputs(&mut node, &[&prev]);
+        }
+        prev = Some(node);
+    }
+    graph.designate_output(prev.as_ref().unwrap());
+    graph
+}
+
+#[test]
+fn test_profile_with_manual_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let graph = build_timed_graph(&clock, &[10, 30, 5]);
+    let (value, report) = graph.compute_profiled();
+    assert_eq!(value, 2);
+    let durations: Vec<_> = report.timings.iter().map(|timing| timing.duration).collect();
+    assert_eq!(durations, [10, 30, 5].map(Duration::from_millis));
+    assert_eq!(report.total, Duration::from_millis(45));
+
+    let (_, summary) = build_timed_graph(&clock, &[10, 30, 5]).compute_summarized();
+    assert_eq!(summary.wall_time, Duration::from_millis(45));
+    assert_eq!(summary.slowest[0].node_name, "node1");
+}
+
+#[derive(Default)]
+struct ProgressLog(Mutex<Vec<Progress>>);
+impl ExecutionListener for ProgressLog {
+    fn on_progress(&self, progress: Progress) {
+        self.0.lock().unwrap().push(progress);
+    }
+}
+
+#[test]
+fn test_progress_with_manual_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let mut graph = build_timed_graph(&clock, &[10, 10, 10, 10]);
+    let log = Arc::new(ProgressLog::default());
+    graph.add_listener(log.clone());
+    graph.compute();
+    let progress = log.0.lock().unwrap();
+    assert_eq!(progress[0].elapsed, Duration::from_millis(10));
+    assert_eq!(progress[0].estimated_remaining, Duration::from_millis(30));
+    assert_eq!(progress[2].estimated_remaining, Duration::from_millis(10));
+}
+
+#[test]
+fn test_watchdog_with_manual_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let watchdog = Arc::new(Watchdog::with_clock(2.0, Duration::from_millis(10),
+        clock.clone()));
+    // Only explicit checks happen during the test
+    watchdog.set_check_interval(Duration::from_secs(3600));
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let callback_reported = reported.clone();
+    watchdog.set_callback(move |node, elapsed| {
+        callback_reported.lock().unwrap().push((node.name.to_owned(), elapsed));
+    });
+
+    let mut graph = ComputationGraph::<i32>::new();
+    let (fast_clock, fast_watchdog) = (clock.clone(), watchdog.clone());
+    let fast = graph.insert_node("fast", Box::new(move |_| {
+        fast_clock.advance(Duration::from_millis(15));
+        fast_watchdog.check();
+        1
+    }));
+    let (slow_clock, slow_watchdog) = (clock.clone(), watchdog.clone());
+    let mut slow = graph.insert_node("slow", Box::new(move |x| {
+        slow_clock.advance(Duration::from_millis(25));
+        slow_watchdog.check();
+        x[0] + 1
+    }));
+    graph.set_inputs(&mut slow, &[&fast]);
+    graph.designate_output(&slow);
+    graph.add_listener(watchdog.clone());
+    assert_eq!(graph.compute(), 2);
+    assert_eq!(*reported.lock().unwrap(), [("slow".to_owned(), Duration::from_millis(25))]);
+}
+
+#[test]
+fn test_time_window_with_manual_clock() {
+    let clock = Arc::new(ManualClock::new());
+    let mut graph = ComputationGraph::<i32>::new();
+    graph.set_clock(clock.clone());
+    let input = graph.insert_placeholder("input");
+    let mut sum = graph.insert_window_node("sum", Window::Time(Duration::from_millis(20)),
+        |items| items.iter().sum());
+    graph.set_inputs(&mut sum, &[&input]);
+    graph.designate_output(&sum);
+    let blocks = (1..=5).inspect(|_| clock.advance(Duration::from_millis(10)));
+    let sums: Vec<_> = graph.compute_stream(&input, blocks).collect();
+    assert_eq!(sums, vec![1, 3, 6, 9, 12]);
+}
+
+#[test]
+fn test_time_window_follows_set_clock() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    let mut sum = graph.insert_window_node("sum", Window::Time(Duration::from_millis(20)),
+        |items| items.iter().sum());
+    graph.set_inputs(&mut sum, &[&input]);
+    graph.designate_output(&sum);
+    // The clock is set after the window node was inserted
+    let clock = Arc::new(ManualClock::new());
+    graph.set_clock(clock.clone());
+    let blocks = (1..=5).inspect(|_| clock.advance(Duration::from_millis(10)));
+    let sums: Vec<_> = graph.compute_stream(&input, blocks).collect();
+    assert_eq!(sums, vec![1, 3, 6, 9, 12]);
+}
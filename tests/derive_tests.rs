@@ -0,0 +1,81 @@
+use dag_compute::{ComputationGraph, ComputeNode};
+
+#[derive(ComputeNode)]
+struct MultiplyAdd<'a> {
+    a: &'a i32,
+    b: &'a i32,
+    c: &'a i32
+}
+impl MultiplyAdd<'_> {
+    fn eval(&self) -> i32 {
+        self.a * self.b + self.c
+    }
+}
+
+#[derive(ComputeNode)]
+#[compute_node(name = "forty_two", value = i32)]
+struct Constant;
+impl Constant {
+    fn eval(&self) -> i32 {
+        42
+    }
+}
+
+#[derive(ComputeNode)]
+struct Negate<'a>(&'a i32);
+impl Negate<'_> {
+    fn eval(&self) -> i32 {
+        -self.0
+    }
+}
+
+#[derive(ComputeNode)]
+struct HTTPRequestId<'a>(&'a i32);
+impl HTTPRequestId<'_> {
+    fn eval(&self) -> i32 {
+        *self.0
+    }
+}
+
+#[derive(ComputeNode)]
+struct Vec3Add<'a>(&'a i32);
+impl Vec3Add<'_> {
+    fn eval(&self) -> i32 {
+        *self.0
+    }
+}
+
+#[test]
+fn test_derived_names() {
+    assert_eq!(<HTTPRequestId as ComputeNode<i32>>::node_name(), "http_request_id");
+    assert_eq!(<Vec3Add as ComputeNode<i32>>::node_name(), "vec3_add");
+}
+
+#[test]
+fn test_derived_nodes() {
+    assert_eq!(<MultiplyAdd as ComputeNode<i32>>::ARITY, 3);
+    assert_eq!(<MultiplyAdd as ComputeNode<i32>>::node_name(), "multiply_add");
+
+    let mut graph = ComputationGraph::<i32>::new();
+    let const_handle = graph.insert_compute_node::<Constant>();
+    let mut neg_handle = graph.insert_compute_node::<Negate>();
+    let mut muladd_handle = graph.insert_compute_node::<MultiplyAdd>();
+    assert_eq!(graph.node_name(&const_handle), "forty_two");
+    assert_eq!(graph.node_name(&neg_handle), "negate");
+    graph.set_inputs(&mut neg_handle, &[&const_handle]);
+    graph.set_inputs(&mut muladd_handle,
+        &[&const_handle, &neg_handle, &const_handle]);
+    graph.designate_output(&muladd_handle);
+    assert_eq!(graph.compute(), 42*-42+42);
+}
+
+#[test]
+#[should_panic(expected = "Node negate expected 1 inputs but received 2")]
+fn test_derived_arity_mismatch() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let const_handle = graph.insert_compute_node::<Constant>();
+    let mut neg_handle = graph.insert_compute_node::<Negate>();
+    graph.set_inputs(&mut neg_handle, &[&const_handle, &const_handle]);
+    graph.designate_output(&neg_handle);
+    graph.compute();
+}
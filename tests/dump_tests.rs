@@ -0,0 +1,35 @@
+use dag_compute::{ComputationGraph, DumpTarget};
+
+use std::fs;
+
+#[test]
+fn test_dump_values_to_directory() {
+    let dir = std::env::temp_dir().join(format!("dag_compute_dump_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let mut graph = ComputationGraph::<Vec<f32>>::new();
+    let input = graph.insert_placeholder("stage/input");
+    let mut scale = graph.insert_node("stage/scale".to_owned(),
+        Box::new(|x| x[0].iter().map(|v| v * 2.0).collect()));
+    graph.set_inputs(&mut scale, &[&input]);
+    let mut total = graph.insert_node("total".to_owned(),
+        Box::new(|x| vec![x[0].iter().sum()]));
+    graph.set_inputs(&mut total, &[&scale]);
+    graph.designate_output(&total);
+
+    graph.dump_values_matching("stage/*", DumpTarget::Directory(dir.clone()));
+    graph.dump_node_values(&total, DumpTarget::Directory(dir.clone()));
+    let scale_file = dir.join(format!("stage_scale-{}.txt", graph.node_id(&scale)));
+    let input_file = dir.join(format!("stage_input-{}.txt", graph.node_id(&input)));
+    let total_file = dir.join(format!("total-{}.txt", graph.node_id(&total)));
+    for block in [vec![1.0, 2.0], vec![0.5]] {
+        graph.compute_stream(&input, [block]).for_each(drop);
+    }
+    assert_eq!(fs::read_to_string(input_file).unwrap(), "[1.0, 2.0]\n[0.5]\n");
+    assert_eq!(fs::read_to_string(scale_file).unwrap(), "[2.0, 4.0]\n[1.0]\n");
+    assert_eq!(fs::read_to_string(total_file).unwrap(), "[6.0]\n[1.0]\n");
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+
+    graph.clear_value_dumps();
+    assert_eq!(graph.compute_stream(&input, [vec![3.0]]).next(), Some(vec![6.0]));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,73 @@
+use dag_compute::{ComputationGraph, ErrorMode, NodeHandle};
+
+// Two failing branches feeding the output, and a healthy sink
+fn build_branches() -> (ComputationGraph<i32>, NodeHandle, NodeHandle, NodeHandle) {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    let mut fail_a = graph.insert_node("fail_a", Box::new(|x| {
+        assert!(*x[0] > 0, "input must be positive");
+        *x[0]
+    }));
+    graph.set_inputs(&mut fail_a, &[&input]);
+    let mut fail_b = graph.insert_node("fail_b", Box::new(|x| {
+        if *x[0] < 0 {
+            panic!("input {} is negative", x[0]);
+        }
+        *x[0]
+    }));
+    graph.set_inputs(&mut fail_b, &[&input]);
+    let mut healthy = graph.insert_node("healthy", Box::new(|x| x[0] * 10));
+    graph.set_inputs(&mut healthy, &[&input]);
+    let mut sum = graph.insert_node("sum", Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut sum, &[&fail_a, &fail_b]);
+    graph.designate_output(&sum);
+    graph.add_sink(&healthy);
+    (graph, input, healthy, sum)
+}
+
+#[test]
+fn test_try_compute_success() {
+    let (graph, input, _, _) = build_branches();
+    assert_eq!(graph.error_mode(), ErrorMode::FailFast);
+    assert_eq!(graph.try_compute_with([(&input, 2)]), Ok(4));
+}
+
+#[test]
+fn test_collect_all() {
+    let (mut graph, input, healthy, sum) = build_branches();
+    let healthy_id = graph.node_id(&healthy);
+    let sum_id = graph.node_id(&sum);
+    graph.set_error_mode(ErrorMode::CollectAll);
+
+    let failure = graph.try_compute_with([(&input, -1)]).unwrap_err();
+    let mut names: Vec<_> = failure.failures.iter().map(|f| f.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["fail_a", "fail_b"]);
+    let fail_b = failure.failures.iter().find(|f| f.name == "fail_b").unwrap();
+    assert_eq!(fail_b.message, "input -1 is negative");
+    assert_eq!(failure.skipped, [sum_id]);
+    assert_eq!(failure.output, None);
+    assert_eq!(failure.sinks.get(&healthy_id), Some(&-10));
+    assert!(failure.to_string().starts_with("2 nodes failed (1 skipped)"));
+}
+
+#[test]
+fn test_fail_fast() {
+    let (graph, input, _, sum) = build_branches();
+    let sum_id = graph.node_id(&sum);
+    let failure = graph.try_compute_with([(&input, -1)]).unwrap_err();
+    assert_eq!(failure.failures.len(), 1);
+    assert!(failure.skipped.contains(&sum_id));
+    assert_eq!(failure.output, None);
+}
+
+#[test]
+fn test_unfed_placeholder() {
+    let (graph, _, _, sum) = build_branches();
+    let sum_id = graph.node_id(&sum);
+    let failure = graph.try_compute().unwrap_err();
+    assert_eq!(failure.failures.len(), 1);
+    assert_eq!(failure.failures[0].name, "input");
+    assert_eq!(failure.failures[0].message, "Placeholder input was not given a value");
+    assert!(failure.skipped.contains(&sum_id));
+}
@@ -0,0 +1,36 @@
+use dag_compute::{ComputationGraph, ExecEvent};
+
+#[test]
+fn test_event_stream() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 4));
+    let mut b = graph.insert_unary_node("b", |x| x * 3);
+    graph.set_inputs(&mut b, &[&a]);
+    graph.designate_output(&b);
+    let a_id = graph.node_id(&a);
+
+    let (events, handle) = graph.compute_with_events();
+    let events: Vec<_> = events.iter().collect();
+    assert_eq!(handle.join().unwrap(), 12);
+    let events: Vec<_> = events.into_iter()
+        .filter(|event| !matches!(event, ExecEvent::Progress(_)))
+        .collect();
+    assert_eq!(events.len(), 7);
+    assert_eq!(events[0], ExecEvent::Planned { node_count: 2 });
+    assert_eq!(events[1], ExecEvent::NodeStarted { node: a_id, name: "a".to_owned() });
+    assert!(matches!(events[4], ExecEvent::NodeFinished { ref name, .. } if name == "b"));
+    assert!(matches!(events[5], ExecEvent::ValueDropped { ref name, .. } if name == "a"));
+    assert!(matches!(events[6], ExecEvent::Completed { .. }));
+}
+
+#[test]
+fn test_event_stream_failure() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| panic!("sensor offline")));
+    graph.designate_output(&a);
+
+    let (events, handle) = graph.compute_with_events();
+    let events: Vec<_> = events.iter().collect();
+    assert!(handle.join().is_err());
+    assert_eq!(events.last(), Some(&ExecEvent::Failed { message: "sensor offline".to_owned() }));
+}
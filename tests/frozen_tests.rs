@@ -0,0 +1,106 @@
+use dag_compute::{ComputationGraph, FrozenGraph, NodeHandle, RetentionPolicy};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+fn assert_send_sync<S: Send + Sync>() {}
+
+#[test]
+fn test_frozen_graph_threads() {
+    assert_send_sync::<FrozenGraph<i64>>();
+    let mut graph = ComputationGraph::<i64>::new();
+    let x = graph.insert_placeholder("x");
+    let y = graph.insert_placeholder("y");
+    let mut product = graph.insert_binary_node("product", |a, b| a * b);
+    graph.set_inputs(&mut product, &[&x, &y]);
+    let mut offset = graph.insert_unary_node("offset", |a| a + 1);
+    graph.set_inputs(&mut offset, &[&product]);
+    graph.designate_output(&offset);
+
+    let frozen = Arc::new(graph.freeze());
+    let (x, y) = (Arc::new(x), Arc::new(y));
+    let workers: Vec<_> = (0..8i64).map(|worker| {
+        let (frozen, x, y) = (frozen.clone(), x.clone(), y.clone());
+        thread::spawn(move || {
+            (0..100i64).all(|i| frozen.compute_with([(&*x, worker), (&*y, i)]) == worker * i + 1)
+        })
+    }).collect();
+    for worker in workers {
+        assert!(worker.join().unwrap());
+    }
+
+    // Thawing gives back a graph that can be modified and computed again
+    let graph = Arc::try_unwrap(frozen).unwrap().into_graph();
+    assert_eq!(graph.compute_with([(&*x, 3), (&*y, 4)]), 13);
+}
+
+#[test]
+fn test_frozen_graph_seeds() {
+    let mut graph = ComputationGraph::<u64>::new();
+    let noise = graph.insert_context_node("noise", Box::new(|context, _| context.seed()));
+    graph.designate_output(&noise);
+    graph.set_seed(1);
+    let frozen = graph.freeze();
+    // Independent calls are reproducible
+    assert_eq!(frozen.compute(), frozen.compute());
+    // Runs of one state differ
+    let mut state = frozen.new_state();
+    let (first, _) = frozen.compute_in(&mut state, Vec::<(&NodeHandle, u64)>::new());
+    let (second, _) = frozen.compute_in(&mut state, Vec::<(&NodeHandle, u64)>::new());
+    assert_ne!(first, second);
+    assert_eq!(first, frozen.compute());
+    assert_eq!(state.runs(), 2);
+}
+
+#[test]
+fn test_frozen_graph_states() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let mut graph = ComputationGraph::<i64>::new();
+    let counter = loads.clone();
+    let table = graph.insert_node("table", Box::new(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        100
+    }));
+    graph.set_retention(&table, RetentionPolicy::RetainForever);
+    let x = graph.insert_placeholder("x");
+    let mut sum = graph.insert_binary_node("sum", |a, b| a + b);
+    graph.set_inputs(&mut sum, &[&table, &x]);
+    graph.designate_output(&sum);
+    let frozen = Arc::new(graph.freeze());
+
+    // Each thread caches the table in its own state
+    let x = Arc::new(x);
+    let workers: Vec<_> = (0..4i64).map(|worker| {
+        let (frozen, x) = (frozen.clone(), x.clone());
+        thread::spawn(move || {
+            let mut state = frozen.new_state();
+            (0..10i64).all(|i| {
+                let (value, retained) = frozen.compute_in(&mut state, [(&*x, worker + i)]);
+                value == 100 + worker + i && retained.len() == 1
+            })
+        })
+    }).collect();
+    for worker in workers {
+        assert!(worker.join().unwrap());
+    }
+    assert_eq!(loads.load(Ordering::SeqCst), 4);
+
+    let mut state = frozen.new_state();
+    frozen.compute_in(&mut state, [(&*x, 1)]);
+    state.clear_retained();
+    frozen.compute_in(&mut state, [(&*x, 1)]);
+    assert_eq!(loads.load(Ordering::SeqCst), 6);
+}
+
+#[test]
+#[should_panic(expected = "Node state cannot be evaluated in a frozen graph")]
+fn test_freeze_with_delay() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let mut state = graph.insert_delay("state", 0);
+    let mut next = graph.insert_unary_node("next", |x| x + 1);
+    graph.set_inputs(&mut next, &[&state]);
+    graph.set_delay_source(&mut state, &next);
+    graph.designate_output(&next);
+    graph.freeze();
+}
@@ -0,0 +1,38 @@
+use dag_compute::{ComputationGraph, Journal, JournalState, MemoryJournal};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn effect_graph(journal: Arc<MemoryJournal<i32>>, writes: Arc<AtomicUsize>)
+        -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let value = graph.insert_node("value".to_owned(), Box::new(|_| 5));
+    let mut write = graph.insert_effect_node("write".to_owned(), journal, move |x| {
+        writes.fetch_add(1, Ordering::SeqCst);
+        *x[0] * 2
+    });
+    graph.set_inputs(&mut write, &[&value]);
+    let mut out = graph.insert_node("out".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut out, &[&write]);
+    graph.designate_output(&out);
+    graph
+}
+
+#[test]
+fn test_effect_at_most_once() {
+    let journal = Arc::new(MemoryJournal::new());
+    let writes = Arc::new(AtomicUsize::new(0));
+    assert_eq!(effect_graph(journal.clone(), writes.clone()).compute(), 11);
+    assert_eq!(journal.state("write"), JournalState::Completed(10));
+    // Retrying reuses the recorded output instead of writing again
+    assert_eq!(effect_graph(journal.clone(), writes.clone()).compute(), 11);
+    assert_eq!(writes.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+#[should_panic(expected = "was interrupted")]
+fn interrupted_effect() {
+    let journal = Arc::new(MemoryJournal::new());
+    journal.record_intent("write");
+    effect_graph(journal, Arc::new(AtomicUsize::new(0))).compute();
+}
@@ -0,0 +1,159 @@
+use dag_compute::{ComputationGraph, NodeHandle};
+
+use std::sync::{Arc, Mutex};
+
+// Builds `mode > 0 ? expensive_a(base) + 1 : expensive_b(base) * 2`,
+// logging the evaluated expensive nodes
+fn build_select(log: &Arc<Mutex<Vec<&'static str>>>)
+        -> (ComputationGraph<i32>, NodeHandle) {
+    let mut graph = ComputationGraph::<i32>::new();
+    let mode = graph.insert_placeholder("mode");
+    let base = graph.insert_node("base", Box::new(|_| 10));
+    let a_log = log.clone();
+    let mut expensive_a = graph.insert_node("expensive_a", Box::new(move |x| {
+        a_log.lock().unwrap().push("a");
+        x[0] * 3
+    }));
+    graph.set_inputs(&mut expensive_a, &[&base]);
+    let mut then_branch = graph.insert_unary_node("then", |x| x + 1);
+    graph.set_inputs(&mut then_branch, &[&expensive_a]);
+    let b_log = log.clone();
+    let mut expensive_b = graph.insert_node("expensive_b", Box::new(move |x| {
+        b_log.lock().unwrap().push("b");
+        x[0] * 5
+    }));
+    graph.set_inputs(&mut expensive_b, &[&base]);
+    let mut else_branch = graph.insert_unary_node("else", |x| x * 2);
+    graph.set_inputs(&mut else_branch, &[&expensive_b]);
+    let mut select = graph.insert_select_node("select", |mode| *mode > 0);
+    graph.set_inputs(&mut select, &[&mode, &then_branch, &else_branch]);
+    graph.designate_output(&select);
+    (graph, mode)
+}
+
+#[test]
+fn test_lazy_select() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (graph, mode) = build_select(&log);
+    assert_eq!(graph.compute_with([(&mode, 1)]), 31);
+    assert_eq!(*log.lock().unwrap(), ["a"]);
+
+    log.lock().unwrap().clear();
+    let (graph, mode) = build_select(&log);
+    assert_eq!(graph.compute_with([(&mode, 0)]), 100);
+    assert_eq!(*log.lock().unwrap(), ["b"]);
+}
+
+#[test]
+fn test_lazy_select_frozen() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (graph, mode) = build_select(&log);
+    let frozen = graph.freeze();
+    assert_eq!(frozen.compute_with([(&mode, 0)]), 100);
+    assert_eq!(frozen.compute_with([(&mode, 2)]), 31);
+    assert_eq!(*log.lock().unwrap(), ["b", "a"]);
+}
+
+#[test]
+fn test_lazy_select_traced() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (graph, mode) = build_select(&log);
+    let traced = graph.compute_traced_with([(&mode, 1)]);
+    assert_eq!(traced.value, 31);
+    let names: Vec<_> = traced.provenance.iter()
+        .map(|record| record.node_name.as_str())
+        .collect();
+    assert!(!names.contains(&"expensive_b") && !names.contains(&"else"));
+    // The unchosen branch is left out of the inputs of the select node
+    assert_eq!(traced.output_provenance().inputs.len(), 2);
+    assert!(!traced.lineage_json("run").contains("\"else\""));
+}
+
+#[test]
+fn test_shared_branch_still_evaluated() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let flag = graph.insert_node("flag", Box::new(|_| 0));
+    let yes = graph.insert_node("yes", Box::new(|_| 1));
+    let no = graph.insert_node("no", Box::new(|_| 2));
+    let mut select = graph.insert_select_node("select", |flag| *flag != 0);
+    graph.set_inputs(&mut select, &[&flag, &yes, &no]);
+    // The unchosen branch is needed by the output regardless
+    let mut sum = graph.insert_binary_node("sum", |x, y| x + y * 10);
+    graph.set_inputs(&mut sum, &[&select, &yes]);
+    graph.designate_output(&sum);
+    assert_eq!(graph.compute(), 12);
+}
+
+#[test]
+fn test_nested_select() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let outer_flag = graph.insert_node("outer_flag", Box::new(|_| 1));
+    let inner_flag = graph.insert_node("inner_flag", Box::new(|_| 0));
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let b = graph.insert_node("b", Box::new(|_| 2));
+    let c = graph.insert_node("c", Box::new(|_| panic!("c should not be evaluated")));
+    let mut inner = graph.insert_select_node("inner", |flag| *flag != 0);
+    graph.set_inputs(&mut inner, &[&inner_flag, &a, &b]);
+    let mut outer = graph.insert_select_node("outer", |flag| *flag != 0);
+    graph.set_inputs(&mut outer, &[&outer_flag, &inner, &c]);
+    graph.designate_output(&outer);
+    assert_eq!(graph.compute(), 2);
+}
+
+// Builds an and or or node over three checks, logging the evaluated checks
+fn build_checks(log: &Arc<Mutex<Vec<&'static str>>>, all: bool, results: [bool; 3])
+        -> ComputationGraph<bool> {
+    let mut graph = ComputationGraph::<bool>::new();
+    let mut checks = Vec::new();
+    for (name, result) in ["first", "second", "third"].into_iter().zip(results) {
+        let check_log = log.clone();
+        let source = graph.insert_node(format!("{}_input", name), Box::new(|_| true));
+        let mut check = graph.insert_node(name, Box::new(move |_| {
+            check_log.lock().unwrap().push(name);
+            result
+        }));
+        graph.set_inputs(&mut check, &[&source]);
+        checks.push(check);
+    }
+    let mut combined = if all {
+        graph.insert_and_node("all", |x| *x)
+    } else {
+        graph.insert_or_node("any", |x| *x)
+    };
+    graph.set_inputs(&mut combined, &checks.iter().collect::<Vec<_>>());
+    graph.designate_output(&combined);
+    graph
+}
+
+#[test]
+fn test_short_circuit_and() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    assert!(!build_checks(&log, true, [true, false, true]).compute());
+    assert_eq!(*log.lock().unwrap(), ["first", "second"]);
+
+    log.lock().unwrap().clear();
+    assert!(build_checks(&log, true, [true, true, true]).compute());
+    assert_eq!(*log.lock().unwrap(), ["first", "second", "third"]);
+}
+
+#[test]
+fn test_short_circuit_or() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    assert!(build_checks(&log, false, [true, false, false]).compute());
+    assert_eq!(*log.lock().unwrap(), ["first"]);
+
+    log.lock().unwrap().clear();
+    assert!(!build_checks(&log, false, [false, false, false]).compute());
+    assert_eq!(*log.lock().unwrap(), ["first", "second", "third"]);
+}
+
+#[test]
+fn test_short_circuit_empty() {
+    let mut graph = ComputationGraph::<bool>::new();
+    let all = graph.insert_and_node("all", |x| *x);
+    let any = graph.insert_or_node("any", |x| *x);
+    let mut both = graph.insert_binary_node("both", |x, y| *x && !*y);
+    graph.set_inputs(&mut both, &[&all, &any]);
+    graph.designate_output(&both);
+    assert!(graph.compute());
+}
@@ -0,0 +1,61 @@
+use dag_compute::{ComputationGraph, ComputeNode, LintKind};
+
+struct Sum3;
+impl ComputeNode<i32> for Sum3 {
+    const ARITY: usize = 3;
+    fn node_name() -> String {
+        "sum3".to_owned()
+    }
+    fn eval_inputs(inputs: &[&i32]) -> i32 {
+        inputs.iter().copied().sum()
+    }
+}
+
+#[test]
+fn test_clean_graph() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let b = graph.insert_node("b", Box::new(|_| 2));
+    let mut sum = graph.insert_binary_node("sum", |x, y| x + y);
+    graph.set_inputs(&mut sum, &[&a, &b]);
+    graph.designate_output(&sum);
+    assert!(graph.lint().is_empty());
+}
+
+#[test]
+fn test_lint_findings() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let b = graph.insert_node("a", Box::new(|_| 2));
+    let mut sum = graph.insert_compute_node::<Sum3>();
+    graph.set_inputs(&mut sum, &[&a, &b]);
+    let mut scale = graph.insert_node("scale", Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut scale, &[&sum, &a]);
+    graph.declare_arity(&scale, 2);
+    graph.declare_unused_input(&scale, 1);
+    graph.add_tag(&scale, "upload");
+    graph.declare_side_effect_tag("upload");
+    graph.designate_output(&scale);
+    let stray = graph.insert_node("stray", Box::new(|_| 3));
+    let mut stray_use = graph.insert_unary_node("stray_use", |x| x + 1);
+    graph.set_inputs(&mut stray_use, &[&stray]);
+
+    let kinds: Vec<_> = graph.lint().into_iter().map(|finding| finding.kind).collect();
+    assert_eq!(kinds, [
+        LintKind::ArityMismatch { expected: 3, actual: 2 },
+        LintKind::UnusedInput { index: 1, input: graph.node_id(&a) },
+        LintKind::DuplicateName { first: graph.node_id(&a) },
+        LintKind::DisconnectedComponent { size: 2 },
+        LintKind::SideEffectOutput { tag: "upload".to_owned() }
+    ]);
+    let findings = graph.lint();
+    assert_eq!(findings[2].node, graph.node_id(&b));
+    assert_eq!(findings[3].name, "stray");
+    assert_eq!(findings[0].to_string(),
+        format!("Node sum3 ({}): expects 3 inputs but 2 are wired", graph.node_id(&sum)));
+
+    // Sinks anchor the nodes connected to them
+    graph.add_sink(&stray_use);
+    assert!(!graph.lint().iter()
+        .any(|finding| matches!(finding.kind, LintKind::DisconnectedComponent { .. })));
+}
@@ -0,0 +1,119 @@
+use dag_compute::{ComputationGraph, ExecutionListener, NodeInfo, SweepPolicy};
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Default)]
+struct RecordingListener {
+    events: Mutex<Vec<String>>
+}
+impl ExecutionListener for RecordingListener {
+    fn on_unreachable(&self, node: NodeInfo<'_>) {
+        self.events.lock().unwrap().push(format!("unreachable {}", node.name));
+    }
+    fn on_plan(&self, order: &[NodeInfo<'_>]) {
+        let names: Vec<_> = order.iter().map(|node| node.name).collect();
+        self.events.lock().unwrap().push(format!("plan {}", names.join(",")));
+    }
+    fn on_node_start(&self, node: NodeInfo<'_>) {
+        self.events.lock().unwrap().push(format!("start {}", node.name));
+    }
+    fn on_node_finish(&self, node: NodeInfo<'_>, _duration: Duration) {
+        self.events.lock().unwrap().push(format!("finish {}", node.name));
+    }
+    fn on_value_dropped(&self, node: NodeInfo<'_>) {
+        self.events.lock().unwrap().push(format!("drop {}", node.name));
+    }
+    fn on_complete(&self, _duration: Duration) {
+        self.events.lock().unwrap().push("complete".to_owned());
+    }
+}
+
+#[test]
+fn test_listener_events() {
+    let listener = Arc::new(RecordingListener::default());
+    let mut graph = ComputationGraph::<i32>::new();
+    graph.add_listener(listener.clone());
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 2));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 3));
+    let mut sum = graph.insert_binary_node("sum", |x, y| x + y);
+    graph.set_inputs(&mut sum, &[&a, &b]);
+    let mut neg = graph.insert_unary_node("neg", |x| -x);
+    graph.set_inputs(&mut neg, &[&sum]);
+    graph.designate_output(&neg);
+    assert_eq!(graph.compute(), -5);
+
+    let events = listener.events.lock().unwrap();
+    assert_eq!(*events, [
+        "plan a,b,sum,neg",
+        "start a", "finish a",
+        "start b", "finish b",
+        "start sum", "finish sum", "drop a", "drop b",
+        "start neg", "finish neg", "drop sum",
+        "complete"
+    ]);
+}
+
+#[test]
+fn test_sweep_warnings() {
+    let listener = Arc::new(RecordingListener::default());
+    let mut graph = ComputationGraph::<i32>::new();
+    graph.add_listener(listener.clone());
+    graph.set_sweep_policy(SweepPolicy::Warn);
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 2));
+    let mut dead = graph.insert_unary_node("dead", |x| x * 2);
+    graph.set_inputs(&mut dead, &[&a]);
+    graph.designate_output(&a);
+    assert_eq!(graph.compute(), 2);
+
+    let events = listener.events.lock().unwrap();
+    assert_eq!(*events, ["unreachable dead", "plan a", "start a", "finish a", "complete"]);
+}
+
+#[derive(Default)]
+struct ProgressListener {
+    progress: Mutex<Vec<dag_compute::Progress>>
+}
+impl ExecutionListener for ProgressListener {
+    fn on_progress(&self, progress: dag_compute::Progress) {
+        self.progress.lock().unwrap().push(progress);
+    }
+}
+
+#[test]
+fn test_progress_estimates() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let mut prev = graph.insert_node("node0".to_owned(), Box::new(|_| 0));
+    for i in 1..4 {
+        let mut next = graph.insert_node(format!("node{}", i), Box::new(|x| {
+            std::thread::sleep(Duration::from_millis(10));
+            x[0] + 1
+        }));
+        graph.set_inputs(&mut next, &[&prev]);
+        prev = next;
+    }
+    graph.designate_output(&prev);
+    let listener = Arc::new(ProgressListener::default());
+    graph.add_listener(listener.clone());
+
+    // Without timings, the remaining nodes take the average time so far
+    assert_eq!(graph.compute_iterations(1), vec![3]);
+    {
+        let progress = listener.progress.lock().unwrap();
+        assert_eq!(progress.len(), 4);
+        assert_eq!((progress[1].completed, progress[1].total), (2, 4));
+        assert_eq!(progress[1].estimated_remaining, progress[1].elapsed);
+        assert_eq!(progress[3].estimated_remaining, Duration::ZERO);
+    }
+
+    // With timings, node0 is known to be cheap, so the estimate does not
+    // assume the remaining nodes are as fast as the average so far
+    let (_, report) = graph.compute_profiled();
+    graph.apply_profile(&report);
+    listener.progress.lock().unwrap().clear();
+    assert_eq!(graph.compute_iterations(1), vec![3]);
+    let progress = listener.progress.lock().unwrap();
+    assert!(progress[1].estimated_remaining >= Duration::from_millis(15));
+    assert!(progress[1].estimated_remaining > progress[1].elapsed);
+    assert_eq!(progress[3].estimated_remaining, Duration::ZERO);
+}
@@ -0,0 +1,112 @@
+use dag_compute::ComputationGraph;
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use log::kv::{Key, VisitSource, Value, Error};
+
+type Fields = Vec<(String, String)>;
+
+// A log record as seen by the logger
+struct LoggedRecord {
+    message: String,
+    target: String,
+    level: Level,
+    fields: Fields
+}
+
+// Records the message and structured fields of every log record
+struct FieldLogger {
+    records: Mutex<Vec<LoggedRecord>>
+}
+struct FieldCollector(Fields);
+impl<'kvs> VisitSource<'kvs> for FieldCollector {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+impl Log for FieldLogger {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+    fn log(&self, record: &Record) {
+        let mut fields = FieldCollector(Vec::new());
+        record.key_values().visit(&mut fields).unwrap();
+        self.records.lock().unwrap().push(LoggedRecord {
+            message: record.args().to_string(),
+            target: record.target().to_owned(),
+            level: record.level(),
+            fields: fields.0
+        });
+    }
+    fn flush(&self) {}
+}
+static LOGGER: FieldLogger = FieldLogger { records: Mutex::new(Vec::new()) };
+
+fn init_logger() {
+    // Tests share the logger, which can only be set once
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(LevelFilter::Trace);
+}
+
+#[test]
+fn test_structured_node_fields() {
+    init_logger();
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 1));
+    let mut b = graph.insert_unary_node("b", |x| x + 1);
+    graph.set_inputs(&mut b, &[&a]);
+    graph.designate_output(&b);
+    let b_id = graph.node_id(&b).to_string();
+    assert_eq!(graph.compute(), 2);
+
+    let records = LOGGER.records.lock().unwrap();
+    let record = records.iter()
+        .find(|record| record.message == "Evaluating chained node b")
+        .unwrap();
+    assert_eq!(record.fields[0], ("node".to_owned(), "b".to_owned()));
+    assert_eq!(record.fields[1], ("node_id".to_owned(), b_id.clone()));
+    assert_eq!(record.fields[2].0, "graph_id");
+    let record = records.iter()
+        .find(|record| record.message.starts_with("Evaluated node b in"))
+        .unwrap();
+    let keys: Vec<_> = record.fields.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["node", "node_id", "graph_id", "duration_us"]);
+    assert_eq!(record.fields[1].1, b_id);
+}
+
+#[test]
+fn test_node_log_overrides() {
+    init_logger();
+    let mut graph = ComputationGraph::<i32>::new();
+    let quiet = graph.insert_node("quiet_source".to_owned(), Box::new(|_| 1));
+    let mut loud = graph.insert_node("loud_sink".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut loud, &[&quiet]);
+    graph.designate_output(&loud);
+    graph.set_log_target(&quiet, "pipeline::quiet");
+    graph.set_log_level(&loud, Level::Info);
+    assert_eq!(graph.compute(), 2);
+
+    let records = LOGGER.records.lock().unwrap();
+    let node_records = |name: &str| -> Vec<&LoggedRecord> {
+        records.iter()
+            .filter(|record| record.fields.iter()
+                .any(|(key, value)| key == "node" && value == name))
+            .collect()
+    };
+    let quiet_records = node_records("quiet_source");
+    assert!(quiet_records.iter()
+        .any(|record| record.message.starts_with("Evaluated node quiet_source")));
+    for record in quiet_records {
+        assert_eq!(record.target, "pipeline::quiet");
+        assert_eq!(record.level, Level::Trace);
+    }
+    let loud_records = node_records("loud_sink");
+    assert!(loud_records.iter()
+        .any(|record| record.message.starts_with("Evaluated node loud_sink")));
+    for record in loud_records {
+        assert!(record.target.starts_with("dag_compute"));
+        assert_eq!(record.level, Level::Info);
+    }
+}
@@ -0,0 +1,51 @@
+use dag_compute::{ComputationGraph, SizeHint};
+
+use std::mem::size_of;
+
+struct Blob(usize);
+impl SizeHint for Blob {
+    fn approx_bytes(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn test_default_value_sizes() {
+    let mut graph = ComputationGraph::<u64>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let b = graph.insert_node("b", Box::new(|_| 2));
+    let mut sum = graph.insert_binary_node("sum", |x, y| x + y);
+    graph.set_inputs(&mut sum, &[&a, &b]);
+    graph.designate_output(&sum);
+
+    let estimate = graph.estimated_memory();
+    // Both inputs are still held while the sum is computed
+    assert_eq!(estimate.peak_live_values, 3);
+    assert_eq!(estimate.peak_value_bytes, 3 * size_of::<u64>());
+    assert_eq!(estimate.peak_node, Some(graph.node_id(&sum)));
+    assert!(estimate.graph_bytes > 0);
+    assert_eq!(estimate.total_bytes(), estimate.graph_bytes + estimate.peak_value_bytes);
+}
+
+#[test]
+fn test_profiled_value_sizes() {
+    let mut graph = ComputationGraph::<Blob>::new();
+    let load = graph.insert_node("load", Box::new(|_| Blob(1000)));
+    let mut shrink = graph.insert_node("shrink", Box::new(|x| Blob(x[0].0 / 10)));
+    graph.set_inputs(&mut shrink, &[&load]);
+    let mut expand = graph.insert_node("expand", Box::new(|x| Blob(x[0].0 * 3)));
+    graph.set_inputs(&mut expand, &[&shrink]);
+    graph.designate_output(&expand);
+
+    let (_, report) = graph.compute_profiled_sized();
+    graph.apply_profile(&report);
+    let estimate = graph.estimated_memory();
+    // The loaded value is dropped before the expanded one is produced
+    assert_eq!(estimate.peak_value_bytes, 1100);
+    assert_eq!(estimate.peak_live_values, 2);
+    assert_eq!(estimate.peak_node, Some(graph.node_id(&shrink)));
+
+    let graph_bytes = estimate.graph_bytes;
+    assert!(graph_bytes > size_of::<ComputationGraph<Blob>>());
+    assert!(ComputationGraph::<Blob>::new().estimated_memory().graph_bytes < graph_bytes);
+}
@@ -0,0 +1,45 @@
+use dag_compute::{ComputationGraph, NodeHandle};
+
+use std::thread;
+
+// Builds a chain computing start + length - 1
+fn build_chain(start: u64, length: usize) -> (ComputationGraph<u64>, NodeHandle) {
+    let mut graph = ComputationGraph::new();
+    let mut prev = graph.insert_node("start".to_owned(), Box::new(move |_| start));
+    for i in 1..length {
+        let mut next = graph.insert_node(format!("step{}", i), Box::new(|x| x[0] + 1));
+        graph.set_inputs(&mut next, &[&prev]);
+        prev = next;
+    }
+    graph.designate_output(&prev);
+    (graph, prev)
+}
+
+#[test]
+fn test_parallel_construction() {
+    let shards: Vec<_> = (0..4)
+        .map(|shard| thread::spawn(move || build_chain(shard * 1000, 1000)))
+        .collect();
+    let mut graph = ComputationGraph::<u64>::new();
+    let mut ends = Vec::new();
+    for shard in shards {
+        let (shard_graph, end) = shard.join().unwrap();
+        let merged = graph.merge(shard_graph);
+        ends.push(merged.translate(&end));
+    }
+    let mut total = graph.insert_node("total".to_owned(),
+        Box::new(|x| x.iter().copied().sum()));
+    let end_refs: Vec<_> = ends.iter().collect();
+    graph.set_inputs(&mut total, &end_refs);
+    graph.designate_output(&total);
+    assert_eq!(graph.compute(), 999 + 1999 + 2999 + 3999);
+}
+
+#[test]
+#[should_panic(expected = "Received NodeHandle for different graph")]
+fn merged_handle_from_other_graph() {
+    let (shard_graph, _) = build_chain(0, 2);
+    let (_, other_end) = build_chain(0, 2);
+    let mut graph = ComputationGraph::<u64>::new();
+    graph.merge(shard_graph).translate(&other_end);
+}
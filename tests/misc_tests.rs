@@ -1,4 +1,6 @@
-use dag_compute::ComputationGraph;
+use dag_compute::{ComputationGraph, SweepPolicy};
+
+use std::sync::{Arc, Mutex};
 
 #[test]
 fn test_add_basic() {
@@ -67,4 +69,391 @@ fn cycle_loop() {
     graph.set_inputs(&mut handle_2, &[&handle_1]);
     graph.designate_output(&handle_1);
     graph.compute();
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Reading {
+    celsius: f64
+}
+impl From<Reading> for f64 {
+    fn from(reading: Reading) -> f64 {
+        reading.celsius
+    }
+}
+
+#[test]
+fn test_placeholder_adapter() {
+    let mut graph = ComputationGraph::<f64>::new();
+    let reading_a = graph.insert_placeholder("reading_a".to_owned());
+    let reading_b = graph.insert_placeholder("reading_b".to_owned());
+    let mut mean = graph.insert_node(
+        "mean".to_owned(),
+        Box::new(|x| (x[0] + x[1]) / 2.0)
+    );
+    graph.set_inputs(&mut mean, &[&reading_a, &reading_b]);
+    graph.designate_output(&mean);
+    let out = graph.compute_with([
+        (&reading_a, Reading { celsius: 20.0 }),
+        (&reading_b, Reading { celsius: 22.0 })
+    ]);
+    assert_eq!(out, 21.0);
+}
+
+#[test]
+#[should_panic(expected = "Node input was not given a value")]
+fn placeholder_missing_value() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input".to_owned());
+    graph.designate_output(&input);
+    graph.compute();
+}
+
+#[test]
+fn test_multi_output() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node(
+        "const".to_owned(),
+        Box::new(|_| 17)
+    );
+    let (mut divmod, outputs) = graph.insert_multi_output_node(
+        "divmod".to_owned(),
+        2,
+        Box::new(|x| vec![x[0] / 5, x[0] % 5])
+    );
+    graph.set_inputs(&mut divmod, &[&src]);
+    assert_eq!(graph.node_name(&outputs[1]), "divmod.1");
+    let mut combine = graph.insert_node(
+        "combine".to_owned(),
+        Box::new(|x| x[0] * 10 + x[1])
+    );
+    graph.set_inputs(&mut combine, &[&outputs[0], &outputs[1]]);
+    graph.designate_output(&combine);
+    assert_eq!(graph.compute(), 32);
+}
+
+#[test]
+fn test_multi_output_partial_use() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let (_, outputs) = graph.insert_multi_output_node(
+        "pair".to_owned(),
+        2,
+        Box::new(|_| vec![1, 2])
+    );
+    graph.designate_output(&outputs[1]);
+    assert_eq!(graph.compute(), 2);
+}
+
+#[test]
+fn test_prune() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 1));
+    let mut keep = graph.insert_node("keep".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut keep, &[&src]);
+    // A dead chain, to check that sweeping a node whose input is also swept works
+    let dead_src = graph.insert_node("dead_src".to_owned(), Box::new(|_| 2));
+    let mut dead = graph.insert_node("dead".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut dead, &[&dead_src, &keep]);
+    graph.designate_output(&keep);
+    assert_eq!(graph.prune(), 2);
+    assert_eq!(graph.prune(), 0);
+    assert!(!graph.dot_graph().to_string().contains("dead"));
+    assert_eq!(graph.compute(), 2);
+}
+
+#[test]
+fn test_deep_chain() {
+    // Deep enough to overflow the stack with a recursive toposort
+    let mut graph = ComputationGraph::<u32>::new();
+    let mut prev = graph.insert_node("node0".to_owned(), Box::new(|_| 0));
+    for i in 1..50_000 {
+        let mut next = graph.insert_node(format!("node{}", i), Box::new(|x| x[0] + 1));
+        graph.set_inputs(&mut next, &[&prev]);
+        prev = next;
+    }
+    graph.designate_output(&prev);
+    assert_eq!(graph.compute_iterations(1), vec![49_999]);
+    // Sweeping the unused half must not be quadratic either
+    let unused = graph.insert_node("unused".to_owned(), Box::new(|_| 0));
+    let mut prev_unused = unused;
+    for i in 0..50_000 {
+        let mut next = graph.insert_node(format!("unused{}", i), Box::new(|x| x[0] + 1));
+        graph.set_inputs(&mut next, &[&prev_unused]);
+        prev_unused = next;
+    }
+    assert_eq!(graph.prune(), 50_001);
+    assert_eq!(graph.compute(), 49_999);
+}
+
+#[test]
+fn test_write_dot() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a \"quoted\"".to_owned(), Box::new(|_| 1));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 2));
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut sum, &[&a, &b]);
+    graph.designate_output(&sum);
+
+    let mut streamed = Vec::new();
+    graph.write_dot(&mut streamed).unwrap();
+    let streamed = String::from_utf8(streamed).unwrap();
+    assert!(streamed.contains(r#"[label="a \"quoted\""];"#));
+    assert!(streamed.contains(&format!("{} [label=\"sum\", shape=box];",
+        graph.node_id(&sum))));
+    // Same statements as the buffered DOT output, possibly in another order
+    let mut streamed_lines: Vec<_> = streamed.lines().collect();
+    let buffered = graph.dot_graph().to_string();
+    let mut buffered_lines: Vec<_> = buffered.lines().collect();
+    streamed_lines.sort_unstable();
+    buffered_lines.sort_unstable();
+    assert_eq!(streamed_lines, buffered_lines);
+}
+
+#[test]
+fn test_dot_reachability() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 1));
+    let mut keep = graph.insert_node("keep".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut keep, &[&src]);
+    let mut dead = graph.insert_node("dead".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut dead, &[&src]);
+    graph.designate_output(&keep);
+
+    let dot = graph.dot_graph_reachability().to_string();
+    let (src_id, keep_id, dead_id) = (graph.node_id(&src), graph.node_id(&keep),
+        graph.node_id(&dead));
+    assert!(dot.contains(&format!("{} [label=\"src\"];", src_id)));
+    assert!(dot.contains(&format!("{} [label=\"keep\", shape=box];", keep_id)));
+    assert!(dot.contains(&format!(
+        "{} [label=\"dead\", style=dashed, color=grey, fontcolor=grey];", dead_id)));
+    assert!(dot.contains(&format!("{}->{};", src_id, keep_id)));
+    assert!(dot.contains(&format!(
+        "{}->{} [style=dashed, color=grey, fontcolor=grey];", src_id, dead_id)));
+    // Nothing is swept until computation
+    assert!(graph.dot_graph().to_string().contains("dead"));
+    assert_eq!(graph.compute(), 2);
+}
+
+#[test]
+fn test_sink_nodes() {
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 3));
+    let mut out = graph.insert_node("out".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut out, &[&src]);
+    let sink_written = written.clone();
+    let mut sink = graph.insert_node("sink".to_owned(), Box::new(move |x| {
+        sink_written.lock().unwrap().push(*x[0]);
+        0
+    }));
+    graph.set_inputs(&mut sink, &[&src]);
+    let _dead = graph.insert_node("dead".to_owned(), Box::new(|_| 0));
+    graph.designate_output(&out);
+    graph.add_sink(&sink);
+
+    assert_eq!(graph.prune(), 1);
+    assert!(graph.dot_graph().to_string().contains("sink"));
+    assert_eq!(graph.compute(), 4);
+    assert_eq!(*written.lock().unwrap(), vec![3]);
+}
+
+#[test]
+#[should_panic(expected = "Nodes not reachable from the output: dead")]
+fn test_sweep_policy_fail() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 2));
+    let mut dead = graph.insert_unary_node("dead", |x| x * 2);
+    graph.set_inputs(&mut dead, &[&a]);
+    graph.designate_output(&a);
+    graph.set_sweep_policy(SweepPolicy::Fail);
+    graph.compute();
+}
+
+#[test]
+fn test_generated_names() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let first = graph.insert_node_anon(Box::new(|_| 1));
+    let mut second = graph.insert_node_anon(Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut second, &[&first]);
+    assert_eq!(graph.node_name(&first), "node_0");
+    assert_eq!(graph.node_name(&second), "node_1");
+
+    assert_eq!(&*graph.generate_name("stage_{n}"), "stage_0");
+    assert_eq!(&*graph.generate_name("stage_{n}"), "stage_1");
+    // Names generated from another template are skipped
+    assert_eq!(&*graph.generate_name("stage"), "stage_2");
+    assert_eq!(&*graph.generate_name("stage_{n}"), "stage_3");
+    assert_eq!(&*graph.generate_name("stage_{n}_{n}"), "stage_0_0");
+    let name = graph.generate_name("sum_{n}");
+    let mut sum = graph.insert_binary_node(name, |x, y| x + y);
+    graph.set_inputs(&mut sum, &[&first, &second]);
+    assert_eq!(graph.node_name(&sum), "sum_0");
+    graph.designate_output(&sum);
+    assert_eq!(graph.compute(), 3);
+}
+
+#[test]
+fn test_deduplication() {
+    let calls = Arc::new(Mutex::new(0));
+    let mut graph = ComputationGraph::<i32>::new();
+    graph.set_deduplication(true);
+    let mut src = graph.insert_node("src".to_owned(), Box::new(|_| 2));
+    graph.set_inputs(&mut src, &[]);
+    let mut src_again = graph.insert_node("src".to_owned(), Box::new(|_| 2));
+    graph.set_inputs(&mut src_again, &[]);
+    assert_eq!(graph.node_id(&src), graph.node_id(&src_again));
+
+    let mut handles = Vec::new();
+    for _ in 0..2 {
+        let expensive_calls = calls.clone();
+        let mut expensive = graph.insert_node("expensive".to_owned(), Box::new(move |x| {
+            *expensive_calls.lock().unwrap() += 1;
+            x[0] * 10
+        }));
+        graph.set_inputs(&mut expensive, &[&src]);
+        handles.push(expensive);
+    }
+    assert_eq!(graph.node_id(&handles[0]), graph.node_id(&handles[1]));
+    // Different function pointers or inputs are kept apart
+    let mut double = graph.insert_unary_node("op", |x| x * 2);
+    graph.set_inputs(&mut double, &[&handles[0]]);
+    let mut triple = graph.insert_unary_node("op", |x| x * 3);
+    graph.set_inputs(&mut triple, &[&handles[0]]);
+    assert_ne!(graph.node_id(&double), graph.node_id(&triple));
+    let mut sum = graph.insert_binary_node("sum", |x, y| x + y);
+    graph.set_inputs(&mut sum, &[&handles[0], &handles[1]]);
+    let mut total = graph.insert_binary_node("sum", |x, y| x + y);
+    graph.set_inputs(&mut total, &[&double, &triple]);
+    assert_ne!(graph.node_id(&sum), graph.node_id(&total));
+    let mut out = graph.insert_binary_node("out", |x, y| x + y);
+    graph.set_inputs(&mut out, &[&sum, &total]);
+    graph.designate_output(&out);
+
+    assert_eq!(graph.compute(), 140);
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+fn build_hashed_graph(reverse: bool, sub_name: &str) -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let (a, b) = if reverse {
+        let b = graph.insert_node("b".to_owned(), Box::new(|_| 2));
+        (graph.insert_node("a".to_owned(), Box::new(|_| 1)), b)
+    } else {
+        let a = graph.insert_node("a".to_owned(), Box::new(|_| 1));
+        (a, graph.insert_node("b".to_owned(), Box::new(|_| 2)))
+    };
+    let mut sub = graph.insert_binary_node(sub_name, |x, y| x - y);
+    graph.set_inputs(&mut sub, &[&a, &b]);
+    graph.designate_output(&sub);
+    graph
+}
+
+#[test]
+fn test_topology_hash() {
+    let hash = build_hashed_graph(false, "sub").topology_hash();
+    assert_eq!(build_hashed_graph(true, "sub").topology_hash(), hash);
+    assert_ne!(build_hashed_graph(false, "diff").topology_hash(), hash);
+
+    // Swapping the inputs changes the structure
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 1));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 2));
+    let mut sub = graph.insert_binary_node("sub", |x, y| x - y);
+    graph.set_inputs(&mut sub, &[&b, &a]);
+    graph.designate_output(&sub);
+    assert_ne!(graph.topology_hash(), hash);
+
+    let mut graph = build_hashed_graph(false, "sub");
+    let unused = graph.insert_placeholder("unused");
+    let with_unused = graph.topology_hash();
+    assert_ne!(with_unused, hash);
+    graph.add_sink(&unused);
+    assert_ne!(graph.topology_hash(), with_unused);
+}
+
+#[test]
+fn test_shrink_to_fit() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let mut name = String::with_capacity(64);
+    name.push('a');
+    let a = graph.insert_node(name, Box::new(|_| 1));
+    for i in 0..100 {
+        graph.insert_node(format!("dead{}", i), Box::new(|_| 0));
+    }
+    let inputs: Vec<_> = (0..8)
+        .map(|i| graph.insert_node(format!("in{}", i), Box::new(move |_| i)))
+        .collect();
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x.iter().copied().sum()));
+    let input_refs: Vec<_> = inputs.iter().chain(std::iter::once(&a)).collect();
+    graph.set_inputs(&mut sum, &input_refs);
+    graph.designate_output(&sum);
+    assert_eq!(graph.prune(), 100);
+    graph.shrink_to_fit();
+    assert_eq!(graph.node_name(&a), "a");
+    assert_eq!(graph.compute(), 29);
+}
+
+#[test]
+fn test_interned_names() {
+    let mut interner = dag_compute::NameInterner::new();
+    let mut graph = ComputationGraph::<i32>::new();
+    let stem = interner.intern("stem");
+    let a = graph.insert_node(stem.clone(), Box::new(|_| 1));
+    let mut b = graph.insert_node(interner.intern("stem"), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut b, &[&a]);
+    graph.designate_output(&b);
+    assert_eq!(interner.len(), 1);
+    assert!(std::ptr::eq(graph.node_name(&a), graph.node_name(&b)));
+    assert_eq!(graph.node_name(&b), "stem");
+    assert_eq!(graph.compute(), 2);
+}
+
+#[test]
+fn test_fn_pointer_nodes() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 3));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 4));
+    let mut neg = graph.insert_unary_node("neg", |x| -x);
+    graph.set_inputs(&mut neg, &[&a]);
+    let mut mul = graph.insert_binary_node("mul", |x, y| x * y);
+    graph.set_inputs(&mut mul, &[&neg, &b]);
+    graph.designate_output(&mul);
+    assert_eq!(graph.compute(), -12);
+}
+
+#[test]
+#[should_panic(expected = "Node add expected 2 inputs but received 1")]
+fn test_binary_node_arity() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 3));
+    let mut add = graph.insert_binary_node("add", |x, y| x + y);
+    graph.set_inputs(&mut add, &[&a]);
+    graph.designate_output(&add);
+    graph.compute();
+}
+
+#[test]
+fn test_chain_with_branches() {
+    // A chain whose middle value is also read by a node at the end
+    let mut graph = ComputationGraph::<i64>::new();
+    let start = graph.insert_node("start".to_owned(), Box::new(|_| 1));
+    let mut prev = start;
+    let mut middle = None;
+    for i in 0..100 {
+        let mut next = graph.insert_unary_node(format!("double{}", i), |x| x * 2 % 1_000_003);
+        graph.set_inputs(&mut next, &[&prev]);
+        if i == 50 {
+            middle = Some(std::mem::replace(&mut prev, next));
+        } else {
+            prev = next;
+        }
+    }
+    let mut diff = graph.insert_binary_node("diff", |x, y| x - y);
+    graph.set_inputs(&mut diff, &[&prev, middle.as_ref().unwrap()]);
+    graph.designate_output(&diff);
+
+    let mut expected = [1i64; 101];
+    for i in 1..101 {
+        expected[i] = expected[i - 1] * 2 % 1_000_003;
+    }
+    assert_eq!(graph.compute_traced().provenance.len(), 102);
+    assert_eq!(graph.compute(), expected[100] - expected[50]);
+}
@@ -0,0 +1,89 @@
+use dag_compute::ComputationGraph;
+
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_namespace_lookup() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let resample = graph.insert_node("preprocess/resample".to_owned(), Box::new(|_| 1));
+    let filter = graph.insert_node("preprocess/resample/filter".to_owned(), Box::new(|_| 2));
+    let _other = graph.insert_node("preprocessing/other".to_owned(), Box::new(|_| 3));
+    let _root = graph.insert_node("preprocess".to_owned(), Box::new(|_| 4));
+
+    let names = |namespace: &str| -> Vec<String> {
+        graph.nodes_in_namespace(namespace).iter()
+            .map(|handle| graph.node_name(handle).to_owned())
+            .collect()
+    };
+    assert_eq!(names("preprocess"), ["preprocess/resample", "preprocess/resample/filter"]);
+    assert_eq!(names("preprocess/resample/"), ["preprocess/resample/filter"]);
+    assert!(names("resample").is_empty());
+    assert_eq!(graph.node_id(&graph.nodes_in_namespace("preprocess")[0]), graph.node_id(&resample));
+    assert_eq!(graph.node_id(&graph.nodes_in_namespace("preprocess/resample")[0]),
+        graph.node_id(&filter));
+}
+
+#[test]
+fn test_disabled_namespace() {
+    let written = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("main/src".to_owned(), Box::new(|_| 3));
+    let diag_written = written.clone();
+    let mut diag = graph.insert_node("debug/write".to_owned(), Box::new(move |x| {
+        diag_written.lock().unwrap().push(*x[0]);
+        0
+    }));
+    graph.set_inputs(&mut diag, &[&src]);
+    graph.designate_output(&src);
+    graph.add_sink(&diag);
+
+    graph.set_namespace_enabled("debug", false);
+    assert!(!graph.is_namespace_enabled("debug/nested"));
+    assert!(graph.is_namespace_enabled("main"));
+    assert_eq!(graph.compute_iterations(1), vec![3]);
+    assert!(written.lock().unwrap().is_empty());
+    assert_eq!(graph.prune(), 0);
+
+    graph.set_namespace_enabled("debug/", true);
+    assert_eq!(graph.compute_iterations(1), vec![3]);
+    assert_eq!(*written.lock().unwrap(), vec![3]);
+}
+
+#[test]
+#[should_panic(expected = "Node main/src is in a disabled namespace")]
+fn test_disabled_dependency() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("main/src".to_owned(), Box::new(|_| 3));
+    let mut out = graph.insert_unary_node("out", |x| x + 1);
+    graph.set_inputs(&mut out, &[&src]);
+    graph.designate_output(&out);
+    graph.set_namespace_enabled("main", false);
+    graph.compute();
+}
+
+#[test]
+fn test_clustered_dot() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("pre/a".to_owned(), Box::new(|_| 1));
+    let mut b = graph.insert_node("pre/inner/b".to_owned(), Box::new(|x| *x[0]));
+    graph.set_inputs(&mut b, &[&a]);
+    let mut out = graph.insert_node("out".to_owned(), Box::new(|x| *x[0]));
+    graph.set_inputs(&mut out, &[&b]);
+    graph.designate_output(&out);
+
+    let dot = graph.dot_graph_clustered().to_string();
+    let (a_id, b_id, out_id) = (graph.node_id(&a), graph.node_id(&b), graph.node_id(&out));
+    let expected = format!("strict digraph {{\n\
+        {out_id} [label=\"out\", shape=box];\n\
+        subgraph cluster_0 {{\n\
+        label=\"pre\";\n\
+        {a_id} [label=\"pre/a\"];\n\
+        subgraph cluster_1 {{\n\
+        label=\"inner\";\n\
+        {b_id} [label=\"pre/inner/b\"];\n\
+        }}\n\
+        }}\n");
+    assert!(dot.starts_with(&expected), "{}", dot);
+    assert!(dot.contains(&format!("{}->{};", a_id, b_id)));
+    assert!(dot.contains(&format!("{}->{};", b_id, out_id)));
+}
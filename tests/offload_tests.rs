@@ -0,0 +1,52 @@
+use dag_compute::{ComputationGraph, OffloadExecutor};
+
+use std::sync::{Arc, Mutex};
+
+// Pretends to run nodes on a device by negating the sum of the inputs
+struct FakeDevice {
+    calls: Arc<Mutex<Vec<(String, String)>>>
+}
+impl OffloadExecutor<i32> for FakeDevice {
+    fn execute(&self, device: &str, node_name: &str, inputs: &[&i32]) -> i32 {
+        self.calls.lock().unwrap().push((device.to_owned(), node_name.to_owned()));
+        -inputs.iter().copied().sum::<i32>()
+    }
+}
+
+fn placed_graph() -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 2));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 3));
+    let mut kernel = graph.insert_node("kernel".to_owned(), Box::new(|x| x[0] * x[1]));
+    graph.set_inputs(&mut kernel, &[&a, &b]);
+    graph.set_device(&kernel, "gpu0".to_owned());
+    assert_eq!(graph.device(&kernel), Some("gpu0"));
+    assert_eq!(graph.device(&a), None);
+    let mut out = graph.insert_node("out".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut out, &[&kernel]);
+    graph.designate_output(&out);
+    graph
+}
+
+#[test]
+fn test_offload() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = placed_graph();
+    graph.set_offload_executor(FakeDevice { calls: calls.clone() });
+    assert_eq!(graph.compute_iterations(2), vec![-4, -4]);
+    assert_eq!(graph.compute(), -4);
+    assert_eq!(*calls.lock().unwrap(), vec![("gpu0".to_owned(), "kernel".to_owned()); 3]);
+}
+
+#[test]
+fn test_offload_without_executor() {
+    assert_eq!(placed_graph().compute(), 7);
+}
+
+#[test]
+#[should_panic(expected = "cannot be placed on a device")]
+fn placeholder_on_device() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input".to_owned());
+    graph.set_device(&input, "gpu0".to_owned());
+}
@@ -0,0 +1,116 @@
+use dag_compute::ComputationGraph;
+
+use std::panic;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_override_node() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = ComputationGraph::<i32>::new();
+    let fetch_calls = calls.clone();
+    let fetch = graph.insert_node("fetch", Box::new(move |_| {
+        fetch_calls.lock().unwrap().push("fetch");
+        100
+    }));
+    let mut scale = graph.insert_unary_node("scale", |x| x * 2);
+    graph.set_inputs(&mut scale, &[&fetch]);
+    let mut offset = graph.insert_node("offset", Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut offset, &[&scale]);
+    graph.designate_output(&offset);
+
+    graph.override_node(&fetch, |_| 5);
+    assert_eq!(graph.compute_profiled().0, 11);
+    // Stubs receive the real inputs, including within chains
+    graph.override_node(&scale, |x| x[0] * 3);
+    assert_eq!(graph.compute_profiled().0, 16);
+    assert!(calls.lock().unwrap().is_empty());
+
+    assert!(graph.remove_override(&fetch));
+    assert!(!graph.remove_override(&fetch));
+    assert_eq!(graph.compute_profiled().0, 301);
+    graph.clear_overrides();
+    assert_eq!(graph.compute(), 201);
+    assert_eq!(*calls.lock().unwrap(), ["fetch", "fetch"]);
+}
+
+#[test]
+fn test_scoped_override() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let mut b = graph.insert_node("b", Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut b, &[&a]);
+    graph.designate_output(&b);
+
+    graph.override_node(&a, |_| 10);
+    let value = graph.with_override(&a, |_| 20, |graph| graph.compute_profiled().0);
+    assert_eq!(value, 21);
+    // The previous override is restored
+    assert_eq!(graph.compute_profiled().0, 11);
+    graph.remove_override(&a);
+    let value = graph.with_override(&b, |x| x[0] * 100, |graph| graph.compute_profiled().0);
+    assert_eq!(value, 100);
+    assert_eq!(graph.compute(), 2);
+}
+
+#[test]
+fn test_override_by_name() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let first = graph.insert_node("load", Box::new(|_| 1));
+    let second = graph.insert_node("load", Box::new(|_| 2));
+    let mut sum = graph.insert_node("sum", Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut sum, &[&first, &second]);
+    graph.designate_output(&sum);
+    assert_eq!(graph.override_nodes_named("load", |_| 7), 2);
+    assert_eq!(graph.override_nodes_named("missing", |_| 0), 0);
+    assert_eq!(graph.compute(), 14);
+}
+
+#[test]
+#[should_panic(expected = "Node input cannot be overridden")]
+fn test_override_placeholder() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    graph.override_node(&input, |_| 0);
+}
+
+#[test]
+fn test_compute_stubbed() {
+    let calls = Arc::new(Mutex::new(0));
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    let fetch_calls = calls.clone();
+    let mut fetch = graph.insert_node("fetch", Box::new(move |x| {
+        *fetch_calls.lock().unwrap() += 1;
+        x[0] * 100
+    }));
+    graph.set_inputs(&mut fetch, &[&input]);
+    let mut train = graph.insert_unary_node("train", |x| x + 1);
+    graph.set_inputs(&mut train, &[&fetch]);
+    let mut report = graph.insert_binary_node("report", |x, y| x * y);
+    graph.set_inputs(&mut report, &[&train, &input]);
+    graph.designate_output(&report);
+
+    // The stubbed node and the branch only it needs are skipped
+    assert_eq!(graph.compute_stubbed_with([(&fetch, 7)], [(&input, 2)]), 16);
+    assert_eq!(graph.compute_stubbed_with([(&train, 5)], [(&input, 3)]), 15);
+    assert_eq!(graph.compute_stubbed([(&report, 9)]), 9);
+    assert_eq!(*calls.lock().unwrap(), 0);
+
+    // The graph itself is unchanged
+    assert_eq!(graph.compute_stubbed_with([], [(&input, 1)]), 101);
+    assert_eq!(*calls.lock().unwrap(), 1);
+}
+
+#[test]
+fn test_scoped_override_panic() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    graph.designate_output(&a);
+    graph.override_node(&a, |_| 10);
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        graph.with_override(&a, |_| 20, |_| panic!("Scoped run failed"))
+    }));
+    assert!(result.is_err());
+    assert_eq!(graph.compute_profiled().0, 10);
+}
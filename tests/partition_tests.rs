@@ -0,0 +1,259 @@
+use dag_compute::{ComputationGraph, PartitionPlan, PlanNode, RemoteExecutor, SubPlan};
+
+use std::collections::{HashMap, HashSet};
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+// Evaluates sub-plans in-process, recording the order of submissions
+struct LocalExecutor<'a> {
+    graph: &'a ComputationGraph<i32>,
+    submitted: Mutex<Vec<usize>>,
+    failing_partition: Option<usize>
+}
+impl<'a> RemoteExecutor<i32> for LocalExecutor<'a> {
+    type Error = String;
+    type Future = Ready<Result<HashMap<u64, i32>, String>>;
+    fn submit(&self, subplan: &SubPlan, inputs: HashMap<u64, i32>) -> Self::Future {
+        self.submitted.lock().unwrap().push(subplan.index);
+        if self.failing_partition == Some(subplan.index) {
+            return ready(Err(format!("partition {} failed", subplan.index)));
+        }
+        ready(Ok(self.graph.execute_subplan(subplan, inputs)))
+    }
+}
+
+fn diamond_graph() -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let source = graph.insert_node("source".to_owned(), Box::new(|_| 3));
+    let (mut split, outputs) = graph.insert_multi_output_node("split".to_owned(), 2,
+        Box::new(|x| vec![x[0] + 1, x[0] * 2]));
+    graph.set_inputs(&mut split, &[&source]);
+    let mut left = graph.insert_node("left".to_owned(), Box::new(|x| x[0] * 10));
+    graph.set_inputs(&mut left, &[&outputs[0]]);
+    let mut right = graph.insert_node("right".to_owned(), Box::new(|x| x[0] - 1));
+    graph.set_inputs(&mut right, &[&outputs[1]]);
+    let mut join = graph.insert_node("join".to_owned(), Box::new(|x| x[0] + x[1] + x[2]));
+    graph.set_inputs(&mut join, &[&left, &right, &source]);
+    graph.designate_output(&join);
+    graph
+}
+
+#[test]
+fn test_partition() {
+    // Two independent chains joined at the end
+    let mut graph = ComputationGraph::<i32>::new();
+    let mut prev_a = graph.insert_node("a0".to_owned(), Box::new(|_| 1));
+    for i in 1..4 {
+        let mut next = graph.insert_node(format!("a{}", i), Box::new(|x| x[0] + 1));
+        graph.set_inputs(&mut next, &[&prev_a]);
+        prev_a = next;
+    }
+    let mut prev_b = graph.insert_node("b0".to_owned(), Box::new(|_| 1));
+    for i in 1..4 {
+        let mut next = graph.insert_node(format!("b{}", i), Box::new(|x| x[0] * 2));
+        graph.set_inputs(&mut next, &[&prev_b]);
+        prev_b = next;
+    }
+    let mut join = graph.insert_node("join".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut join, &[&prev_a, &prev_b]);
+    graph.designate_output(&join);
+
+    let plan = graph.partition(2);
+    assert_eq!(plan.partitions.len(), 2);
+    let all_nodes: HashSet<_> = plan.partitions.iter()
+        .flat_map(|partition| partition.nodes.iter().map(|node| node.id))
+        .collect();
+    assert_eq!(all_nodes.len(), 9);
+    // A contiguous split puts chain a and b0 in the first partition, so
+    // both a3 and b0 would cross over, until b0 is moved to its consumers
+    let first_names: Vec<_> = plan.partitions[0].nodes.iter()
+        .map(|node| node.name.as_str())
+        .collect();
+    assert_eq!(first_names, ["a0", "a1", "a2", "a3"]);
+    assert_eq!(plan.dependencies.len(), 1);
+    assert_eq!(plan.partitions[1].inputs, vec![plan.partitions[0].nodes[3].id]);
+    for dep in plan.dependencies.iter() {
+        assert!(dep.from_partition < dep.to_partition);
+        assert!(plan.partitions[dep.from_partition].outputs.contains(&dep.node));
+        assert!(plan.partitions[dep.to_partition].inputs.contains(&dep.node));
+    }
+    assert_eq!(plan.partitions[1].outputs, vec![plan.output]);
+}
+
+#[test]
+fn test_partition_count_clamped() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let node = graph.insert_node("only".to_owned(), Box::new(|_| 1));
+    graph.designate_output(&node);
+    let plan = graph.partition(4);
+    assert_eq!(plan.partitions.len(), 1);
+    assert!(plan.dependencies.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_partition_serde_roundtrip() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 1));
+    let mut b = graph.insert_node("b".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut b, &[&a]);
+    graph.designate_output(&b);
+    let plan = graph.partition(2);
+    let json = serde_json::to_string(&plan).unwrap();
+    let parsed: dag_compute::PartitionPlan = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, plan);
+}
+
+#[test]
+fn test_execute_remote() {
+    let graph = diamond_graph();
+    for partition_count in 1..=7 {
+        let plan = graph.partition(partition_count);
+        let executor = LocalExecutor {
+            graph: &graph,
+            submitted: Mutex::new(Vec::new()),
+            failing_partition: None
+        };
+        assert_eq!(plan.execute_remote(&executor), Ok(40 + 5 + 3));
+        let mut submitted = executor.submitted.into_inner().unwrap();
+        submitted.sort_unstable();
+        assert_eq!(submitted, (0..plan.partitions.len()).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test_execute_remote_error() {
+    let graph = diamond_graph();
+    let plan = graph.partition(3);
+    let executor = LocalExecutor {
+        graph: &graph,
+        submitted: Mutex::new(Vec::new()),
+        failing_partition: Some(1)
+    };
+    assert_eq!(plan.execute_remote(&executor), Err("partition 1 failed".to_owned()));
+    // Partitions depending on the failed one are never submitted
+    assert!(!executor.submitted.into_inner().unwrap().contains(&2));
+}
+
+// Resolves once `released` holds, polling again right away until then, or
+// fails if it is still held after many polls
+struct GatedFuture {
+    released: Box<dyn Fn() -> bool>,
+    polls: usize,
+    result: Option<HashMap<u64, i32>>
+}
+impl Future for GatedFuture {
+    type Output = Result<HashMap<u64, i32>, String>;
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Self::Output> {
+        if (self.released)() {
+            return Poll::Ready(Ok(self.result.take().unwrap()));
+        }
+        self.polls += 1;
+        if self.polls > 10_000 {
+            return Poll::Ready(Err("held partition was never released".to_owned()));
+        }
+        context.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+// Evaluates sub-plans in-process, holding back the result of one partition
+// until another one has been submitted
+struct GatedExecutor<'a> {
+    graph: &'a ComputationGraph<i32>,
+    submitted: Arc<Mutex<Vec<usize>>>,
+    held_partition: usize,
+    released_by: usize
+}
+impl<'a> RemoteExecutor<i32> for GatedExecutor<'a> {
+    type Error = String;
+    type Future = GatedFuture;
+    fn submit(&self, subplan: &SubPlan, inputs: HashMap<u64, i32>) -> Self::Future {
+        self.submitted.lock().unwrap().push(subplan.index);
+        let (submitted, released_by) = (self.submitted.clone(), self.released_by);
+        let held = subplan.index == self.held_partition;
+        GatedFuture {
+            released: Box::new(move || {
+                !held || submitted.lock().unwrap().contains(&released_by)
+            }),
+            polls: 0,
+            result: Some(self.graph.execute_subplan(subplan, inputs))
+        }
+    }
+}
+
+#[test]
+fn test_execute_remote_submits_when_inputs_ready() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let slow = graph.insert_node("slow".to_owned(), Box::new(|_| 10));
+    let fast = graph.insert_node("fast".to_owned(), Box::new(|_| 1));
+    let mut fast_next = graph.insert_node("fast_next".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut fast_next, &[&fast]);
+    let mut join = graph.insert_node("join".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut join, &[&slow, &fast_next]);
+    graph.designate_output(&join);
+
+    let plan_node = |node, inputs: &[u64]| PlanNode {
+        id: graph.node_id(node),
+        name: graph.node_name(node).to_owned(),
+        inputs: inputs.to_vec()
+    };
+    let (slow_id, fast_id, fast_next_id, join_id) = (graph.node_id(&slow),
+        graph.node_id(&fast), graph.node_id(&fast_next), graph.node_id(&join));
+    let subplan = |index, nodes, inputs, outputs| SubPlan { index, nodes, inputs, outputs };
+    let plan = PartitionPlan {
+        format_version: PartitionPlan::FORMAT_VERSION,
+        partitions: vec![
+            subplan(0, vec![plan_node(&slow, &[])], vec![], vec![slow_id]),
+            subplan(1, vec![plan_node(&fast, &[])], vec![], vec![fast_id]),
+            subplan(2, vec![plan_node(&fast_next, &[fast_id])], vec![fast_id],
+                vec![fast_next_id]),
+            subplan(3, vec![plan_node(&join, &[slow_id, fast_next_id])],
+                vec![slow_id, fast_next_id], vec![join_id])
+        ],
+        dependencies: Vec::new(),
+        output: join_id
+    };
+    // Partition 0 only finishes once partition 2, which does not depend on
+    // it, has been submitted
+    let executor = GatedExecutor {
+        graph: &graph,
+        submitted: Arc::new(Mutex::new(Vec::new())),
+        held_partition: 0,
+        released_by: 2
+    };
+    assert_eq!(plan.execute_remote(&executor), Ok(12));
+    assert_eq!(*executor.submitted.lock().unwrap(), [0, 1, 2, 3]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_partition_plan_migration() {
+    let graph = diamond_graph();
+    let plan = graph.partition(2);
+    assert_eq!(plan.format_version, dag_compute::PartitionPlan::FORMAT_VERSION);
+
+    // Plans stored before the format was versioned have no version field
+    let mut json = serde_json::to_value(&plan).unwrap();
+    json.as_object_mut().unwrap().remove("format_version");
+    let mut old_plan: dag_compute::PartitionPlan = serde_json::from_value(json).unwrap();
+    assert_eq!(old_plan.format_version, 0);
+    let mut migrated_from = Vec::new();
+    old_plan.migrate_with(|_, from_version| migrated_from.push(from_version));
+    assert_eq!(migrated_from, [0]);
+    assert_eq!(old_plan, plan);
+
+    // Current plans are left unchanged
+    old_plan.migrate();
+    assert_eq!(old_plan, plan);
+}
+
+#[test]
+#[should_panic(expected = "Plan format version 99 is newer than the supported version")]
+fn test_partition_plan_from_newer_version() {
+    let mut plan = diamond_graph().partition(2);
+    plan.format_version = 99;
+    plan.migrate();
+}
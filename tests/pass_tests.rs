@@ -0,0 +1,233 @@
+use dag_compute::{ComputationGraph, GraphPass, NodeMetadata, PassReport};
+
+// Inserts a disconnected node, which is swept at computation time
+struct InsertUnused;
+impl GraphPass<i32> for InsertUnused {
+    fn name(&self) -> &str {
+        "insert_unused"
+    }
+    fn run(&self, graph: &mut ComputationGraph<i32>) -> PassReport {
+        let mut report = PassReport::new(self.name().to_owned());
+        let node = graph.insert_node("negate".to_owned(), Box::new(|x| -x[0]));
+        report.record(format!("inserted {}", graph.node_name(&node)));
+        report
+    }
+}
+struct NoOp;
+impl GraphPass<i32> for NoOp {
+    fn name(&self) -> &str {
+        "no_op"
+    }
+    fn run(&self, _: &mut ComputationGraph<i32>) -> PassReport {
+        PassReport::new(self.name().to_owned())
+    }
+}
+
+#[test]
+fn test_optimize_reports() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("const".to_owned(), Box::new(|_| 1));
+    graph.designate_output(&src);
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![Box::new(InsertUnused), Box::new(NoOp)];
+    let reports = graph.optimize(&passes);
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].pass_name(), "insert_unused");
+    assert_eq!(reports[0].changes(), &["inserted negate".to_owned()]);
+    assert!(!reports[1].changed());
+    assert_eq!(graph.compute(), 1);
+}
+
+#[test]
+fn test_cse() {
+    use dag_compute::CommonSubexpressionElimination;
+
+    // Two identical "square" chains off one source should collapse into one
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 3));
+    let other_src = graph.insert_node("other_src".to_owned(), Box::new(|_| 4));
+    let mut square_a = graph.insert_node("square".to_owned(), Box::new(|x| x[0]*x[0]));
+    graph.set_inputs(&mut square_a, &[&src]);
+    let mut square_b = graph.insert_node("square".to_owned(), Box::new(|x| x[0]*x[0]));
+    graph.set_inputs(&mut square_b, &[&src]);
+    let mut square_c = graph.insert_node("square".to_owned(), Box::new(|x| x[0]*x[0]));
+    graph.set_inputs(&mut square_c, &[&other_src]);
+    let mut inc_a = graph.insert_node("inc".to_owned(), Box::new(|x| x[0]+1));
+    graph.set_inputs(&mut inc_a, &[&square_a]);
+    let mut inc_b = graph.insert_node("inc".to_owned(), Box::new(|x| x[0]+1));
+    graph.set_inputs(&mut inc_b, &[&square_b]);
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x.iter().copied().sum()));
+    graph.set_inputs(&mut sum, &[&inc_a, &inc_b, &square_c]);
+    graph.designate_output(&sum);
+    for handle in [&square_a, &square_b, &square_c, &inc_a, &inc_b] {
+        graph.mark_pure(handle);
+    }
+
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![
+        Box::new(CommonSubexpressionElimination::new(|a: &NodeMetadata, b: &NodeMetadata| {
+            a.name == b.name
+        }))
+    ];
+    let reports = graph.optimize(&passes);
+    assert_eq!(reports[0].changes(), &[
+        "merged square into square".to_owned(),
+        "merged inc into inc".to_owned()
+    ]);
+    assert_eq!(graph.compute(), 10+10+16);
+}
+
+#[test]
+fn test_cse_keeps_distinct_nodes() {
+    use dag_compute::CommonSubexpressionElimination;
+
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 3));
+    let mut impure_a = graph.insert_node("read".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut impure_a, &[&src]);
+    let mut impure_b = graph.insert_node("read".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut impure_b, &[&src]);
+    let mut func = graph.insert_node("double".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut func, &[&src]);
+    let mut unary = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut unary, &[&src]);
+    let mut placed = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut placed, &[&src]);
+    graph.set_device(&placed, "gpu:0".to_owned());
+    let mut tapped = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut tapped, &[&src]);
+    let _receiver = graph.tap(&tapped);
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x.iter().copied().sum()));
+    graph.set_inputs(&mut sum, &[&impure_a, &impure_b, &func, &unary, &placed, &tapped]);
+    graph.designate_output(&sum);
+    for handle in [&func, &unary, &placed, &tapped] {
+        graph.mark_pure(handle);
+    }
+
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![
+        Box::new(CommonSubexpressionElimination::new(|a: &NodeMetadata, b: &NodeMetadata| {
+            a.name == b.name
+        }))
+    ];
+    let reports = graph.optimize(&passes);
+    assert!(!reports[0].changed());
+    assert_eq!(graph.compute(), 4 + 4 + 6 * 4);
+}
+
+#[test]
+fn test_constant_folding() {
+    use dag_compute::ConstantFolding;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let eval_count = Arc::new(AtomicUsize::new(0));
+    let mut graph = ComputationGraph::<i32>::new();
+    let two = graph.insert_node("two".to_owned(), Box::new(|_| 2));
+    let three = graph.insert_node("three".to_owned(), Box::new(|_| 3));
+    let counter = eval_count.clone();
+    let mut mul = graph.insert_node("mul".to_owned(), Box::new(move |x| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        x[0] * x[1]
+    }));
+    graph.set_inputs(&mut mul, &[&two, &three]);
+    let mut inc = graph.insert_node("inc".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut inc, &[&mul]);
+    let input = graph.insert_placeholder("input".to_owned());
+    let mut add = graph.insert_node("add".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut add, &[&inc, &input]);
+    graph.designate_output(&add);
+    for handle in [&two, &three, &mul, &inc] {
+        graph.mark_pure(handle);
+    }
+
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![Box::new(ConstantFolding::new())];
+    let reports = graph.optimize(&passes);
+    assert_eq!(eval_count.load(Ordering::SeqCst), 1);
+    assert_eq!(reports[0].changes().len(), 4);
+    assert_eq!(reports[0].changes()[0], "folded inc");
+    assert_eq!(graph.compute_with([(&input, 10)]), 17);
+    assert_eq!(eval_count.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_dead_node_elimination() {
+    use dag_compute::DeadNodeElimination;
+
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 1));
+    let mut dead = graph.insert_node("dead".to_owned(), Box::new(|x| *x[0]));
+    graph.set_inputs(&mut dead, &[&src]);
+    graph.designate_output(&src);
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![Box::new(DeadNodeElimination::new())];
+    let reports = graph.optimize(&passes);
+    assert_eq!(reports[0].changes(), &["removed dead".to_owned()]);
+}
+
+#[test]
+fn test_node_fusion() {
+    use dag_compute::NodeFusion;
+
+    // src -> double -> inc -> sum, with src also feeding sum directly
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 5));
+    let mut double = graph.insert_node("double".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut double, &[&src]);
+    let mut inc = graph.insert_node("inc".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut inc, &[&double]);
+    let mut negate = graph.insert_node("negate".to_owned(), Box::new(|x| -x[0]));
+    graph.set_inputs(&mut negate, &[&inc]);
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut sum, &[&negate, &src]);
+    graph.designate_output(&sum);
+
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![Box::new(NodeFusion::new())];
+    let reports = graph.optimize(&passes);
+    assert_eq!(reports[0].changes().len(), 2);
+    assert_eq!(graph.node_name(&negate), "negate");
+    assert_eq!(graph.compute(), -11 + 5);
+}
+
+#[test]
+fn test_constant_folding_keeps_tapped_nodes() {
+    use dag_compute::ConstantFolding;
+
+    let mut graph = ComputationGraph::<i32>::new();
+    let two = graph.insert_node("two".to_owned(), Box::new(|_| 2));
+    let mut double = graph.insert_node("double".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut double, &[&two]);
+    let input = graph.insert_placeholder("input".to_owned());
+    let mut add = graph.insert_node("add".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut add, &[&double, &input]);
+    graph.designate_output(&add);
+    graph.mark_pure(&two);
+    graph.mark_pure(&double);
+    let doubled = graph.tap(&double);
+
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![Box::new(ConstantFolding::new())];
+    let reports = graph.optimize(&passes);
+    assert!(reports[0].changes().is_empty());
+    assert_eq!(graph.compute_with([(&input, 10)]), 14);
+    assert_eq!(*doubled.recv().unwrap(), 4);
+}
+
+#[test]
+fn test_node_fusion_keeps_annotated_nodes() {
+    use dag_compute::NodeFusion;
+
+    // src -> double -> inc -> negate, with inc stubbed and negate hinted
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src".to_owned(), Box::new(|_| 5));
+    let mut double = graph.insert_node("double".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut double, &[&src]);
+    let mut inc = graph.insert_node("inc".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut inc, &[&double]);
+    graph.override_node(&inc, |x| x[0] + 100);
+    let mut negate = graph.insert_node("negate".to_owned(), Box::new(|x| -x[0]));
+    graph.set_inputs(&mut negate, &[&inc]);
+    graph.declare_arity(&negate, 1);
+    graph.designate_output(&negate);
+
+    let passes: Vec<Box<dyn GraphPass<i32>>> = vec![Box::new(NodeFusion::new())];
+    let reports = graph.optimize(&passes);
+    assert_eq!(reports[0].changes(), &["fused src into double".to_owned()]);
+    assert!(graph.lint().is_empty());
+    assert_eq!(graph.compute(), -110);
+}
@@ -0,0 +1,86 @@
+use dag_compute::{ComputationGraph, NodeHandle};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+fn build_stream_graph() -> (ComputationGraph<Vec<i32>>, NodeHandle) {
+    let mut graph = ComputationGraph::<Vec<i32>>::new();
+    let input = graph.insert_placeholder("input");
+    let mut running_sum = graph.insert_stateful_node("running_sum", 0,
+        |total: &mut i32, x| {
+            x[0].iter().map(|val| {
+                *total += val;
+                *total
+            }).collect()
+        });
+    graph.set_inputs(&mut running_sum, &[&input]);
+    let mut scale = graph.insert_node("scale", Box::new(|x| x[0].iter().map(|val| val * 10).collect()));
+    graph.set_inputs(&mut scale, &[&running_sum]);
+    // Uses the input again in the last stage
+    let mut offset = graph.insert_node("offset", Box::new(|x| {
+        x[0].iter().zip(x[1].iter()).map(|(a, b)| a + b).collect()
+    }));
+    graph.set_inputs(&mut offset, &[&scale, &input]);
+    graph.designate_output(&offset);
+    (graph, input)
+}
+
+#[test]
+fn test_pipeline_matches_stream() {
+    let blocks: Vec<_> = (0..20).map(|i| vec![i, i + 1]).collect();
+    let (graph, input) = build_stream_graph();
+    let expected: Vec<_> = graph.compute_stream(&input, blocks.clone()).collect();
+    for stages in 1..=4 {
+        let (graph, input) = build_stream_graph();
+        assert_eq!(graph.compute_pipelined(&input, blocks.clone(), stages, 2), expected);
+    }
+}
+
+#[test]
+fn test_pipeline_backpressure() {
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let written = Arc::new(AtomicUsize::new(0));
+    let max_ahead = Arc::new(AtomicUsize::new(0));
+
+    let mut graph = ComputationGraph::<u64>::new();
+    let input = graph.insert_placeholder("input");
+    let mut double = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut double, &[&input]);
+    let sink_pulled = pulled.clone();
+    let sink_written = written.clone();
+    let sink_max_ahead = max_ahead.clone();
+    let mut slow_sink = graph.insert_node("slow_sink", Box::new(move |x| {
+        thread::sleep(Duration::from_millis(2));
+        let written = sink_written.fetch_add(1, Ordering::SeqCst) + 1;
+        let ahead = sink_pulled.load(Ordering::SeqCst) - written;
+        sink_max_ahead.fetch_max(ahead, Ordering::SeqCst);
+        *x[0]
+    }));
+    graph.set_inputs(&mut slow_sink, &[&double]);
+    graph.designate_output(&slow_sink);
+
+    let source_pulled = pulled.clone();
+    let blocks = (0..50).inspect(move |_| {
+        source_pulled.fetch_add(1, Ordering::SeqCst);
+    });
+    let outputs = graph.compute_pipelined(&input, blocks, 2, 1);
+    assert_eq!(outputs, (0..50).map(|i| i * 2).collect::<Vec<_>>());
+    // Two stages with three channels of one block each, plus one block held
+    // by each thread
+    assert!(max_ahead.load(Ordering::SeqCst) <= 6);
+}
+
+#[test]
+#[should_panic(expected = "Node prev cannot be evaluated in a pipeline")]
+fn test_pipeline_rejects_delay() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    let mut prev = graph.insert_delay("prev", 0);
+    let mut sum = graph.insert_node("sum", Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut sum, &[&input, &prev]);
+    graph.set_delay_source(&mut prev, &sum);
+    graph.designate_output(&sum);
+    graph.compute_pipelined(&input, 0..4, 2, 1);
+}
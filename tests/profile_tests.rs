@@ -0,0 +1,45 @@
+use dag_compute::{ComputationGraph, ManualClock, SchedulingStrategy};
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn test_profile_guided_order() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let clock = Arc::new(ManualClock::new());
+    let mut graph = ComputationGraph::<i32>::new();
+    graph.set_clock(clock.clone());
+    let cheap_log = log.clone();
+    let cheap = graph.insert_node("cheap".to_owned(), Box::new(move |_| {
+        cheap_log.lock().unwrap().push("cheap");
+        1
+    }));
+    let (slow_log, slow_clock) = (log.clone(), clock.clone());
+    let slow = graph.insert_node("slow".to_owned(), Box::new(move |_| {
+        slow_log.lock().unwrap().push("slow");
+        slow_clock.advance(Duration::from_millis(20));
+        2
+    }));
+    let mut slow_next = graph.insert_node("slow_next".to_owned(), Box::new(|x| x[0] * 10));
+    graph.set_inputs(&mut slow_next, &[&slow]);
+    let mut join = graph.insert_node("join".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut join, &[&cheap, &slow_next]);
+    graph.designate_output(&join);
+
+    let (value, report) = graph.compute_profiled();
+    assert_eq!(value, 21);
+    assert_eq!(report.timings.len(), 4);
+    assert_eq!(report.timings[0].node_name, "cheap");
+    assert_eq!(report.duration_of(graph.node_id(&slow)), Some(Duration::from_millis(20)));
+    assert_eq!(report.duration_of(graph.node_id(&cheap)), Some(Duration::ZERO));
+    assert_eq!(report.total, Duration::from_millis(20));
+
+    graph.apply_profile(&report);
+    assert_eq!(graph.scheduling_strategy(), SchedulingStrategy::DepthFirst);
+    graph.set_scheduling_strategy(SchedulingStrategy::ProfileGuided);
+    let (value, report) = graph.compute_profiled();
+    assert_eq!(value, 21);
+    let names: Vec<_> = report.timings.iter().map(|timing| timing.node_name.as_str()).collect();
+    assert_eq!(names, ["slow", "slow_next", "cheap", "join"]);
+    assert_eq!(*log.lock().unwrap(), ["cheap", "slow", "slow", "cheap"]);
+}
@@ -0,0 +1,54 @@
+use dag_compute::ComputationGraph;
+
+#[test]
+fn test_compute_traced() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 2));
+    let b = graph.insert_node("b".to_owned(), Box::new(|_| 3));
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x[0] + x[1]));
+    graph.set_inputs(&mut sum, &[&a, &b]);
+    graph.set_version_tag(&sum, "v2".to_owned());
+    graph.designate_output(&sum);
+
+    let traced = graph.compute_traced();
+    assert_eq!(traced.value, 5);
+    assert_eq!(traced.provenance.len(), 3);
+    let output = traced.output_provenance();
+    assert_eq!(output.node, graph.node_id(&sum));
+    assert_eq!(output.node_name, "sum");
+    assert_eq!(output.inputs, vec![graph.node_id(&a), graph.node_id(&b)]);
+    assert_eq!(output.version_tag.as_deref(), Some("v2"));
+    let input = traced.provenance_of(graph.node_id(&a)).unwrap();
+    assert_eq!(input.version_tag, None);
+    assert!(input.computed_at <= output.computed_at);
+    // The graph is not consumed
+    assert_eq!(graph.compute(), 5);
+}
+
+#[test]
+fn test_lineage_json() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("reading \"raw\"".to_owned());
+    let mut scaled = graph.insert_node("scaled".to_owned(), Box::new(|x| x[0] * 10));
+    graph.set_inputs(&mut scaled, &[&input]);
+    graph.set_version_tag(&scaled, "calib-3".to_owned());
+    graph.designate_output(&scaled);
+
+    let traced = graph.compute_traced_with([(&input, 4)]);
+    assert_eq!(traced.value, 40);
+    assert!(traced.provenance_of(graph.node_id(&input)).unwrap().external);
+    assert!(!traced.output_provenance().external);
+
+    let lineage: serde_json::Value =
+        serde_json::from_str(&traced.lineage_json("run-7")).unwrap();
+    assert_eq!(lineage["run"], "run-7");
+    assert_eq!(lineage["output"], graph.node_id(&scaled));
+    let nodes = lineage["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 2);
+    assert_eq!(nodes[0]["name"], "reading \"raw\"");
+    assert_eq!(nodes[0]["external"], true);
+    assert_eq!(nodes[0]["version_tag"], serde_json::Value::Null);
+    assert_eq!(nodes[1]["inputs"], serde_json::json!([graph.node_id(&input)]));
+    assert_eq!(nodes[1]["version_tag"], "calib-3");
+    assert!(nodes[1]["computed_at_unix_micros"].as_u64().unwrap() > 0);
+}
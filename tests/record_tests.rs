@@ -0,0 +1,47 @@
+use dag_compute::{ComputationGraph, TraceRecord};
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn trace_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dag_compute_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_record_and_replay() {
+    let dir = trace_dir("record_and_replay");
+    let source_runs = Arc::new(AtomicUsize::new(0));
+    let mut graph = ComputationGraph::<i64>::new();
+    let input = graph.insert_placeholder("input");
+    let counter = source_runs.clone();
+    let mut noisy = graph.insert_node("noisy".to_owned(), Box::new(move |x| {
+        // Differs on every run, like a node reading a changing source
+        x[0] + counter.fetch_add(1, Ordering::SeqCst) as i64
+    }));
+    graph.set_inputs(&mut noisy, &[&input]);
+    let mut scaled = graph.insert_unary_node("scaled", |x| x * 10);
+    graph.set_inputs(&mut scaled, &[&noisy]);
+    graph.designate_output(&scaled);
+
+    assert_eq!(graph.compute_recorded_with(&dir, [(&input, 4)]).unwrap(), 40);
+    let record: TraceRecord<i64> = TraceRecord::read(&dir, graph.node_id(&scaled)).unwrap();
+    assert_eq!(record.name, "scaled");
+    assert_eq!(record.inputs, [graph.node_id(&noisy)]);
+    assert_eq!(record.output, 40);
+    let record: TraceRecord<i64> = TraceRecord::read(&dir, graph.node_id(&input)).unwrap();
+    assert_eq!(record.output, 4);
+
+    // Replaying the placeholder alone re-runs noisy, which now differs
+    assert_eq!(graph.replay(&dir, &[]).unwrap(), 50);
+    assert_eq!(source_runs.load(Ordering::SeqCst), 2);
+    // Substituting noisy reproduces the recorded run without evaluating it
+    assert_eq!(graph.replay(&dir, &[&noisy]).unwrap(), 40);
+    assert_eq!(source_runs.load(Ordering::SeqCst), 2);
+
+    assert!(graph.replay(trace_dir("missing"), &[]).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,107 @@
+use dag_compute::{ComputationGraph, RetentionPolicy, RunOptions};
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[test]
+fn test_retention_policies() {
+    let source_runs = Arc::new(AtomicUsize::new(0));
+    let mut graph = ComputationGraph::<i32>::new();
+    let counter = source_runs.clone();
+    let source = graph.insert_node("source".to_owned(), Box::new(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        3
+    }));
+    let mut expensive = graph.insert_node("expensive".to_owned(), Box::new(|x| x[0] * 10));
+    graph.set_inputs(&mut expensive, &[&source]);
+    let mut scaled = graph.insert_node("scaled".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut scaled, &[&expensive]);
+    let mut out = graph.insert_node("out".to_owned(), Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut out, &[&scaled]);
+    graph.designate_output(&out);
+
+    assert_eq!(graph.retention(&scaled), RetentionPolicy::DropEagerly);
+    graph.set_retention(&expensive, RetentionPolicy::RetainForever);
+    graph.set_retention(&scaled, RetentionPolicy::RetainUntilEnd);
+
+    let (value, retained) = graph.compute_retaining();
+    assert_eq!(value, 62);
+    assert_eq!(retained.len(), 2);
+    assert_eq!(retained.get(&expensive), Some(&30));
+    assert_eq!(retained.get(&scaled), Some(&31));
+    assert_eq!(retained.get(&source), None);
+    assert_eq!(source_runs.load(Ordering::SeqCst), 1);
+
+    // The kept value of expensive spares evaluating source again
+    let (value, retained) = graph.compute_retaining();
+    assert_eq!(value, 62);
+    assert_eq!(retained.get(&expensive), Some(&30));
+    assert_eq!(source_runs.load(Ordering::SeqCst), 1);
+
+    graph.clear_retained();
+    assert_eq!(graph.compute_retaining().0, 62);
+    assert_eq!(source_runs.load(Ordering::SeqCst), 2);
+    assert_eq!(graph.compute(), 62);
+}
+
+#[test]
+fn test_run_options_combined() {
+    let loads = Arc::new(AtomicUsize::new(0));
+    let mut graph = ComputationGraph::<i32>::new();
+    let counter = loads.clone();
+    let table = graph.insert_node("table", Box::new(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        100
+    }));
+    graph.set_retention(&table, RetentionPolicy::RetainForever);
+    let x = graph.insert_placeholder("x");
+    let mut sum = graph.insert_binary_node("sum", |a, b| a + b);
+    graph.set_inputs(&mut sum, &[&table, &x]);
+    graph.set_retention(&sum, RetentionPolicy::RetainForever);
+    let mut upload = graph.insert_node("upload", Box::new(|_| panic!("Uploaded")));
+    graph.set_inputs(&mut upload, &[&sum]);
+    graph.add_tag(&upload, "upload");
+    graph.add_sink(&upload);
+    graph.designate_output(&sum);
+
+    let mut state = graph.new_state();
+    let mut evaluated = Vec::new();
+    for input in [1, 2] {
+        let output = graph.run(RunOptions::new()
+            .input(&x, input)
+            .filter(|tags| !tags.iter().any(|tag| tag == "upload"))
+            .in_state(&mut state)
+            .profiled());
+        // Values derived from a placeholder are never reused
+        assert_eq!(output.value, 100 + input);
+        assert_eq!(output.retained.get(&sum), Some(&(100 + input)));
+        assert_eq!(output.retained.get(&table), Some(&100));
+        evaluated.push(output.report.unwrap().timings.len());
+    }
+    // The kept table is not evaluated again
+    assert_eq!(evaluated[1], evaluated[0] - 1);
+    assert_eq!(loads.load(Ordering::SeqCst), 1);
+    assert_eq!(state.runs(), 2);
+}
+
+#[test]
+fn test_retained_values_invalidated() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let b = graph.insert_node("b", Box::new(|_| 2));
+    let mut double = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut double, &[&a]);
+    graph.set_retention(&double, RetentionPolicy::RetainForever);
+    graph.designate_output(&double);
+    assert_eq!(graph.compute_retaining().0, 2);
+
+    // Rewiring changes the version of the node
+    graph.set_inputs(&mut double, &[&b]);
+    assert_eq!(graph.compute_retaining().0, 4);
+
+    // As does changing its function along with its version tag
+    graph.override_node(&double, |x| x[0] * 3);
+    assert_eq!(graph.compute_retaining().0, 4);
+    graph.set_version_tag(&double, "v2".to_owned());
+    assert_eq!(graph.compute_retaining().0, 6);
+}
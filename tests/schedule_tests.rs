@@ -0,0 +1,92 @@
+use dag_compute::{ComputationGraph, SchedulingStrategy};
+
+use std::sync::{Arc, Mutex};
+
+// big feeds two consumers separated by an unrelated chain in depth-first order
+fn shared_input_graph(log: Arc<Mutex<Vec<&'static str>>>) -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let logged = |name: &'static str, func: fn(&[&i32]) -> i32| {
+        let log = log.clone();
+        Box::new(move |x: &[&i32]| {
+            log.lock().unwrap().push(name);
+            func(x)
+        })
+    };
+    let big = graph.insert_node("big".to_owned(), logged("big", |_| 100));
+    let mut first = graph.insert_node("first".to_owned(), logged("first", |x| x[0] + 1));
+    graph.set_inputs(&mut first, &[&big]);
+    let other = graph.insert_node("other".to_owned(), logged("other", |_| 5));
+    let mut other_next = graph.insert_node("other_next".to_owned(),
+        logged("other_next", |x| x[0] * 2));
+    graph.set_inputs(&mut other_next, &[&other]);
+    let mut second = graph.insert_node("second".to_owned(), logged("second", |x| x[0] - 1));
+    graph.set_inputs(&mut second, &[&big]);
+    let mut join = graph.insert_node("join".to_owned(),
+        logged("join", |x| x[0] + x[1] + x[2]));
+    graph.set_inputs(&mut join, &[&first, &other_next, &second]);
+    graph.designate_output(&join);
+    graph
+}
+
+#[test]
+fn test_depth_first_schedule() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let graph = shared_input_graph(log.clone());
+    assert_eq!(graph.scheduling_strategy(), SchedulingStrategy::DepthFirst);
+    assert_eq!(graph.compute(), 210);
+    assert_eq!(*log.lock().unwrap(),
+        vec!["big", "first", "other", "other_next", "second", "join"]);
+}
+
+#[test]
+fn test_locality_schedule() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let mut graph = shared_input_graph(log.clone());
+    graph.set_scheduling_strategy(SchedulingStrategy::Locality);
+    assert_eq!(graph.compute_iterations(1), vec![210]);
+    assert_eq!(graph.compute(), 210);
+    let expected = ["big", "first", "second", "other", "other_next", "join"];
+    let log = log.lock().unwrap();
+    assert_eq!(log[..6], expected[..]);
+    assert_eq!(log[6..], expected[..]);
+}
+
+// shared feeds a chain and a single node, both joined into the output
+fn shared_chain_graph(log: Arc<Mutex<Vec<&'static str>>>) -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let logged = |name: &'static str, func: fn(&[&i32]) -> i32| {
+        let log = log.clone();
+        Box::new(move |x: &[&i32]| {
+            log.lock().unwrap().push(name);
+            func(x)
+        })
+    };
+    let shared = graph.insert_node("shared", logged("shared", |_| 10));
+    let mut chain = graph.insert_node("chain", logged("chain", |x| x[0] + 1));
+    graph.set_inputs(&mut chain, &[&shared]);
+    let mut chain_next = graph.insert_node("chain_next", logged("chain_next", |x| x[0] * 2));
+    graph.set_inputs(&mut chain_next, &[&chain]);
+    let mut single = graph.insert_node("single", logged("single", |x| x[0] - 1));
+    graph.set_inputs(&mut single, &[&shared]);
+    let mut join = graph.insert_node("join", logged("join", |x| x[0] + x[1]));
+    graph.set_inputs(&mut join, &[&chain_next, &single]);
+    graph.designate_output(&join);
+    graph
+}
+
+#[test]
+fn test_locality_and_consumer_first_differ() {
+    for (strategy, expected) in [
+        // Both consumers of shared are evaluated before descending
+        (SchedulingStrategy::Locality, ["shared", "chain", "single", "chain_next", "join"]),
+        // The chain is followed before the other consumer of shared
+        (SchedulingStrategy::ConsumerFirst, ["shared", "chain", "chain_next", "single", "join"])
+    ] {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut graph = shared_chain_graph(log.clone());
+        graph.set_scheduling_strategy(strategy);
+        assert_eq!(graph.scheduling_strategy(), strategy);
+        assert_eq!(graph.compute_traced().value, 31);
+        assert_eq!(*log.lock().unwrap(), expected);
+    }
+}
@@ -0,0 +1,38 @@
+use dag_compute::{ComputationGraph, SizeHint};
+
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::sync::Arc;
+
+#[test]
+fn test_std_sizes() {
+    assert_eq!(5u32.approx_bytes(), 4);
+    let mut values: Vec<u64> = Vec::with_capacity(10);
+    values.extend([1, 2, 3]);
+    assert_eq!(values.approx_bytes(), size_of::<Vec<u64>>() + 10 * 8);
+    let nested = vec![String::from("abc"), String::with_capacity(16)];
+    assert_eq!(nested.approx_bytes(),
+        size_of::<Vec<String>>() + 2 * size_of::<String>() + 3 + 16);
+    assert_eq!(Some(7u8).approx_bytes(), size_of::<Option<u8>>());
+    assert_eq!(Arc::new([0u16; 4]).approx_bytes(), size_of::<Arc<[u16; 4]>>() + 8);
+    let map: HashMap<u32, u32> = [(1, 2)].into_iter().collect();
+    assert!(map.approx_bytes() >= size_of::<HashMap<u32, u32>>() + 8);
+    assert_eq!((1u8, 2u32).approx_bytes(), size_of::<(u8, u32)>());
+}
+
+#[test]
+fn test_profiled_sizes() {
+    let mut graph = ComputationGraph::<Vec<f32>>::new();
+    let source = graph.insert_node("source".to_owned(), Box::new(|_| vec![0.0; 100]));
+    let mut half = graph.insert_node("half".to_owned(), Box::new(|x| x[0][..50].to_vec()));
+    graph.set_inputs(&mut half, &[&source]);
+    graph.designate_output(&half);
+
+    let (value, report) = graph.compute_profiled_sized();
+    assert_eq!(value.len(), 50);
+    let vec_size = size_of::<Vec<f32>>();
+    assert_eq!(report.timings[0].output_bytes, Some(vec_size + 400));
+    assert_eq!(report.timings[1].output_bytes, Some(vec_size + 200));
+    let (_, report) = graph.compute_profiled();
+    assert_eq!(report.timings[0].output_bytes, None);
+}
@@ -0,0 +1,69 @@
+use dag_compute::ComputationGraph;
+
+use std::sync::mpsc;
+use std::thread;
+
+fn build_source_graph(left: Vec<i32>, right: Vec<i32>) -> ComputationGraph<i32> {
+    let mut graph = ComputationGraph::<i32>::new();
+    let left = graph.insert_source("left", left);
+    let right = graph.insert_source("right", right);
+    let mut running_sum = graph.insert_stateful_node("running_sum", 0,
+        |total: &mut i32, x| {
+            *total += *x[0];
+            *total
+        });
+    graph.set_inputs(&mut running_sum, &[&left]);
+    let mut product = graph.insert_node("product", Box::new(|x| x[0] * x[1]));
+    graph.set_inputs(&mut product, &[&running_sum, &right]);
+    graph.designate_output(&product);
+    graph
+}
+
+#[test]
+fn test_compute_sources() {
+    // Stops at the end of the shorter source
+    let graph = build_source_graph(vec![1, 2, 3, 4], vec![10, 20, 30]);
+    assert_eq!(graph.compute_sources(), vec![10, 60, 180]);
+    // Sources stay exhausted
+    assert_eq!(graph.compute_sources(), Vec::<i32>::new());
+}
+
+#[test]
+fn test_pipelined_sources() {
+    let left: Vec<_> = (0..50).collect();
+    let right: Vec<_> = (0..50).map(|i| i % 7).collect();
+    let expected = build_source_graph(left.clone(), right.clone()).compute_sources();
+    assert_eq!(expected.len(), 50);
+    for stages in 1..=3 {
+        let graph = build_source_graph(left.clone(), right.clone());
+        assert_eq!(graph.compute_sources_pipelined(stages, 2), expected);
+    }
+}
+
+#[test]
+fn test_channel_source() {
+    let (sender, receiver) = mpsc::channel();
+    let mut graph = ComputationGraph::<i32>::new();
+    let source = graph.insert_source("source", receiver);
+    let mut double = graph.insert_node("double", Box::new(|x| x[0] * 2));
+    graph.set_inputs(&mut double, &[&source]);
+    graph.designate_output(&double);
+
+    let producer = thread::spawn(move || {
+        for i in 0..5 {
+            sender.send(i).unwrap();
+        }
+    });
+    // Ends once the sender is dropped
+    assert_eq!(graph.compute_sources_pipelined(2, 1), vec![0, 2, 4, 6, 8]);
+    producer.join().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "Output does not depend on any source node")]
+fn test_no_sources() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let constant = graph.insert_node("constant", Box::new(|_| 1));
+    graph.designate_output(&constant);
+    graph.compute_sources();
+}
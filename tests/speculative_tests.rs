@@ -0,0 +1,144 @@
+use dag_compute::{ComputationGraph, NodeHandle};
+
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// The predicate only returns once both branches have started, which never
+// happens unless they run in parallel with it
+fn build_speculative(log: &Arc<Mutex<Vec<&'static str>>>, choice: bool)
+        -> (ComputationGraph<i32>, NodeHandle) {
+    let mut graph = ComputationGraph::<i32>::new();
+    let (started_sender, started_receiver) = mpsc::channel();
+    let started_receiver = Mutex::new(started_receiver);
+    let mut predicate = graph.insert_node("predicate", Box::new(move |_| {
+        let receiver = started_receiver.lock().unwrap();
+        for _ in 0..2 {
+            receiver.recv_timeout(Duration::from_secs(5))
+                .expect("Branches did not start in parallel");
+        }
+        choice as i32
+    }));
+    let base = graph.insert_node("base", Box::new(|_| 10));
+    graph.set_inputs(&mut predicate, &[&base]);
+    let then_branch = insert_branch(&mut graph, &base, "then", started_sender.clone(), log, 2);
+    let else_branch = insert_branch(&mut graph, &base, "else", started_sender, log, 3);
+    let mut select = graph.insert_select_node("select", |x| *x != 0);
+    graph.set_inputs(&mut select, &[&predicate, &then_branch, &else_branch]);
+    graph.set_speculative(&select, true);
+    graph.designate_output(&select);
+    (graph, select)
+}
+
+// A branch of two nodes, the first slow enough to still be running when the
+// predicate is known
+fn insert_branch(graph: &mut ComputationGraph<i32>, base: &NodeHandle, name: &'static str,
+        started: Sender<()>, log: &Arc<Mutex<Vec<&'static str>>>, factor: i32) -> NodeHandle {
+    let started = Mutex::new(started);
+    let slow_log = log.clone();
+    let mut slow = graph.insert_node(format!("{}_slow", name), Box::new(move |x| {
+        started.lock().unwrap().send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        slow_log.lock().unwrap().push(name);
+        x[0] * factor
+    }));
+    graph.set_inputs(&mut slow, &[base]);
+    let finish_log = log.clone();
+    let mut finish = graph.insert_node(format!("{}_finish", name), Box::new(move |x| {
+        finish_log.lock().unwrap().push(name);
+        x[0] + 1
+    }));
+    graph.set_inputs(&mut finish, &[&slow]);
+    finish
+}
+
+#[test]
+fn test_speculative_select() {
+    for (choice, expected, winner) in [(true, 21, "then"), (false, 31, "else")] {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let (graph, select) = build_speculative(&log, choice);
+        assert!(graph.is_speculative(&select));
+        assert_eq!(graph.compute_speculative(), expected);
+        assert_eq!(log.lock().unwrap().iter().filter(|name| **name == winner).count(), 2);
+        // The losing branch finishes its running node in the background but
+        // is cancelled after
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(log.lock().unwrap().len(), 3);
+    }
+}
+
+#[test]
+fn test_losing_branch_panic_ignored() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let predicate = graph.insert_node("predicate", Box::new(|_| {
+        thread::sleep(Duration::from_millis(20));
+        0
+    }));
+    let then_branch = graph.insert_node("then", Box::new(|_| panic!("Speculation failed")));
+    let else_branch = graph.insert_node("else", Box::new(|_| 4));
+    let mut select = graph.insert_select_node("select", |x| *x != 0);
+    graph.set_inputs(&mut select, &[&predicate, &then_branch, &else_branch]);
+    graph.set_speculative(&select, true);
+    let mut out = graph.insert_unary_node("out", |x| x * 2);
+    graph.set_inputs(&mut out, &[&select]);
+    graph.designate_output(&out);
+    assert_eq!(graph.compute_speculative(), 8);
+}
+
+#[test]
+fn test_losing_branch_not_awaited() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let (started_sender, started_receiver) = mpsc::channel();
+    let (release_sender, release_receiver) = mpsc::channel::<()>();
+    let (done_sender, done_receiver) = mpsc::channel();
+    let started_receiver = Mutex::new(started_receiver);
+    let predicate = graph.insert_node("predicate", Box::new(move |_| {
+        started_receiver.lock().unwrap().recv_timeout(Duration::from_secs(5)).unwrap();
+        0
+    }));
+    let (started_sender, release_receiver) = (Mutex::new(started_sender),
+        Mutex::new(release_receiver));
+    let done_sender = Mutex::new(done_sender);
+    // Only finishes once released after the run returned
+    let then_branch = graph.insert_node("then", Box::new(move |_| {
+        started_sender.lock().unwrap().send(()).unwrap();
+        let released = release_receiver.lock().unwrap()
+            .recv_timeout(Duration::from_secs(5)).is_ok();
+        done_sender.lock().unwrap().send(released).unwrap();
+        1
+    }));
+    let else_branch = graph.insert_node("else", Box::new(|_| 4));
+    let mut select = graph.insert_select_node("select", |x| *x != 0);
+    graph.set_inputs(&mut select, &[&predicate, &then_branch, &else_branch]);
+    graph.set_speculative(&select, true);
+    graph.designate_output(&select);
+    assert_eq!(graph.compute_speculative(), 4);
+    release_sender.send(()).unwrap();
+    assert!(done_receiver.recv().unwrap());
+}
+
+#[test]
+fn test_nested_select_stays_lazy() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let predicate = graph.insert_node("predicate", Box::new(|_| 1));
+    let guard = graph.insert_node("guard", Box::new(|_| 0));
+    let guarded = graph.insert_node("guarded", Box::new(|_| panic!("Guard ignored")));
+    let fallback = graph.insert_node("fallback", Box::new(|_| 5));
+    let mut inner = graph.insert_select_node("inner", |x| *x != 0);
+    graph.set_inputs(&mut inner, &[&guard, &guarded, &fallback]);
+    let else_branch = graph.insert_node("else", Box::new(|_| 7));
+    let mut select = graph.insert_select_node("select", |x| *x != 0);
+    graph.set_inputs(&mut select, &[&predicate, &inner, &else_branch]);
+    graph.set_speculative(&select, true);
+    graph.designate_output(&select);
+    assert_eq!(graph.compute_speculative(), 5);
+}
+
+#[test]
+#[should_panic(expected = "Node base is not a select node")]
+fn test_speculative_requires_select() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let base = graph.insert_node("base", Box::new(|_| 1));
+    graph.set_speculative(&base, true);
+}
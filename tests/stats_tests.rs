@@ -0,0 +1,62 @@
+use dag_compute::{ComputationGraph, StatsCollector};
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_stats_over_stream() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    // Every tenth block is slow
+    let mut slow = graph.insert_node("slow", Box::new(|x| {
+        if *x[0] % 10 == 9 {
+            thread::sleep(Duration::from_millis(20));
+        }
+        x[0] * 2
+    }));
+    graph.set_inputs(&mut slow, &[&input]);
+    graph.designate_output(&slow);
+    let slow_id = graph.node_id(&slow);
+    let collector = Arc::new(StatsCollector::new());
+    graph.add_listener(collector.clone());
+
+    let outputs = graph.compute_pipelined(&input, 0..20, 2, 2);
+    assert_eq!(outputs.len(), 20);
+    let report = collector.report();
+    assert_eq!(report.nodes.len(), 2);
+    let stats = report.stats_of(slow_id).unwrap();
+    assert_eq!(stats.node_name, "slow");
+    assert_eq!(stats.count, 20);
+    assert!(stats.max >= Duration::from_millis(20));
+    // Both slow runs are in the top 5%, so the 95th percentile is slow too
+    assert!(stats.p95 >= Duration::from_millis(20));
+    assert!(stats.mean >= Duration::from_millis(2));
+    assert!(stats.mean < stats.max);
+
+    collector.reset();
+    assert!(collector.report().nodes.is_empty());
+}
+
+#[test]
+fn test_stats_from_reports() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let mut b = graph.insert_node("b", Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut b, &[&a]);
+    graph.designate_output(&b);
+
+    let collector = StatsCollector::new();
+    for _ in 0..3 {
+        let (value, report) = graph.compute_profiled();
+        assert_eq!(value, 2);
+        collector.add_report(&report);
+    }
+    let report = collector.report();
+    let names: Vec<_> = report.nodes.iter().map(|stats| stats.node_name.as_str()).collect();
+    assert_eq!(names, vec!["a", "b"]);
+    for stats in report.nodes.iter() {
+        assert_eq!(stats.count, 3);
+        assert!(stats.mean <= stats.p95 && stats.p95 <= stats.max);
+    }
+}
@@ -0,0 +1,83 @@
+use dag_compute::ComputationGraph;
+
+#[test]
+fn test_stream_with_state() {
+    // Running sum over blocks, then scaled
+    let mut graph = ComputationGraph::<Vec<i32>>::new();
+    let input = graph.insert_placeholder("input".to_owned());
+    let mut running_sum = graph.insert_stateful_node(
+        "running_sum".to_owned(),
+        0,
+        |total: &mut i32, x| {
+            x[0].iter().map(|val| {
+                *total += val;
+                *total
+            }).collect()
+        }
+    );
+    graph.set_inputs(&mut running_sum, &[&input]);
+    let mut scale = graph.insert_node(
+        "scale".to_owned(),
+        Box::new(|x| x[0].iter().map(|val| val * 10).collect())
+    );
+    graph.set_inputs(&mut scale, &[&running_sum]);
+    graph.designate_output(&scale);
+
+    let blocks = vec![vec![1, 2], vec![3], vec![4, 5]];
+    let outputs: Vec<_> = graph.compute_stream(&input, blocks).collect();
+    assert_eq!(outputs, vec![vec![10, 30], vec![60], vec![100, 150]]);
+    // The graph remains usable and the state carries on
+    assert_eq!(graph.compute_with([(&input, vec![0])]), vec![150]);
+}
+
+#[test]
+fn test_delay_feedback() {
+    // y[n] = 1 + y[n-1]/2
+    let mut graph = ComputationGraph::<f64>::new();
+    let mut prev = graph.insert_delay("y[n-1]".to_owned(), 0.0);
+    let one = graph.insert_node("one".to_owned(), Box::new(|_| 1.0));
+    let mut y = graph.insert_node("y".to_owned(), Box::new(|x| x[0] + x[1] / 2.0));
+    graph.set_inputs(&mut y, &[&one, &prev]);
+    graph.set_delay_source(&mut prev, &y);
+    graph.designate_output(&y);
+    assert_eq!(graph.compute_iterations(3), vec![1.0, 1.5, 1.75]);
+}
+
+#[test]
+fn test_delay_source_outside_output() {
+    // The output only observes the delayed counter, not the counter itself
+    let mut graph = ComputationGraph::<i32>::new();
+    let mut prev = graph.insert_delay("prev".to_owned(), 0);
+    let mut counter = graph.insert_node("counter".to_owned(), Box::new(|x| x[0] + 1));
+    graph.set_inputs(&mut counter, &[&prev]);
+    graph.set_delay_source(&mut prev, &counter);
+    let mut observe = graph.insert_node("observe".to_owned(), Box::new(|x| x[0] * 10));
+    graph.set_inputs(&mut observe, &[&prev]);
+    graph.designate_output(&observe);
+    assert_eq!(graph.prune(), 0);
+    assert_eq!(graph.compute_iterations(3), vec![0, 10, 20]);
+}
+
+#[test]
+fn test_compute_until() {
+    // Newton's method for sqrt(2)
+    let mut graph = ComputationGraph::<f64>::new();
+    let guess = graph.insert_placeholder("guess".to_owned());
+    let mut step = graph.insert_node(
+        "newton_step".to_owned(),
+        Box::new(|x| (x[0] + 2.0 / x[0]) / 2.0)
+    );
+    graph.set_inputs(&mut step, &[&guess]);
+    graph.designate_output(&step);
+
+    let result = graph.compute_until(&guess, 1.0, 100,
+        |prev, next| (prev - next).abs() < 1e-12);
+    assert!(result.converged);
+    assert!(result.iterations < 10);
+    assert!((result.value - 2.0_f64.sqrt()).abs() < 1e-12);
+
+    let capped = graph.compute_until(&guess, 1.0, 2, |_, _| false);
+    assert!(!capped.converged);
+    assert_eq!(capped.iterations, 2);
+    assert!((capped.value - 17.0 / 12.0).abs() < 1e-12);
+}
@@ -0,0 +1,55 @@
+use dag_compute::ComputationGraph;
+
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_execution_summary() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a".to_owned(), Box::new(|_| 1));
+    let slow = graph.insert_node("slow".to_owned(), Box::new(|_| {
+        thread::sleep(Duration::from_millis(20));
+        2
+    }));
+    let c = graph.insert_node("c".to_owned(), Box::new(|_| 3));
+    let _unused = graph.insert_node("unused".to_owned(), Box::new(|_| 4));
+    let mut sum = graph.insert_node("sum".to_owned(), Box::new(|x| x[0] + x[1] + x[2]));
+    graph.set_inputs(&mut sum, &[&a, &slow, &c]);
+    graph.designate_output(&sum);
+    let slow_id = graph.node_id(&slow);
+
+    let (value, summary) = graph.compute_summarized();
+    assert_eq!(value, 6);
+    assert_eq!(summary.nodes_evaluated, 4);
+    assert_eq!(summary.nodes_swept, 1);
+    assert_eq!(summary.peak_live_values, 4);
+    assert_eq!(summary.slowest.len(), 4);
+    assert_eq!(summary.slowest[0].node, slow_id);
+    assert!(summary.wall_time >= summary.slowest[0].duration);
+
+    let text = summary.to_string();
+    assert!(text.starts_with("Evaluated 4 nodes (1 swept) in "));
+    assert!(text.contains("Peak live values: 4"));
+    assert!(text.contains(&format!("\n  slow ({}): ", slow_id)));
+}
+
+#[test]
+fn test_summary_with_inputs() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    let mut chain = graph.insert_unary_node("double", |x| x * 2);
+    graph.set_inputs(&mut chain, &[&input]);
+    for i in 0..6 {
+        let mut next = graph.insert_unary_node(format!("step{}", i), |x| x + 1);
+        graph.set_inputs(&mut next, &[&chain]);
+        chain = next;
+    }
+    graph.designate_output(&chain);
+
+    let (value, summary) = graph.compute_summarized_with([(&input, 5)]);
+    assert_eq!(value, 16);
+    assert_eq!(summary.nodes_evaluated, 8);
+    assert_eq!(summary.nodes_swept, 0);
+    assert_eq!(summary.slowest.len(), 5);
+    assert!(summary.peak_live_values <= 2);
+}
@@ -0,0 +1,70 @@
+use dag_compute::{ComputationGraph, SweepPolicy};
+
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_tag_lookup() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let b = graph.insert_node("b", Box::new(|_| 2));
+    graph.add_tag(&a, "upload");
+    graph.add_tag(&a, "slow");
+    graph.add_tag(&a, "upload");
+    graph.add_tag(&b, "slow");
+
+    assert_eq!(graph.tags(&a), ["upload", "slow"]);
+    let tagged: Vec<_> = graph.nodes_tagged("slow").iter()
+        .map(|handle| graph.node_name(handle).to_owned())
+        .collect();
+    assert_eq!(tagged, ["a", "b"]);
+    assert!(graph.nodes_tagged("missing").is_empty());
+}
+
+#[test]
+fn test_skip_tagged_sink() {
+    let uploaded = Arc::new(Mutex::new(Vec::new()));
+    let encoded = Arc::new(Mutex::new(0));
+    let build = || {
+        let mut graph = ComputationGraph::<i32>::new();
+        graph.set_sweep_policy(SweepPolicy::Fail);
+        let src = graph.insert_node("src", Box::new(|_| 3));
+        let encode_count = encoded.clone();
+        let mut encode = graph.insert_node("encode", Box::new(move |x| {
+            *encode_count.lock().unwrap() += 1;
+            x[0] * 2
+        }));
+        graph.set_inputs(&mut encode, &[&src]);
+        let upload_log = uploaded.clone();
+        let mut upload = graph.insert_node("upload", Box::new(move |x| {
+            upload_log.lock().unwrap().push(*x[0]);
+            0
+        }));
+        graph.set_inputs(&mut upload, &[&encode]);
+        graph.add_tag(&upload, "upload");
+        graph.designate_output(&src);
+        graph.add_sink(&upload);
+        graph
+    };
+
+    // The branch feeding only the skipped sink is not evaluated either
+    let skip_upload = |tags: &[String]| !tags.iter().any(|tag| tag == "upload");
+    assert_eq!(build().compute_with_filter(skip_upload), 3);
+    assert!(uploaded.lock().unwrap().is_empty());
+    assert_eq!(*encoded.lock().unwrap(), 0);
+
+    assert_eq!(build().compute_with_filter(|_| true), 3);
+    assert_eq!(*uploaded.lock().unwrap(), [6]);
+    assert_eq!(*encoded.lock().unwrap(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Node src is rejected by the tag filter")]
+fn test_filtered_dependency() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let src = graph.insert_node("src", Box::new(|_| 3));
+    let mut out = graph.insert_unary_node("out", |x| x + 1);
+    graph.set_inputs(&mut out, &[&src]);
+    graph.designate_output(&out);
+    graph.add_tag(&src, "remote");
+    graph.compute_with_filter(|tags| tags.is_empty());
+}
@@ -0,0 +1,50 @@
+use dag_compute::ComputationGraph;
+
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn test_tap_stream() {
+    let mut graph = ComputationGraph::<Vec<i32>>::new();
+    let input = graph.insert_placeholder("input");
+    let mut running_sum = graph.insert_stateful_node("running_sum", 0,
+        |total: &mut i32, x| {
+            x[0].iter().map(|val| {
+                *total += val;
+                *total
+            }).collect()
+        });
+    graph.set_inputs(&mut running_sum, &[&input]);
+    let mut scale = graph.insert_node("scale", Box::new(|x| x[0].iter().map(|val| val * 10).collect()));
+    graph.set_inputs(&mut scale, &[&running_sum]);
+    graph.designate_output(&scale);
+
+    let sums = graph.tap(&running_sum);
+    let monitor = thread::spawn(move || sums.iter().collect::<Vec<_>>());
+    let blocks = vec![vec![1, 2], vec![3], vec![4, 5]];
+    let outputs: Vec<_> = graph.compute_stream(&input, blocks).collect();
+    assert_eq!(outputs, vec![vec![10, 30], vec![60], vec![100, 150]]);
+    // Dropping the graph closes the channel
+    drop(graph);
+    let tapped = monitor.join().unwrap();
+    assert_eq!(tapped, vec![Arc::new(vec![1, 3]), Arc::new(vec![6]), Arc::new(vec![10, 15])]);
+}
+
+#[test]
+fn test_tap_chain() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let a = graph.insert_node("a", Box::new(|_| 1));
+    let mut b = graph.insert_unary_node("b", |x| x + 1);
+    graph.set_inputs(&mut b, &[&a]);
+    let mut c = graph.insert_unary_node("c", |x| x * 3);
+    graph.set_inputs(&mut c, &[&b]);
+    graph.designate_output(&c);
+
+    let source_values = graph.tap(&a);
+    let chained_values = graph.tap(&b);
+    assert_eq!(graph.compute_iterations(2), vec![6, 6]);
+    graph.clear_taps();
+    assert_eq!(graph.compute(), 6);
+    assert_eq!(source_values.iter().map(|v| *v).collect::<Vec<_>>(), [1, 1]);
+    assert_eq!(chained_values.iter().map(|v| *v).collect::<Vec<_>>(), [2, 2]);
+}
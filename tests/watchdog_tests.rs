@@ -0,0 +1,30 @@
+use dag_compute::{ComputationGraph, Watchdog};
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_watchdog_reports_slow_node() {
+    let mut graph = ComputationGraph::<i32>::new();
+    let fast = graph.insert_node("fast".to_owned(), Box::new(|_| 1));
+    let mut slow = graph.insert_node("slow".to_owned(), Box::new(|x| {
+        thread::sleep(Duration::from_millis(200));
+        x[0] + 1
+    }));
+    graph.set_inputs(&mut slow, &[&fast]);
+    graph.designate_output(&slow);
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let watchdog = Arc::new(Watchdog::new(2.0, Duration::from_millis(10)));
+    watchdog.set_check_interval(Duration::from_millis(5));
+    let callback_reported = reported.clone();
+    watchdog.set_callback(move |node, elapsed| {
+        assert!(elapsed > Duration::from_millis(20));
+        callback_reported.lock().unwrap().push(node.name.to_owned());
+    });
+    graph.add_listener(watchdog.clone());
+    assert_eq!(graph.compute(), 2);
+    drop(watchdog);
+    assert_eq!(*reported.lock().unwrap(), ["slow"]);
+}
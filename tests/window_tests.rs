@@ -0,0 +1,48 @@
+use dag_compute::{ComputationGraph, NodeHandle, Window};
+
+use std::thread;
+use std::time::Duration;
+
+fn build_window_graph(window: Window) -> (ComputationGraph<i32>, NodeHandle) {
+    let mut graph = ComputationGraph::<i32>::new();
+    let input = graph.insert_placeholder("input");
+    let mut sum = graph.insert_window_node("sum", window, |items| items.iter().sum());
+    graph.set_inputs(&mut sum, &[&input]);
+    graph.designate_output(&sum);
+    (graph, input)
+}
+
+#[test]
+fn test_sliding_window() {
+    let (graph, input) = build_window_graph(Window::Sliding(3));
+    let sums: Vec<_> = graph.compute_stream(&input, 1..=6).collect();
+    assert_eq!(sums, vec![1, 3, 6, 9, 12, 15]);
+}
+
+#[test]
+fn test_tumbling_window() {
+    let (graph, input) = build_window_graph(Window::Tumbling(3));
+    let sums: Vec<_> = graph.compute_stream(&input, 1..=7).collect();
+    assert_eq!(sums, vec![1, 3, 6, 4, 9, 15, 7]);
+}
+
+#[test]
+fn test_pipelined_window() {
+    let (graph, input) = build_window_graph(Window::Sliding(4));
+    let expected: Vec<_> = graph.compute_stream(&input, 0..40).collect();
+    let (graph, input) = build_window_graph(Window::Sliding(4));
+    assert_eq!(graph.compute_pipelined(&input, 0..40, 2, 1), expected);
+}
+
+#[test]
+fn test_time_window() {
+    let (graph, input) = build_window_graph(Window::Time(Duration::from_millis(5)));
+    let blocks = (1..=3).inspect(|_| thread::sleep(Duration::from_millis(20)));
+    // Every earlier item is older than the window
+    let sums: Vec<_> = graph.compute_stream(&input, blocks).collect();
+    assert_eq!(sums, vec![1, 2, 3]);
+
+    let (graph, input) = build_window_graph(Window::Time(Duration::from_secs(3600)));
+    let sums: Vec<_> = graph.compute_stream(&input, 1..=4).collect();
+    assert_eq!(sums, vec![1, 3, 6, 10]);
+}